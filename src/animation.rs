@@ -0,0 +1,268 @@
+/// L'animation des caméras et des objets : des valeurs clés données à des instants (en secondes),
+/// entre lesquelles les valeurs sont interpolées, et l'intervalle d'images à rendre.
+
+use std::ops::{Add, Sub, Mul};
+use math::Vector3f;
+
+/// Une valeur qui peut être interpolée entre deux clés.
+pub trait Animatable
+    : Copy + Add<Output = Self> + Sub<Output = Self> + Mul<f32, Output = Self> {
+}
+
+impl<T> Animatable for T
+    where T: Copy + Add<Output = T> + Sub<Output = T> + Mul<f32, Output = T>
+{
+}
+
+/// L'interpolation entre une clé et la suivante.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    /// Interpolation linéaire : la valeur change à vitesse constante.
+    Linear,
+    /// Courbe de Bézier cubique, dont les poignées sont calculées à partir des clés voisines :
+    /// la trajectoire est lisse au passage des clés, et la vitesse est nulle à la première et à la
+    /// dernière clé.
+    Bezier,
+}
+
+impl Default for Interpolation {
+    fn default() -> Self {
+        Interpolation::Linear
+    }
+}
+
+/// Une valeur clé : la valeur `value` à l'instant `time`, en secondes. `interpolation` est
+/// l'interpolation utilisée jusqu'à la clé suivante.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Keyframe<T> {
+    pub time: f32,
+    pub value: T,
+    #[serde(default)]
+    pub interpolation: Interpolation,
+}
+
+/// Une suite de clés, triées par instant croissant.
+pub type Track<T> = Vec<Keyframe<T>>;
+
+/// La valeur de la piste `track` à l'instant `time`, ou `None` si la piste est vide. Avant la
+/// première clé et après la dernière, la valeur est celle de la clé la plus proche.
+pub fn evaluate<T: Animatable>(track: &[Keyframe<T>], time: f32) -> Option<T> {
+    if track.is_empty() {
+        return None;
+    }
+    let last = track.len() - 1;
+    if time <= track[0].time {
+        return Some(track[0].value);
+    }
+    if time >= track[last].time {
+        return Some(track[last].value);
+    }
+    // La clé qui précède l'instant
+    let i = match track.iter().position(|key| key.time > time) {
+        Some(next) => next - 1,
+        None => last,
+    };
+    let (k0, k1) = (&track[i], &track[i + 1]);
+    let duration = k1.time - k0.time;
+    if duration <= 0.0 {
+        return Some(k1.value);
+    }
+    let t = (time - k0.time) / duration;
+    match k0.interpolation {
+        Interpolation::Linear => Some(k0.value + (k1.value - k0.value) * t),
+        Interpolation::Bezier => {
+            // Les poignées sont placées au tiers du segment, selon la tangente en chaque clé.
+            let p1 = k0.value + tangent(track, i) * (duration / 3.0);
+            let p2 = k1.value - tangent(track, i + 1) * (duration / 3.0);
+            let u = 1.0 - t;
+            Some(k0.value * (u * u * u) + p1 * (3.0 * u * u * t) + p2 * (3.0 * u * t * t) +
+                 k1.value * (t * t * t))
+        }
+    }
+}
+
+// La tangente (la dérivée par rapport au temps) de la piste à la clé `i` : la pente entre les
+// clés voisines, nulle aux extrémités de la piste.
+fn tangent<T: Animatable>(track: &[Keyframe<T>], i: usize) -> T {
+    let zero = track[i].value * 0.0;
+    if i == 0 || i + 1 == track.len() {
+        return zero;
+    }
+    let (previous, next) = (&track[i - 1], &track[i + 1]);
+    let duration = next.time - previous.time;
+    if duration <= 0.0 {
+        zero
+    } else {
+        (next.value - previous.value) * (1.0 / duration)
+    }
+}
+
+/// Les images à rendre pour une animation : de `frame_start` à `frame_end` inclus, à
+/// `frame_rate` images par seconde. L'image n est rendue à l'instant n / `frame_rate`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct FrameRange {
+    #[serde(default = "default_frame")]
+    pub frame_start: u32,
+    #[serde(default = "default_frame")]
+    pub frame_end: u32,
+    #[serde(default = "default_frame_rate")]
+    pub frame_rate: f32,
+}
+
+fn default_frame() -> u32 {
+    1
+}
+
+fn default_frame_rate() -> f32 {
+    24.0
+}
+
+impl Default for FrameRange {
+    fn default() -> Self {
+        FrameRange {
+            frame_start: default_frame(),
+            frame_end: default_frame(),
+            frame_rate: default_frame_rate(),
+        }
+    }
+}
+
+impl FrameRange {
+    /// L'instant de l'image `frame`, en secondes.
+    pub fn time(&self, frame: u32) -> f32 {
+        frame as f32 / self.frame_rate
+    }
+
+    /// La durée d'une image, en secondes.
+    pub fn frame_duration(&self) -> f32 {
+        1.0 / self.frame_rate
+    }
+
+    /// Vérifie que l'intervalle d'images n'est pas vide et que la fréquence est positive.
+    pub fn check(&self) -> Result<(), String> {
+        if self.frame_end < self.frame_start {
+            return Err(format!("The last frame ({}) is before the first one ({})",
+                               self.frame_end,
+                               self.frame_start));
+        }
+        if self.frame_rate <= 0.0 {
+            return Err(format!("The frame rate must be positive, got {}", self.frame_rate));
+        }
+        Ok(())
+    }
+}
+
+/// Les paramètres animés d'une caméra. Les pistes absentes ou vides laissent le paramètre
+/// inchangé.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CameraAnimation {
+    #[serde(default)]
+    pub world_position: Track<Vector3f>,
+    #[serde(default)]
+    pub target_position: Track<Vector3f>,
+    #[serde(default)]
+    pub fov: Track<f32>,
+    #[serde(default)]
+    pub focus_distance: Track<f32>,
+}
+
+/// Les paramètres animés d'un objet. La rotation est donnée en degrés selon les trois axes.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ObjectAnimation {
+    #[serde(default)]
+    pub position: Track<Vector3f>,
+    #[serde(default)]
+    pub scale: Track<Vector3f>,
+    #[serde(default)]
+    pub rotation: Track<Vector3f>,
+}
+
+/// Le chemin du fichier de l'image `frame`, d'après le motif `pattern`, où `{frame}` est remplacé
+/// par le numéro de l'image sur 4 chiffres. Si le motif ne le contient pas, le numéro est ajouté
+/// avant l'extension : `out.png` devient `out_0001.png`.
+pub fn frame_file_path(pattern: &str, frame: u32) -> String {
+    let number = format!("{:04}", frame);
+    if pattern.contains("{frame}") {
+        return pattern.replace("{frame}", &number);
+    }
+    match pattern.rfind('.') {
+        Some(dot) if !pattern[dot..].contains('/') => {
+            format!("{}_{}{}", &pattern[..dot], number, &pattern[dot..])
+        }
+        _ => format!("{}_{}", pattern, number),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use math::Vector3f;
+    use super::{Keyframe, Interpolation, FrameRange, evaluate, frame_file_path};
+
+    fn key(time: f32, value: f32, interpolation: Interpolation) -> Keyframe<f32> {
+        Keyframe {
+            time: time,
+            value: value,
+            interpolation: interpolation,
+        }
+    }
+
+    #[test]
+    fn test_linear_keyframes() {
+        let track = vec![key(0.0, 1.0, Interpolation::Linear),
+                         key(2.0, 5.0, Interpolation::Linear)];
+        assert_eq!(evaluate(&track, -1.0), Some(1.0));
+        assert_eq!(evaluate(&track, 0.5), Some(2.0));
+        assert_eq!(evaluate(&track, 3.0), Some(5.0));
+        assert_eq!(evaluate::<f32>(&[], 1.0), None);
+
+        let positions = vec![Keyframe {
+                                 time: 0.0,
+                                 value: Vector3f::new(0.0, 0.0, 0.0),
+                                 interpolation: Interpolation::Linear,
+                             },
+                             Keyframe {
+                                 time: 1.0,
+                                 value: Vector3f::new(2.0, 4.0, 0.0),
+                                 interpolation: Interpolation::Linear,
+                             }];
+        assert_eq!(evaluate(&positions, 0.25), Some(Vector3f::new(0.5, 1.0, 0.0)));
+    }
+
+    #[test]
+    fn test_bezier_keyframes() {
+        let track = vec![key(0.0, 0.0, Interpolation::Bezier),
+                         key(1.0, 1.0, Interpolation::Bezier),
+                         key(2.0, 2.0, Interpolation::Bezier)];
+        // La courbe passe par les clés, part et arrive avec une vitesse nulle...
+        assert!((evaluate(&track, 1.0).unwrap() - 1.0).abs() < 1e-6);
+        assert!(evaluate(&track, 0.05).unwrap() < 0.05);
+        assert!(evaluate(&track, 1.95).unwrap() > 1.95);
+        // ...et est lisse au passage de la clé du milieu.
+        let before = evaluate(&track, 0.99).unwrap();
+        let after = evaluate(&track, 1.01).unwrap();
+        assert!(((after - before) / 0.02 - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_frame_range_check() {
+        let range = |start: u32, end: u32, rate: f32| {
+            FrameRange {
+                frame_start: start,
+                frame_end: end,
+                frame_rate: rate,
+            }
+        };
+        assert!(range(1, 1, 24.0).check().is_ok());
+        assert!(range(1, 48, 24.0).check().is_ok());
+        assert!(range(10, 9, 24.0).check().is_err());
+        assert!(range(1, 48, 0.0).check().is_err());
+        assert!(range(1, 48, -24.0).check().is_err());
+    }
+
+    #[test]
+    fn test_frame_file_path() {
+        assert_eq!(frame_file_path("renders/out.png", 1), "renders/out_0001.png");
+        assert_eq!(frame_file_path("frame{frame}.png", 42), "frame0042.png");
+        assert_eq!(frame_file_path("./out", 3), "./out_0003");
+    }
+}
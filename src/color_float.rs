@@ -32,6 +32,18 @@ impl LinearColor {
         self.internal_color.clamp();
     }
 
+    pub fn is_black(&self) -> bool {
+        self.internal_color.r == 0.0 && self.internal_color.g == 0.0 &&
+        self.internal_color.b == 0.0
+    }
+
+    /// La luminance de la couleur, avec les coefficients des primaires Rec. 709 (celles de
+    /// sRGB).
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.internal_color.r + 0.7152 * self.internal_color.g +
+        0.0722 * self.internal_color.b
+    }
+
     pub fn get_internal_color(&self) -> &FloatColor {
         &self.internal_color
     }
@@ -90,6 +102,9 @@ impl<'de> Deserialize<'de> for RGBColor {
             R,
             G,
             B,
+            // La composante alpha est acceptée pour la compatibilité avec les scènes existantes,
+            // mais elle est ignorée.
+            A,
         }
 
         struct RGBColorVisitor;
@@ -128,6 +143,9 @@ impl<'de> Deserialize<'de> for RGBColor {
                             }
                             b = Some(map.next_value()?);
                         }
+                        Field::A => {
+                            map.next_value::<u8>()?;
+                        }
                     }
                 }
                 let r: u8 = r.ok_or_else(|| de::Error::missing_field("r"))?;
@@ -174,8 +192,8 @@ impl Mul<f32> for LinearColor {
     fn mul(self, other: f32) -> Self::Output {
         LinearColor {
             internal_color: FloatColor::new(self.internal_color.r * other,
-                                            self.internal_color.b * other,
-                                            self.internal_color.g * other),
+                                            self.internal_color.g * other,
+                                            self.internal_color.b * other),
         }
     }
 }
@@ -196,33 +214,74 @@ impl Div<f32> for LinearColor {
     fn div(self, other: f32) -> Self::Output {
         LinearColor {
             internal_color: FloatColor::new(self.internal_color.r / other,
-                                            self.internal_color.b / other,
-                                            self.internal_color.g / other),
+                                            self.internal_color.g / other,
+                                            self.internal_color.b / other),
         }
     }
 }
 
-const GAMMA: f32 = 2.2;
-const INV_GAMMA: f32 = 1f32 / GAMMA;
 
-/// Conversion entre l'espace `sRGB` et l'espace linéaire.
+/// Décodage de l'espace `sRGB` vers l'espace linéaire.
 impl Into<LinearColor> for RGBColor {
     fn into(self) -> LinearColor {
         LinearColor {
-            internal_color: FloatColor::new(self.internal_color.r.powf(INV_GAMMA).min(1f32),
-                                            self.internal_color.g.powf(INV_GAMMA).min(1f32),
-                                            self.internal_color.b.powf(INV_GAMMA).min(1f32)),
+            internal_color: FloatColor::new(srgb_to_linear(self.internal_color.r),
+                                            srgb_to_linear(self.internal_color.g),
+                                            srgb_to_linear(self.internal_color.b)),
         }
     }
 }
 
-/// Conversion entre un espace linéaire et l'espace `sRGB`.
+/// Encodage de l'espace linéaire vers l'espace `sRGB`. La couleur est bornée à [0, 1].
 impl Into<RGBColor> for LinearColor {
     fn into(self) -> RGBColor {
         RGBColor {
-            internal_color: FloatColor::new(self.internal_color.r.powf(GAMMA).min(1f32),
-                                            self.internal_color.g.powf(GAMMA).min(1f32),
-                                            self.internal_color.b.powf(GAMMA).min(1f32)),
+            internal_color: FloatColor::new(linear_to_srgb(self.internal_color.r),
+                                            linear_to_srgb(self.internal_color.g),
+                                            linear_to_srgb(self.internal_color.b)),
+        }
+    }
+}
+
+/// La fonction de transfert inverse de `sRGB` : d'une composante encodée dans [0, 1] à sa valeur
+/// linéaire.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    let value = value.max(0.0).min(1.0);
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// La fonction de transfert de `sRGB` : d'une composante linéaire, bornée à [0, 1], à sa valeur
+/// encodée.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    let value = value.max(0.0).min(1.0);
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// L'espace de couleur dans lequel sont stockés les pixels d'une image 8 bits.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ColorSpace {
+    /// Les composantes sont encodées par la fonction de transfert `sRGB` : c'est le cas des
+    /// images de couleur (photos, textures diffuses).
+    SRGB,
+    /// Les composantes sont stockées telles quelles : c'est le cas des textures de données
+    /// (normales, rugosité, métal).
+    Linear,
+}
+
+impl ColorSpace {
+    /// La couleur linéaire d'un pixel stocké dans cet espace.
+    pub fn decode(&self, pixel: (u8, u8, u8, u8)) -> LinearColor {
+        match *self {
+            ColorSpace::SRGB => RGBColor { internal_color: pixel.into() }.into(),
+            ColorSpace::Linear => LinearColor { internal_color: pixel.into() },
         }
     }
 }
@@ -232,6 +291,8 @@ const STEP: f32 = 1f32 / 255f32;
 const INV_STEP: f32 = 255f32;
 
 // TODO faire une macro
+// Les conversions avec les entiers ne font que changer d'échelle, sans fonction de transfert : la
+// conversion d'espace se fait entre `RGBColor` et `LinearColor`, ou avec `ColorSpace`.
 // conversion vers (u8, u8, u8)
 impl Into<(u8, u8, u8)> for RGBColor {
     fn into(self) -> (u8, u8, u8) {
@@ -290,10 +351,10 @@ impl Into<LinearColor> for (u8, u8, u8, u8) {
 
 impl Into<(u8, u8, u8)> for FloatColor {
     fn into(self) -> (u8, u8, u8) {
-        // On se raméne entre 0 et 255
-        let r = 255f32.min(self.r * INV_STEP);
-        let g = 255f32.min(self.g * INV_STEP);
-        let b = 255f32.min(self.b * INV_STEP);
+        // On se raméne entre 0 et 255, à l'entier le plus proche
+        let r = 255f32.min((self.r * INV_STEP).round());
+        let g = 255f32.min((self.g * INV_STEP).round());
+        let b = 255f32.min((self.b * INV_STEP).round());
         (r as u8, g as u8, b as u8)
     }
 }
@@ -308,10 +369,10 @@ impl Into<FloatColor> for (u8, u8, u8) {
 
 impl Into<(u8, u8, u8, u8)> for FloatColor {
     fn into(self) -> (u8, u8, u8, u8) {
-        // On se raméne entre 0 et 255
-        let r = 255f32.min(self.r * INV_STEP);
-        let g = 255f32.min(self.g * INV_STEP);
-        let b = 255f32.min(self.b * INV_STEP);
+        // On se raméne entre 0 et 255, à l'entier le plus proche
+        let r = 255f32.min((self.r * INV_STEP).round());
+        let g = 255f32.min((self.g * INV_STEP).round());
+        let b = 255f32.min((self.b * INV_STEP).round());
         (r as u8, g as u8, b as u8, 255u8)
     }
 }
@@ -450,4 +511,26 @@ mod test {
         println!("{:?}", k);
         assert_eq!(k.0, 127u8);
     }
+
+    #[test]
+    fn test_srgb_transfer_function() {
+        assert_eq!(srgb_to_linear(0.0), 0.0);
+        assert!((srgb_to_linear(1.0) - 1.0).abs() < 1e-6);
+        assert!((srgb_to_linear(0.5) - 0.214_041).abs() < 1e-5);
+        assert!((linear_to_srgb(0.5) - 0.735_357).abs() < 1e-5);
+        for i in 0..101 {
+            let value = i as f32 / 100.0;
+            assert!((srgb_to_linear(linear_to_srgb(value)) - value).abs() < 1e-5);
+        }
+        // L'encodage borne les valeurs hors de [0, 1].
+        assert_eq!(linear_to_srgb(4.0), linear_to_srgb(1.0));
+
+        let srgb = ColorSpace::SRGB.decode((128, 128, 128, 255));
+        let linear = ColorSpace::Linear.decode((128, 128, 128, 255));
+        assert!((srgb.get_internal_color().r - srgb_to_linear(128.0 / 255.0)).abs() < 1e-6);
+        assert!((linear.get_internal_color().r - 128.0 / 255.0).abs() < 1e-6);
+        let color: RGBColor = srgb.into();
+        let encoded: (u8, u8, u8) = color.into();
+        assert!(encoded.0 == 127 || encoded.0 == 128);
+    }
 }
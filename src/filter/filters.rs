@@ -54,22 +54,10 @@ impl Default for MitchellFilter {
 }
 
 impl Filter for MitchellFilter {
-    // TODO : Accélerer ce calcul ?
-    fn compute_color(&self, data: &Pixel, pixel_position: (u32, u32)) -> LinearColor {
+    fn accumulate(&self, data: &Pixel, pixel_position: (u32, u32)) -> (LinearColor, f32) {
         let mut result: LinearColor = LinearColor::new_black();
         let mut weight_sum: f32 = 0.0;
         // On calcule les contributions de chaque sample
-        for sample in data.samples() {
-            // La position exprimée dans le système de coordonnée de l'image
-            let absolute_sample_pos = sample.position();
-            // On ramène la valeur pour la mettre au centre du pixel concerné
-            let relative_sample_pixel_pos =
-                Vector2f::new(absolute_sample_pos.x - data.x() as f32 - pixel_position.0 as f32 -
-                              0.5,
-                              absolute_sample_pos.y - data.y() as f32 - pixel_position.1 as f32 -
-                              0.5);
-            weight_sum += self.weight_contribution(relative_sample_pixel_pos);
-        }
         for sample in data.samples() {
             // La position exprimée dans le système de coordonnée de l'image
             let absolute_sample_pos = sample.position();
@@ -80,9 +68,10 @@ impl Filter for MitchellFilter {
                               absolute_sample_pos.y - data.y() as f32 - pixel_position.1 as f32 -
                               0.5);
             let weight = self.weight_contribution(relative_sample_pixel_pos);
-            result += &(sample.color * (weight / weight_sum));
+            result += &(sample.color * weight);
+            weight_sum += weight;
         }
-        result
+        (result, weight_sum)
     }
 }
 
@@ -92,13 +81,13 @@ pub struct BoxFilter {}
 
 
 impl Filter for BoxFilter {
-    fn compute_color(&self, data: &Pixel, _: (u32, u32)) -> LinearColor {
+    fn accumulate(&self, data: &Pixel, _: (u32, u32)) -> (LinearColor, f32) {
         let mut result: LinearColor = LinearColor::new_black();
-        let sum: u32 = data.samples().fold(0, |acc, _| acc + 1);
+        let mut sum: u32 = 0;
         for sample in data.samples() {
-            result += &(sample.color / sum as f32);
+            result += &sample.color;
+            sum += 1;
         }
-        result
-
+        (result, sum as f32)
     }
 }
@@ -6,7 +6,19 @@ use filter::filters::{BoxFilter, MitchellFilter};
 
 /** Un trait qui représente un filtre */
 pub trait Filter {
-    fn compute_color(&self, data: &Pixel, position: (u32, u32)) -> LinearColor;
+    /// La somme des couleurs des samples du pixel pondérées par le filtre, et la somme des
+    /// poids. Les deux sommes sont accumulées dans le `Film`, qui ne les divise qu'à l'export.
+    fn accumulate(&self, data: &Pixel, position: (u32, u32)) -> (LinearColor, f32);
+
+    /// La couleur reconstruite du pixel : la moyenne des samples pondérée par le filtre.
+    fn compute_color(&self, data: &Pixel, position: (u32, u32)) -> LinearColor {
+        let (color, weight) = self.accumulate(data, position);
+        if weight == 0.0 {
+            LinearColor::new_black()
+        } else {
+            color / weight
+        }
+    }
 }
 
 #[derive(Serialize,Deserialize, Debug)]
@@ -9,6 +9,8 @@ pub struct BoundingBox {
     max: Vector3f,
 }
 
+const ROBUST_FACTOR: f32 = 1.0 + 1e-6;
+
 fn new_min() -> Vector3f {
     Vector3f::new(f32::MAX, f32::MAX, f32::MAX)
 }
@@ -35,8 +37,15 @@ impl BoundingBox {
 
         result
     }
+    // Crée la Bounding Box d'un unique triangle
+    pub fn new_from_triangle(tri: &Triangle) -> Self {
+        let mut result = BoundingBox::new();
+        result.add_triangle(tri);
+        result
+    }
+
     // Ajoute un point à une Bounding Box
-    fn add_point(&mut self, b: Vector3f) {
+    pub fn add_point(&mut self, b: Vector3f) {
 
         self.min = Vector3f::new(f32::min(self.min.x, b.x),
                                  f32::min(self.min.y, b.y),
@@ -61,6 +70,69 @@ impl BoundingBox {
         }
     }
 
+    // Agrandit la Bounding Box pour qu'elle contienne aussi `other`
+    pub fn add_bounding_box(&mut self, other: &BoundingBox) {
+        if !other.is_empty() {
+            self.add_point(other.min);
+            self.add_point(other.max);
+        }
+    }
+
+    // Renvoie l'union de deux Bounding Box
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        let mut result = self.clone();
+        result.add_bounding_box(other);
+        result
+    }
+
+    // Une Bounding Box vide ne contient aucun point (min > max)
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn min(&self) -> Vector3f {
+        self.min
+    }
+
+    pub fn max(&self) -> Vector3f {
+        self.max
+    }
+
+    // Le centre de la Bounding Box
+    pub fn centroid(&self) -> Vector3f {
+        (self.min + self.max) * 0.5
+    }
+
+    // Renvoie la composante de `v` selon l'axe `axis` (0 : x, 1 : y, 2 : z)
+    pub fn axis_value(v: &Vector3f, axis: usize) -> f32 {
+        match axis {
+            0 => v.x,
+            1 => v.y,
+            _ => v.z,
+        }
+    }
+
+    // L'axe (0 : x, 1 : y, 2 : z) selon lequel la Bounding Box est la plus étendue
+    pub fn maximum_extent(&self) -> usize {
+        let diagonal = self.max - self.min;
+        if diagonal.x > diagonal.y && diagonal.x > diagonal.z {
+            0
+        } else if diagonal.y > diagonal.z {
+            1
+        } else {
+            2
+        }
+    }
+
+    // L'aire de la surface de la Bounding Box, utilisée par l'heuristique SAH
+    pub fn surface_area(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let d = self.max - self.min;
+        2.0 * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+
 
     // Algorithme issue de : http://people.csail.mit.edu/amy/papers/box-jgt.pdf
     fn fast_intersect(&self, ray: &Ray) -> bool {
@@ -126,6 +198,45 @@ impl BoundingBox {
     pub fn intersects(&self, ray: &Ray) -> bool {
         self.fast_intersect(ray)
     }
+
+    // Méthode des "slabs" : on renvoie la distance (paramètre du rayon) à laquelle le rayon entre
+    // dans la Bounding Box, à condition que cette entrée soit devant l'origine du rayon et avant
+    // `ray.max_t` (lorsque celui-ci est positif).
+    pub fn intersection_distance(&self, ray: &Ray) -> Option<f32> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let origin = ray.origin();
+        let inv_slope = ray.inv_slope();
+
+        let mut t0: f32 = 0.0;
+        let mut t1: f32 = if ray.max_t > 0.0 { ray.max_t } else { f32::MAX };
+
+        for axis in 0..3 {
+            let inv = BoundingBox::axis_value(&inv_slope, axis);
+            let o = BoundingBox::axis_value(&origin, axis);
+            let mut t_near = (BoundingBox::axis_value(&self.min, axis) - o) * inv;
+            let mut t_far = (BoundingBox::axis_value(&self.max, axis) - o) * inv;
+            if t_near > t_far {
+                ::std::mem::swap(&mut t_near, &mut t_far);
+            }
+            // On élargit légèrement l'intervalle pour ne pas rater les boîtes plates (un plan
+            // aligné sur un axe par exemple) à cause des erreurs d'arrondi.
+            t_far *= ROBUST_FACTOR;
+            // Les comparaisons sont écrites de façon à ignorer les NaN (0 * infini)
+            if t_near > t0 {
+                t0 = t_near;
+            }
+            if t_far < t1 {
+                t1 = t_far;
+            }
+            if t0 > t1 {
+                return None;
+            }
+        }
+        Some(t0)
+    }
 }
 
 impl Default for BoundingBox {
@@ -0,0 +1,534 @@
+/// Une hiérarchie de volumes englobants (Bounding Volume Hierarchy), construite selon
+/// l'heuristique SAH (Surface Area Heuristic).
+/// Le BVH ne connait pas la nature des primitives qu'il contient : il ne manipule que leurs
+/// Bounding Box et leurs indices. C'est à l'appelant de fournir le test d'intersection d'une
+/// primitive lors de la traversée.
+
+use geometry::bounding_box::BoundingBox;
+use math::Vector3f;
+use ray::Ray;
+use std::cmp::{self, Ordering};
+use std::f32;
+
+/// Le nombre de "seaux" utilisés pour évaluer l'heuristique SAH.
+const SAH_BUCKETS: usize = 12;
+
+/// Le coût de la traversée d'un noeud, relativement au coût d'un test d'intersection.
+const TRAVERSAL_COST: f32 = 0.125;
+
+/// Les différentes structures d'accélération utilisables pour calculer les intersections.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum AccelerationStructure {
+    /// On teste tous les objets les uns après les autres.
+    Linear,
+    /// Une structure à deux niveaux : un BVH par mesh construit dans l'espace de l'objet, et un
+    /// BVH sur les objets de la scène.
+    BVH { max_primitives_in_node: usize },
+}
+
+impl Default for AccelerationStructure {
+    fn default() -> Self {
+        AccelerationStructure::BVH { max_primitives_in_node: 4 }
+    }
+}
+
+/// Un noeud du BVH. Les noeuds sont stockés en profondeur d'abord : le premier enfant d'un noeud
+/// intérieur est toujours le noeud qui le suit directement dans le vecteur.
+#[derive(Debug, Clone)]
+enum BVHNode {
+    Leaf {
+        bbox: BoundingBox,
+        first_primitive: usize,
+        primitive_count: usize,
+    },
+    Interior {
+        bbox: BoundingBox,
+        second_child: usize,
+        axis: usize,
+    },
+}
+
+impl BVHNode {
+    fn bbox(&self) -> &BoundingBox {
+        match *self {
+            BVHNode::Leaf { ref bbox, .. } |
+            BVHNode::Interior { ref bbox, .. } => bbox,
+        }
+    }
+}
+
+/// Les informations sur une primitive nécessaires à la construction.
+struct BuildPrimitive {
+    index: usize,
+    bbox: BoundingBox,
+    centroid: Vector3f,
+}
+
+#[derive(Debug, Clone)]
+pub struct BVH {
+    nodes: Vec<BVHNode>,
+    // Les indices des primitives, rangés de manière à ce que chaque feuille corresponde à une
+    // tranche contigüe.
+    primitives: Vec<usize>,
+}
+
+impl BVH {
+    /// Un BVH vide, qui n'intersecte jamais rien.
+    pub fn new_empty() -> Self {
+        BVH {
+            nodes: vec![],
+            primitives: vec![],
+        }
+    }
+
+    /// Construit un BVH à partir des Bounding Box des primitives. La primitive `i` est celle dont
+    /// la Bounding Box est `bboxes[i]`. Les primitives dont la Bounding Box est vide sont rangées
+    /// ensemble dans une feuille à part, qui n'est jamais touchée tant qu'elle reste vide : un
+    /// `refit` peut ainsi les faire apparaître plus tard.
+    pub fn new(bboxes: &[BoundingBox], max_primitives_in_node: usize) -> Self {
+        let (mut build_primitives, empty_primitives): (Vec<BuildPrimitive>, Vec<BuildPrimitive>) =
+            bboxes.iter()
+                .enumerate()
+                .map(|(index, bbox)| {
+                    BuildPrimitive {
+                        index: index,
+                        bbox: bbox.clone(),
+                        centroid: bbox.centroid(),
+                    }
+                })
+                .partition(|prim| !prim.bbox.is_empty());
+
+        let mut result = BVH::new_empty();
+        match (build_primitives.is_empty(), empty_primitives.is_empty()) {
+            (true, true) => {}
+            (true, false) => {
+                result.push_leaf(BoundingBox::new(), &empty_primitives);
+            }
+            (false, true) => {
+                result.build_recursive(&mut build_primitives,
+                                       cmp::max(1, max_primitives_in_node));
+            }
+            (false, false) => {
+                // La racine a pour enfants l'arbre des primitives non vides et la feuille des
+                // primitives vides.
+                result.nodes.push(BVHNode::Interior {
+                                      bbox: BoundingBox::new(),
+                                      second_child: 0,
+                                      axis: 0,
+                                  });
+                result.build_recursive(&mut build_primitives,
+                                       cmp::max(1, max_primitives_in_node));
+                let bbox = result.nodes[1].bbox().clone();
+                let second_child = result.push_leaf(BoundingBox::new(), &empty_primitives);
+                result.nodes[0] = BVHNode::Interior {
+                    bbox: bbox,
+                    second_child: second_child,
+                    axis: 0,
+                };
+            }
+        }
+        result
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// La Bounding Box de l'ensemble des primitives du BVH.
+    pub fn bounding_box(&self) -> BoundingBox {
+        match self.nodes.first() {
+            Some(node) => node.bbox().clone(),
+            None => BoundingBox::new(),
+        }
+    }
+
+    fn push_leaf(&mut self, bbox: BoundingBox, primitives: &[BuildPrimitive]) -> usize {
+        let first_primitive = self.primitives.len();
+        for prim in primitives {
+            self.primitives.push(prim.index);
+        }
+        self.nodes.push(BVHNode::Leaf {
+                            bbox: bbox,
+                            first_primitive: first_primitive,
+                            primitive_count: primitives.len(),
+                        });
+        self.nodes.len() - 1
+    }
+
+    /// Construit récursivement le sous arbre contenant `primitives` et renvoie l'indice de sa
+    /// racine.
+    fn build_recursive(&mut self,
+                       primitives: &mut [BuildPrimitive],
+                       max_primitives_in_node: usize)
+                       -> usize {
+        let mut bbox = BoundingBox::new();
+        let mut centroid_bbox = BoundingBox::new();
+        for prim in primitives.iter() {
+            bbox.add_bounding_box(&prim.bbox);
+            centroid_bbox.add_point(prim.centroid);
+        }
+
+        let count = primitives.len();
+        if count == 1 {
+            return self.push_leaf(bbox, primitives);
+        }
+
+        let axis = centroid_bbox.maximum_extent();
+        let c_min = BoundingBox::axis_value(&centroid_bbox.min(), axis);
+        let c_max = BoundingBox::axis_value(&centroid_bbox.max(), axis);
+
+        // Tous les centres sont confondus : il n'y a aucune manière intelligente de découper.
+        if c_max <= c_min {
+            return self.push_leaf(bbox, primitives);
+        }
+
+        let bucket_of = |prim: &BuildPrimitive| -> usize {
+            let relative = (BoundingBox::axis_value(&prim.centroid, axis) - c_min) /
+                           (c_max - c_min);
+            cmp::min((relative * SAH_BUCKETS as f32) as usize, SAH_BUCKETS - 1)
+        };
+
+        // On répartit les primitives dans les seaux
+        let mut counts = [0usize; SAH_BUCKETS];
+        let mut bounds: Vec<BoundingBox> = vec![BoundingBox::new(); SAH_BUCKETS];
+        for prim in primitives.iter() {
+            let bucket = bucket_of(prim);
+            counts[bucket] += 1;
+            bounds[bucket].add_bounding_box(&prim.bbox);
+        }
+
+        // On évalue le coût d'une coupe après chacun des seaux
+        let total_area = bbox.surface_area();
+        let mut best_cost = f32::MAX;
+        let mut best_split = 0;
+        for split in 0..(SAH_BUCKETS - 1) {
+            let mut bbox_below = BoundingBox::new();
+            let mut bbox_above = BoundingBox::new();
+            let mut count_below = 0;
+            let mut count_above = 0;
+            for bucket in 0..(split + 1) {
+                bbox_below.add_bounding_box(&bounds[bucket]);
+                count_below += counts[bucket];
+            }
+            for bucket in (split + 1)..SAH_BUCKETS {
+                bbox_above.add_bounding_box(&bounds[bucket]);
+                count_above += counts[bucket];
+            }
+            let cost = TRAVERSAL_COST +
+                       (count_below as f32 * bbox_below.surface_area() +
+                        count_above as f32 * bbox_above.surface_area()) /
+                       total_area;
+            if cost < best_cost {
+                best_cost = cost;
+                best_split = split;
+            }
+        }
+
+        // Le coût d'une feuille est celui de tester toutes ses primitives
+        let leaf_cost = count as f32;
+        if count <= max_primitives_in_node && (total_area <= 0.0 || best_cost >= leaf_cost) {
+            return self.push_leaf(bbox, primitives);
+        }
+
+        // On partitionne les primitives selon la meilleure coupe
+        let mut mid = 0;
+        for i in 0..count {
+            if bucket_of(&primitives[i]) <= best_split {
+                primitives.swap(i, mid);
+                mid += 1;
+            }
+        }
+
+        // Si la coupe SAH est dégénérée, on coupe à la médiane
+        if mid == 0 || mid == count {
+            primitives.sort_by(|a, b| {
+                                   BoundingBox::axis_value(&a.centroid, axis)
+                                       .partial_cmp(&BoundingBox::axis_value(&b.centroid, axis))
+                                       .unwrap_or(Ordering::Equal)
+                               });
+            mid = count / 2;
+        }
+
+        // On réserve la place du noeud, que l'on complétera une fois les enfants construits
+        let node_index = self.nodes.len();
+        self.nodes.push(BVHNode::Interior {
+                            bbox: bbox.clone(),
+                            second_child: 0,
+                            axis: axis,
+                        });
+
+        let (below, above) = primitives.split_at_mut(mid);
+        self.build_recursive(below, max_primitives_in_node);
+        let second_child = self.build_recursive(above, max_primitives_in_node);
+
+        self.nodes[node_index] = BVHNode::Interior {
+            bbox: bbox,
+            second_child: second_child,
+            axis: axis,
+        };
+        node_index
+    }
+
+    /// Recalcule les Bounding Box de tous les noeuds à partir des nouvelles Bounding Box des
+    /// primitives, sans changer la topologie de l'arbre. C'est beaucoup moins coûteux qu'une
+    /// reconstruction, mais la qualité de l'arbre se dégrade si les primitives bougent beaucoup.
+    /// Une primitive dont la Bounding Box est vide n'est pas touchée tant qu'elle le reste.
+    pub fn refit(&mut self, bboxes: &[BoundingBox]) {
+        // Les enfants d'un noeud sont toujours rangés après lui : en parcourant les noeuds à
+        // l'envers, on traite les enfants avant leur parent.
+        for node_index in (0..self.nodes.len()).rev() {
+            let new_bbox = match self.nodes[node_index] {
+                BVHNode::Leaf { first_primitive, primitive_count, .. } => {
+                    let mut bbox = BoundingBox::new();
+                    for i in first_primitive..(first_primitive + primitive_count) {
+                        bbox.add_bounding_box(&bboxes[self.primitives[i]]);
+                    }
+                    bbox
+                }
+                BVHNode::Interior { second_child, .. } => {
+                    self.nodes[node_index + 1].bbox().union(self.nodes[second_child].bbox())
+                }
+            };
+            match self.nodes[node_index] {
+                BVHNode::Leaf { ref mut bbox, .. } |
+                BVHNode::Interior { ref mut bbox, .. } => *bbox = new_bbox,
+            }
+        }
+    }
+
+    /// Parcourt le BVH et appelle `intersect_primitive` sur toutes les primitives dont les
+    /// Bounding Box englobantes sont touchées par le rayon. `intersect_primitive` doit renvoyer
+    /// `true` si le rayon touche la primitive, et réduire `ray.max_t` en conséquence : les noeuds
+    /// plus lointains sont alors ignorés. Renvoie `true` si au moins une primitive a été touchée.
+    pub fn intersect<F>(&self, ray: &mut Ray, intersect_primitive: F) -> bool
+        where F: FnMut(usize, &mut Ray) -> bool
+    {
+        self.traverse(ray, intersect_primitive, false)
+    }
+
+    /// Comme `intersect`, mais la traversée s'arrête à la première primitive touchée. Utile pour
+    /// les rayons d'ombre, où l'on cherche seulement à savoir s'il y a un obstacle.
+    pub fn occluded<F>(&self, ray: &mut Ray, intersect_primitive: F) -> bool
+        where F: FnMut(usize, &mut Ray) -> bool
+    {
+        self.traverse(ray, intersect_primitive, true)
+    }
+
+    fn traverse<F>(&self, ray: &mut Ray, mut intersect_primitive: F, any_hit: bool) -> bool
+        where F: FnMut(usize, &mut Ray) -> bool
+    {
+        if self.nodes.is_empty() {
+            return false;
+        }
+
+        let slope = ray.slope();
+        let dir_is_neg = [slope.x < 0.0, slope.y < 0.0, slope.z < 0.0];
+
+        let mut hit = false;
+        let mut to_visit: Vec<usize> = Vec::with_capacity(64);
+        to_visit.push(0);
+
+        while let Some(node_index) = to_visit.pop() {
+            let node = &self.nodes[node_index];
+            if node.bbox().intersection_distance(ray).is_none() {
+                continue;
+            }
+
+            match *node {
+                BVHNode::Leaf { first_primitive, primitive_count, .. } => {
+                    for i in first_primitive..(first_primitive + primitive_count) {
+                        if intersect_primitive(self.primitives[i], ray) {
+                            if any_hit {
+                                return true;
+                            }
+                            hit = true;
+                        }
+                    }
+                }
+                BVHNode::Interior { second_child, axis, .. } => {
+                    // On visite d'abord l'enfant le plus proche de l'origine du rayon, pour
+                    // réduire ray.max_t le plus tôt possible.
+                    if dir_is_neg[axis] {
+                        to_visit.push(node_index + 1);
+                        to_visit.push(second_child);
+                    } else {
+                        to_visit.push(second_child);
+                        to_visit.push(node_index + 1);
+                    }
+                }
+            }
+        }
+        hit
+    }
+}
+
+impl Default for BVH {
+    fn default() -> Self {
+        BVH::new_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use math::Vector3f;
+    use ray::{Ray, Surface};
+    use geometry::obj3d::{GeoPoint, Triangle};
+    use geometry::bounding_box::BoundingBox;
+    use super::BVH;
+
+    // Des triangles posés sur plusieurs couches horizontales
+    fn make_triangles() -> Vec<Triangle> {
+        let mut result = vec![];
+        for i in 0..6 {
+            for j in 0..6 {
+                for k in 0..4 {
+                    let (x, y, z) = (i as f32, j as f32, k as f32 * 1.5);
+                    result.push(Triangle::new(GeoPoint::new_pos(Vector3f::new(x, y, z)),
+                                              GeoPoint::new_pos(Vector3f::new(x + 0.9, y, z)),
+                                              GeoPoint::new_pos(Vector3f::new(x, y + 0.9, z))));
+                }
+            }
+        }
+        result
+    }
+
+    fn closest_linear(triangles: &[Triangle], ray: &mut Ray) -> Option<f32> {
+        let mut result = None;
+        for tri in triangles {
+            if let Some(frag) = tri.get_intersection_fragment(ray) {
+                result = Some(frag.param);
+            }
+        }
+        result
+    }
+
+    fn closest_bvh(bvh: &BVH, triangles: &[Triangle], ray: &mut Ray) -> Option<f32> {
+        let mut result = None;
+        bvh.intersect(ray, |i, ray| match triangles[i].get_intersection_fragment(ray) {
+            Some(frag) => {
+                result = Some(frag.param);
+                true
+            }
+            None => false,
+        });
+        result
+    }
+
+    #[test]
+    fn test_bvh_closest_hit_matches_linear_scan() {
+        let triangles = make_triangles();
+        let bboxes: Vec<BoundingBox> =
+            triangles.iter().map(BoundingBox::new_from_triangle).collect();
+        let bvh = BVH::new(&bboxes, 2);
+        assert!(bvh.node_count() > 1);
+
+        let origins = [Vector3f::new(0.2, 0.2, 10.0),
+                       Vector3f::new(3.3, 4.1, -5.0),
+                       Vector3f::new(-3.0, 2.2, 2.0),
+                       Vector3f::new(5.5, 5.5, 10.0)];
+        let slopes = [Vector3f::new(0.0, 0.0, -1.0),
+                      Vector3f::new(0.01, 0.02, 1.0),
+                      Vector3f::new(1.0, 0.05, 0.3),
+                      Vector3f::new(0.0, 0.0, -1.0)];
+
+        for (origin, slope) in origins.iter().zip(slopes.iter()) {
+            let mut r1 = Ray::new(*origin, *slope);
+            let mut r2 = Ray::new(*origin, *slope);
+            let expected = closest_linear(&triangles, &mut r1);
+            let actual = closest_bvh(&bvh, &triangles, &mut r2);
+            match (expected, actual) {
+                (Some(e), Some(a)) => assert!((e - a).abs() < 0.0001),
+                (None, None) => {}
+                _ => panic!("BVH and linear scan disagree : {:?} / {:?}", expected, actual),
+            }
+        }
+    }
+
+    #[test]
+    fn test_bvh_occlusion() {
+        let triangles = make_triangles();
+        let bboxes: Vec<BoundingBox> =
+            triangles.iter().map(BoundingBox::new_from_triangle).collect();
+        let bvh = BVH::new(&bboxes, 4);
+
+        // Ce rayon traverse les couches
+        let mut blocked = Ray::new(Vector3f::new(0.2, 0.2, 10.0), Vector3f::new(0.0, 0.0, -10.0));
+        blocked.max_t = 0.999;
+        assert!(bvh.occluded(&mut blocked, |i, ray| triangles[i].fast_intersection(ray)));
+
+        // Celui-ci s'arrête avant la première couche
+        let mut free = Ray::new(Vector3f::new(0.2, 0.2, 10.0), Vector3f::new(0.0, 0.0, -1.0));
+        free.max_t = 4.0;
+        assert!(!bvh.occluded(&mut free, |i, ray| triangles[i].fast_intersection(ray)));
+
+        let empty = BVH::new(&[], 4);
+        assert!(!empty.occluded(&mut blocked, |i, ray| triangles[i].fast_intersection(ray)));
+    }
+
+    #[test]
+    fn test_bvh_refit() {
+        let triangles = make_triangles();
+        let mut bboxes: Vec<BoundingBox> =
+            triangles.iter().map(BoundingBox::new_from_triangle).collect();
+        let mut bvh = BVH::new(&bboxes, 2);
+
+        // On "cache" toutes les primitives : plus rien ne doit être touché.
+        for bbox in &mut bboxes {
+            *bbox = BoundingBox::new();
+        }
+        bvh.refit(&bboxes);
+        assert!(bvh.bounding_box().is_empty());
+        let mut ray = Ray::new(Vector3f::new(0.2, 0.2, 10.0), Vector3f::new(0.0, 0.0, -1.0));
+        assert_eq!(closest_bvh(&bvh, &triangles, &mut ray), None);
+
+        // Puis on les fait réapparaître
+        let bboxes: Vec<BoundingBox> =
+            triangles.iter().map(BoundingBox::new_from_triangle).collect();
+        bvh.refit(&bboxes);
+        let mut ray = Ray::new(Vector3f::new(0.2, 0.2, 10.0), Vector3f::new(0.0, 0.0, -1.0));
+        assert!(closest_bvh(&bvh, &triangles, &mut ray).is_some());
+    }
+
+    #[test]
+    fn test_bvh_keeps_empty_primitives() {
+        let triangles = make_triangles();
+        let mut bboxes: Vec<BoundingBox> =
+            triangles.iter().map(BoundingBox::new_from_triangle).collect();
+        let first_bbox = bboxes[0].clone();
+        bboxes[0] = BoundingBox::new();
+
+        // La première primitive est vide à la construction : elle n'est pas touchée...
+        let mut bvh = BVH::new(&bboxes, 2);
+        let mut ray = Ray::new(Vector3f::new(0.2, 0.2, 10.0), Vector3f::new(0.0, 0.0, -1.0));
+        let mut hits = vec![];
+        bvh.intersect(&mut ray, |i, _| {
+            hits.push(i);
+            false
+        });
+        assert!(!hits.contains(&0));
+
+        // ...mais un refit la fait apparaître.
+        bboxes[0] = first_bbox;
+        bvh.refit(&bboxes);
+        let mut ray = Ray::new(Vector3f::new(0.2, 0.2, 10.0), Vector3f::new(0.0, 0.0, -1.0));
+        let mut hits = vec![];
+        bvh.intersect(&mut ray, |i, _| {
+            hits.push(i);
+            false
+        });
+        assert!(hits.contains(&0));
+
+        // De même si toutes les primitives sont vides à la construction.
+        let empty: Vec<BoundingBox> = triangles.iter().map(|_| BoundingBox::new()).collect();
+        let mut bvh = BVH::new(&empty, 2);
+        let bboxes: Vec<BoundingBox> =
+            triangles.iter().map(BoundingBox::new_from_triangle).collect();
+        bvh.refit(&bboxes);
+        let mut ray = Ray::new(Vector3f::new(0.2, 0.2, 10.0), Vector3f::new(0.0, 0.0, -1.0));
+        assert!(closest_bvh(&bvh, &triangles, &mut ray).is_some());
+    }
+}
@@ -1,3 +1,5 @@
 pub mod obj3d;
 pub mod bounding_box;
 pub mod obj_parser;
+pub mod bvh;
+pub mod transform;
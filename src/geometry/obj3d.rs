@@ -1,13 +1,18 @@
 use std::vec::Vec;
 use std::f32;
 use math::{Vector3, Vector3f, Vector2f, VectorialOperations, AlmostEq};
-use material::flat_material::FlatMaterial;
+use material::{Material, MaterialObject};
 use ray::{Ray, Plane, Surface, Fragment, Intersection};
 use std::slice::Iter;
+use std::sync::Arc;
+use std::collections::HashMap;
 use angle::{Rad, Deg};
 use colored::*;
 use geometry::bounding_box::BoundingBox;
+use geometry::bvh::BVH;
+use geometry::transform::Transform;
 use geometry::obj_parser;
+use animation::{self, ObjectAnimation};
 use tools::orthogonalize_vec;
 
 #[derive(Clone,Debug,Copy,PartialEq)]
@@ -238,15 +243,53 @@ impl Surface for Triangle {
     }
 }
 
-#[derive(Clone,Debug,PartialEq)]
+/** Cache des meshs chargés, indexé par le chemin du .obj. Les objets qui utilisent le même
+fichier partagent ainsi la même géométrie (et le même BVH). */
+pub type MeshRegister = HashMap<String, Arc<Mesh>>;
+
+#[derive(Clone,Debug)]
 pub struct Mesh {
     triangles: Vec<Triangle>,
+
+    // Le BVH construit sur les triangles, dans l'espace de l'objet. Il est vide tant que
+    // build_bvh() n'a pas été appelé, et dans ce cas on teste les triangles un à un.
+    bvh: BVH,
+
+    // Le barycentre du mesh tel qu'il était dans le .obj, avant d'être recentré
+    barycenter: Vector3f,
+}
+
+// Deux meshs sont égaux s'ils ont les mêmes triangles, peu importe leur BVH.
+impl PartialEq for Mesh {
+    fn eq(&self, other: &Mesh) -> bool {
+        self.triangles == other.triangles
+    }
 }
 
 impl Mesh {
     // Crée un nouveau mesh vide
     pub fn new_empty() -> Mesh {
-        Mesh { triangles: vec![] }
+        Mesh {
+            triangles: vec![],
+            bvh: BVH::new_empty(),
+            barycenter: Vector3f::zero(),
+        }
+    }
+
+    // Crée un mesh vide partagé, utilisé comme valeur par défaut à la désérialisation
+    pub fn new_shared_empty() -> Arc<Mesh> {
+        Arc::new(Mesh::new_empty())
+    }
+
+    // Charge le .obj donné par `path` et le recentre en (0,0,0). Le BVH est construit si
+    // `max_primitives_in_node` est donné.
+    pub fn load(path: &str, max_primitives_in_node: Option<usize>) -> Mesh {
+        let mut mesh = obj_parser::open_obj(path);
+        mesh.center(path);
+        if let Some(max) = max_primitives_in_node {
+            mesh.build_bvh(max);
+        }
+        mesh
     }
 
     pub fn create_point(pos: usize,
@@ -292,6 +335,127 @@ impl Mesh {
         self.triangles.iter()
     }
 
+    pub fn triangle(&self, index: usize) -> &Triangle {
+        &self.triangles[index]
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    // Déplace tous les triangles du mesh
+    pub fn translate(&mut self, offset: &Vector3f) {
+        for tri in &mut self.triangles {
+            tri.add_position(offset);
+        }
+        self.bvh = BVH::new_empty();
+    }
+
+    // Construit le BVH du mesh (BVH "de bas niveau").
+    pub fn build_bvh(&mut self, max_primitives_in_node: usize) {
+        let bboxes: Vec<BoundingBox> =
+            self.triangles.iter().map(BoundingBox::new_from_triangle).collect();
+        self.bvh = BVH::new(&bboxes, max_primitives_in_node);
+    }
+
+    pub fn clear_bvh(&mut self) {
+        self.bvh = BVH::new_empty();
+    }
+
+    pub fn barycenter(&self) -> &Vector3f {
+        &self.barycenter
+    }
+
+    // Calcule le barycentre du mesh, le compare à (0,0,0) et recentre le mesh en fonction.
+    fn center(&mut self, name: &str) {
+        let barycenter = self.get_barycenter(name);
+        if !barycenter.aeq(&Vector3f::zero()) {
+            println!("{} ",
+                     format!("Warning, the mesh {} is not centered in (0,0,0) but in {}",
+                             name,
+                             &barycenter)
+                             .yellow()
+                             .dimmed());
+            // On centre le mesh à l'origine.
+            self.translate(&-barycenter);
+        }
+        self.barycenter = barycenter;
+    }
+
+    // La Bounding Box du mesh, dans l'espace de l'objet
+    pub fn bounding_box(&self) -> BoundingBox {
+        let mut result = BoundingBox::new();
+        for tri in &self.triangles {
+            result.add_bounding_box(&BoundingBox::new_from_triangle(tri));
+        }
+        result
+    }
+
+    // Renvoie le fragment le plus proche de l'origine du rayon. Le rayon doit être exprimé dans
+    // l'espace de l'objet.
+    pub fn get_intersection_fragment(&self, ray: &mut Ray) -> Option<Fragment> {
+        if self.bvh.is_empty() {
+            let points: Vec<Option<Fragment>> = self.triangles
+                .iter()
+                .map(|tri| tri.get_intersection_fragment(ray))
+                .filter(|point| point.is_some())
+                .collect();
+
+            match points.len() {
+                0 => None,
+                n => points[n - 1],
+            }
+        } else {
+            let mut result = None;
+            let triangles = &self.triangles;
+            self.bvh.intersect(ray, |i, ray| match triangles[i].get_intersection_fragment(ray) {
+                Some(frag) => {
+                    result = Some(frag);
+                    true
+                }
+                None => false,
+            });
+            result
+        }
+    }
+
+    // Renvoie true si le rayon (exprimé dans l'espace de l'objet) touche un triangle.
+    pub fn fast_intersection(&self, ray: &mut Ray) -> bool {
+        if self.bvh.is_empty() {
+            for tri in &self.triangles {
+                if tri.fast_intersection(ray) {
+                    return true;
+                }
+            }
+            false
+        } else {
+            let triangles = &self.triangles;
+            self.bvh.occluded(ray, |i, ray| triangles[i].fast_intersection(ray))
+        }
+    }
+
+    // Renvoie l'indice du triangle le plus proche touché par le rayon (exprimé dans l'espace de
+    // l'objet), et réduit `ray.max_t` à sa distance. `ray.max_t` doit être positif.
+    pub fn nearest_triangle(&self, ray: &mut Ray) -> Option<usize> {
+        let mut result = None;
+        if self.bvh.is_empty() {
+            for (i, tri) in self.triangles.iter().enumerate() {
+                if tri.fast_intersection(ray) {
+                    result = Some(i);
+                }
+            }
+        } else {
+            let triangles = &self.triangles;
+            self.bvh.intersect(ray, |i, ray| if triangles[i].fast_intersection(ray) {
+                result = Some(i);
+                true
+            } else {
+                false
+            });
+        }
+        result
+    }
+
     #[allow(float_cmp)]
     fn get_barycenter(&self, name: &str) -> Vector3f {
         let mut sum = Vector3f::new(0.0, 0.0, 0.0);
@@ -320,15 +484,16 @@ impl Mesh {
 
 #[derive(Serialize,Deserialize,Debug,Clone)]
 pub struct Object {
-    #[serde(skip_serializing,skip_deserializing,default = "Mesh::new_empty")]
-    mesh: Mesh,
+    // Le mesh, éventuellement partagé avec d'autres objets qui utilisent le même .obj
+    #[serde(skip_serializing,skip_deserializing,default = "Mesh::new_shared_empty")]
+    mesh: Arc<Mesh>,
 
     // Le materiau de l'objet
-    #[serde(skip_serializing, skip_deserializing,default = "FlatMaterial::new_empty",
+    #[serde(skip_serializing, skip_deserializing,default = "MaterialObject::new_empty",
     rename="do_not_use")]
-    material: FlatMaterial,
+    material: MaterialObject,
 
-    // La position de l'objet (offset qui se propagera ensuite aux triangles)
+    // La position de l'objet
     position: Vector3f,
 
     // L'échelle de l'objet selon les trois axes
@@ -351,12 +516,39 @@ pub struct Object {
     #[serde(skip_serializing,skip_deserializing,default = "Vector3f::zero")]
     barycenter: Vector3f,
 
-    // La bounding box
+    // La bounding box, dans l'espace du monde
     #[serde(skip_serializing, skip_deserializing, default = "BoundingBox::new")]
     bbox: BoundingBox,
 
+    // La transformation qui fait passer de l'espace de l'objet à celui du monde. Elle est
+    // calculée à partir de la position, de l'échelle et de la rotation.
+    #[serde(skip_serializing, skip_deserializing, default = "Transform::identity")]
+    transform: Transform,
+
     // La visibilité de l'objet
     visible: bool,
+
+    // La position, l'échelle et la rotation de l'objet à l'instant 1, pour le flou de
+    // mouvement : `position`, `scale` et `rotation` sont celles de l'instant 0, et l'objet passe
+    // linéairement des unes aux autres. Par défaut l'objet ne bouge pas.
+    #[serde(default)]
+    end_position: Option<Vector3f>,
+    #[serde(default)]
+    end_scale: Option<Vector3f>,
+    #[serde(default)]
+    end_rotation: Option<Vector3<Deg<f32>>>,
+
+    // Les paramètres animés de l'objet
+    #[serde(default)]
+    animation: Option<ObjectAnimation>,
+}
+
+// Le nombre d'instants où la Bounding Box d'un objet en mouvement est calculée
+const MOTION_BOUNDING_BOX_STEPS: usize = 32;
+
+// L'interpolation linéaire entre a et b
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
 }
 
 impl Object {
@@ -370,55 +562,120 @@ impl Object {
         result
     }
 
-    // Charge la géométrie donnée par le chemin "obj_path"
-    fn load_mesh(&mut self) {
-        self.mesh = obj_parser::open_obj(&self.obj_path);
-    }
 
-    fn apply_position(&mut self) {
-        for tri in &mut self.mesh.triangles {
-            tri.add_position(&self.position);
+    // Recalcule la transformation de l'objet, ainsi que sa Bounding Box dans l'espace du monde.
+    // Doit être appelé à chaque fois que la position, l'échelle ou la rotation change.
+    fn update_transform(&mut self) {
+        self.transform = Transform::new(&self.position, &self.scale, &self.rotation);
+        let local_bbox = BoundingBox::new_from_object(self);
+        self.bbox = self.transform.apply_to_bounding_box(&local_bbox);
+        // La Bounding Box d'un objet en mouvement contient toutes ses positions. Entre deux
+        // instants calculés, une rotation peut faire un peu sortir l'objet : on l'élargit.
+        if self.is_moving() {
+            for step in 1..(MOTION_BOUNDING_BOX_STEPS + 1) {
+                let time = step as f32 / MOTION_BOUNDING_BOX_STEPS as f32;
+                let bbox = self.transform_at(time).apply_to_bounding_box(&local_bbox);
+                self.bbox.add_bounding_box(&bbox);
+            }
+            if !self.bbox.is_empty() {
+                let margin = (self.bbox.max() - self.bbox.min()) * 0.01;
+                let (min, max) = (self.bbox.min() - margin, self.bbox.max() + margin);
+                self.bbox.add_point(min);
+                self.bbox.add_point(max);
+            }
         }
-
-        // On réinitialise car ça n'a aucun sens de l'appliquer deux fois
-        self.position = Vector3f::new(0.0, 0.0, 0.0);
     }
 
-    fn apply_rotation(&mut self) {
-        for tri in &mut self.mesh.triangles {
-            tri.rotate_around(&Vector3f::new(1.0, 0.0, 0.0), (&self.rotation.x).into());
-            tri.rotate_around(&Vector3f::new(0.0, 1.0, 0.0), (&self.rotation.y).into());
-            tri.rotate_around(&Vector3f::new(0.0, 0.0, 1.0), (&self.rotation.z).into());
-        }
-
-        // On réinitialise car ça n'a aucun sens de l'appliquer deux fois
-        self.rotation = Vector3::new(deg!(0.0f32), deg!(0.0f32), deg!(0.0f32));
+    /// Vrai si l'objet se déplace, change d'échelle ou tourne pendant l'intervalle de temps.
+    pub fn is_moving(&self) -> bool {
+        self.end_position.is_some() || self.end_scale.is_some() || self.end_rotation.is_some()
     }
 
-    //TODO La rotation autour d'un point (même si c'est un peu plus compliqué)
-    fn apply_scale(&mut self) {
-        for tri in &mut self.mesh.triangles {
-            tri.scale_from(&Vector3f::new(0.0, 0.0, 0.0), &self.scale);
+    /// La transformation de l'objet à l'instant `time`, entre 0 et 1.
+    pub fn transform_at(&self, time: f32) -> Transform {
+        if !self.is_moving() || time <= 0.0 {
+            return self.transform;
         }
-
-        // On réinitialise car ça n'a aucun sens de l'appliquer deux fois
-        self.scale = Vector3f::new(1.0, 1.0, 1.0);
+        let t = f32::min(time, 1.0);
+        let position = match self.end_position {
+            Some(end) => self.position + (end - self.position) * t,
+            None => self.position,
+        };
+        let scale = match self.end_scale {
+            Some(end) => self.scale + (end - self.scale) * t,
+            None => self.scale,
+        };
+        let rotation = match self.end_rotation {
+            Some(ref end) => {
+                Vector3 {
+                    x: Deg(lerp(self.rotation.x.0, end.x.0, t)),
+                    y: Deg(lerp(self.rotation.y.0, end.y.0, t)),
+                    z: Deg(lerp(self.rotation.z.0, end.z.0, t)),
+                }
+            }
+            None => self.rotation.clone(),
+        };
+        Transform::new(&position, &scale, &rotation)
+    }
+
+    pub fn set_animation(&mut self, animation: Option<ObjectAnimation>) {
+        self.animation = animation;
+    }
+
+    /// Donne aux paramètres animés de l'objet leur valeur à l'instant `time`, en secondes, et
+    /// leur valeur à l'instant `time + frame_duration` comme état de fin pour le flou de
+    /// mouvement. Renvoie vrai si l'objet est animé.
+    pub fn set_time(&mut self, time: f32, frame_duration: f32) -> bool {
+        let animation = match self.animation {
+            Some(ref animation) => animation.clone(),
+            None => return false,
+        };
+        let end = time + frame_duration;
+        let to_deg = |v: Vector3f| {
+            Vector3 {
+                x: Deg(v.x),
+                y: Deg(v.y),
+                z: Deg(v.z),
+            }
+        };
+        if let Some(position) = animation::evaluate(&animation.position, time) {
+            self.position = position;
+            self.end_position = animation::evaluate(&animation.position, end);
+        }
+        if let Some(scale) = animation::evaluate(&animation.scale, time) {
+            self.scale = scale;
+            self.end_scale = animation::evaluate(&animation.scale, end);
+        }
+        if let Some(rotation) = animation::evaluate(&animation.rotation, time) {
+            self.rotation = to_deg(rotation);
+            self.end_rotation = animation::evaluate(&animation.rotation, end).map(to_deg);
+        }
+        self.update_transform();
+        true
     }
 
-    fn compute_barycenter(&self) -> Vector3f {
-        self.mesh.get_barycenter(&self.name)
+    /// Donne la position, l'échelle et la rotation de l'objet à l'instant 1 (flou de
+    /// mouvement). `None` garde la valeur de l'instant 0.
+    pub fn set_motion(&mut self,
+                      end_position: Option<Vector3f>,
+                      end_scale: Option<Vector3f>,
+                      end_rotation: Option<Vector3<Deg<f32>>>) {
+        self.end_position = end_position;
+        self.end_scale = end_scale;
+        self.end_rotation = end_rotation;
+        self.update_transform();
     }
 
     // Chargement du matériau
     fn load_material(&mut self) {
         if self.material_path != "" {
-            self.material = match FlatMaterial::read_from_file(self.material_path.as_str()) {
+            self.material = match MaterialObject::read_from_file(self.material_path.as_str()) {
                 Ok(value) => value,
                 Err(e) => {
                     println!("Can't load the material {} due to error : {:?}",
                              self.material_path,
                              e);
-                    FlatMaterial::new_empty()
+                    MaterialObject::new_empty()
                 }
             }
         } else {
@@ -427,45 +684,30 @@ impl Object {
         }
     }
 
-    // Calcule le barycentre de l'objet, le compare à (0,0,0) et recentre l'objet en fonction
-    // /!\ Réinitialise la position de l'objet !!!!
-    fn center(&mut self) {
-        let barycenter = self.compute_barycenter();
-        // Application des transformations
-        let old_pos: Vector3f = self.position;
-        if !barycenter.aeq(&Vector3f::zero()) {
-            println!("{} ",
-                     format!("Warning, the object {} is not centered in (0,0,0) but in {}",
-                             self.name,
-                             &barycenter)
-                             .yellow()
-                             .dimmed());
-            // On centre l'objet à l'origine.
-            self.position = -barycenter;
-            self.apply_position();
-            self.position = old_pos;
-        }
-        self.barycenter = barycenter;
-    }
-
-    // Initialise un objet. Pour l'instant cela ne fait que charger le mesh, mais on peut imaginer
-    // d'autres traitements.
+    // Initialise un objet : charge le mesh et le matériau. La géométrie reste exprimée dans
+    // l'espace de l'objet, la position, l'échelle et la rotation ne sont appliquées qu'à travers
+    // la transformation de l'objet.
     pub fn initialize(&mut self) {
         // Important, on charge le mesh avant de commencer à rendre car sinon le calcul du
         // barycentre est débile.
-        self.load_mesh();
-        self.center();
-        self.apply_scale();
-        self.apply_rotation();
-        self.apply_position();
-        self.bbox = BoundingBox::new_from_object(self);
+        let mesh = Arc::new(Mesh::load(&self.obj_path, None));
+        self.initialize_with_mesh(mesh);
+    }
+
+    // Initialise un objet avec un mesh déjà chargé (et recentré), par exemple depuis le cache
+    // des meshs du monde.
+    pub fn initialize_with_mesh(&mut self, mesh: Arc<Mesh>) {
+        self.barycenter = *mesh.barycenter();
+        self.mesh = mesh;
+        self.update_transform();
         self.load_material();
     }
+
     // Crée un objet vide
     pub fn new_empty() -> Object {
         Object {
-            mesh: Mesh::new_empty(),
-            material: FlatMaterial::new_empty(),
+            mesh: Mesh::new_shared_empty(),
+            material: MaterialObject::new_empty(),
             position: Vector3::new(0_f32, 0_f32, 0_f32),
             scale: Vector3f::new(1f32, 1f32, 1f32),
             rotation: Vector3 {
@@ -478,7 +720,12 @@ impl Object {
             name: "untitled".to_string(),
             barycenter: Vector3f::zero(),
             bbox: BoundingBox::new(),
+            transform: Transform::identity(),
             visible: true,
+            end_position: None,
+            end_scale: None,
+            end_rotation: None,
+            animation: None,
         }
     }
 
@@ -487,8 +734,8 @@ impl Object {
         self.mesh.triangles()
     }
 
-    pub fn material(&self) -> &FlatMaterial {
-        &self.material
+    pub fn material(&self) -> &Material {
+        self.material.as_trait()
     }
 
     pub fn bounding_box(&self) -> &BoundingBox {
@@ -503,36 +750,93 @@ impl Object {
         &self.position
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn obj_path(&self) -> &str {
+        &self.obj_path
+    }
+
+    pub fn mesh(&self) -> &Arc<Mesh> {
+        &self.mesh
+    }
+
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    pub fn set_position(&mut self, position: Vector3f) {
+        self.position = position;
+        self.update_transform();
+    }
+
+    pub fn set_scale(&mut self, scale: Vector3f) {
+        self.scale = scale;
+        self.update_transform();
+    }
+
+    pub fn set_rotation(&mut self, rotation: Vector3<Deg<f32>>) {
+        self.rotation = rotation;
+        self.update_transform();
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
+    pub fn set_obj_path(&mut self, path: String) {
+        self.obj_path = path;
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn triangle(&self, index: usize) -> &Triangle {
+        self.mesh.triangle(index)
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.mesh.triangle_count()
+    }
+
     pub fn get_intersection_point(&self, ray: &mut Ray) -> Option<Intersection> {
 
         match self.get_intersection_fragment(ray) {
-            // Attention ici le rayon est copié !!!!!!
-            Some(frag) => Some(Intersection::new(frag, *ray, &self.mesh, &self.material)),
+            Some(frag) => Some(self.intersection_from_fragment(frag, ray)),
             None => None,
         }
     }
+
+    // Crée l'intersection correspondant à un fragment situé sur l'un des triangles de l'objet.
+    pub fn intersection_from_fragment(&self, frag: Fragment, ray: &Ray) -> Intersection {
+        // Attention ici le rayon est copié !!!!!!
+        Intersection::new(frag, *ray, &self.mesh, self.material.as_trait())
+    }
 }
 
 impl Surface for Object {
+    // On ramène le rayon dans l'espace de l'objet pour le tester contre le mesh, puis on exprime
+    // le fragment obtenu dans l'espace du monde.
     fn get_intersection_fragment(&self, ray: &mut Ray) -> Option<Fragment> {
-
-        let points: Vec<Option<Fragment>> = self.triangles()
-            .map(|tri| tri.get_intersection_fragment(ray))
-            .filter(|point| point.is_some())
-            .collect();
-
-        match points.len() {
-            0 => None,
-            n => points[n - 1],
+        let transform = self.transform_at(ray.time);
+        let mut local_ray = transform.ray_to_local(ray);
+        match self.mesh.get_intersection_fragment(&mut local_ray) {
+            Some(frag) => {
+                ray.max_t = local_ray.max_t;
+                Some(transform.fragment_to_world(&frag))
+            }
+            None => None,
         }
     }
 
     fn fast_intersection(&self, ray: &mut Ray) -> bool {
         if self.visible && self.bbox.intersects(ray) {
-            for tri in self.triangles() {
-                if tri.fast_intersection(ray) {
-                    return true;
-                }
+            let mut local_ray = self.transform_at(ray.time).ray_to_local(ray);
+            if self.mesh.fast_intersection(&mut local_ray) {
+                ray.max_t = local_ray.max_t;
+                return true;
             }
         }
         false
@@ -543,7 +847,7 @@ impl Surface for Object {
 mod test {
     use math::{Vector3, Vector3f};
     use ray::{Surface, Ray};
-    use super::{GeoPoint, Triangle};
+    use super::{GeoPoint, Triangle, Object};
 
     #[test]
     fn test_triangle_ray_intersection() {
@@ -572,4 +876,28 @@ mod test {
         let frag3 = tri1.get_intersection_fragment(&mut r3);
         assert_ne!(frag3, None);
     }
+
+    #[test]
+    fn test_object_motion() {
+        // Un plan de 2x2 perpendiculaire à l'axe y, qui se déplace de 4 selon x
+        let mut plane = Object::new(Vector3f::new(0.0, 0.0, 0.0),
+                                    "models/plane_no_uv.obj".to_string(),
+                                    "plane".to_string());
+        plane.set_motion(Some(Vector3f::new(4.0, 0.0, 0.0)), None, None);
+        assert!(plane.bounding_box().min().x < -0.99 && plane.bounding_box().max().x > 4.99);
+
+        let ray_at = |x: f32, time: f32| -> Ray {
+            let mut ray = Ray::new(Vector3f::new(x, -1.0, 0.0), Vector3f::new(0.0, 1.0, 0.0));
+            ray.max_t = 10.0;
+            ray.time = time;
+            ray
+        };
+        assert!(plane.fast_intersection(&mut ray_at(0.0, 0.0)));
+        assert!(!plane.fast_intersection(&mut ray_at(0.0, 1.0)));
+        assert!(plane.fast_intersection(&mut ray_at(4.0, 1.0)));
+        assert!(plane.fast_intersection(&mut ray_at(2.0, 0.5)));
+        assert!(!plane.fast_intersection(&mut ray_at(2.0, 0.1)));
+        let frag = plane.get_intersection_fragment(&mut ray_at(2.5, 0.5)).unwrap();
+        assert!((frag.position.x - 2.5).abs() < 1e-4 && frag.position.y.abs() < 1e-4);
+    }
 }
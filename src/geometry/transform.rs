@@ -0,0 +1,173 @@
+use math::{Vector3, Vector3f, Matrix3f};
+use angle::{Deg, Rad};
+use ray::{Ray, Fragment};
+use geometry::bounding_box::BoundingBox;
+
+/// Une transformation affine, qui fait passer un point de l'espace d'un objet à l'espace du
+/// monde : `p_monde = linear * p_objet + translation`.
+/// On garde l'inverse de la partie linéaire pour pouvoir ramener les rayons dans l'espace de
+/// l'objet lors des calculs d'intersection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    linear: Matrix3f,
+    inverse_linear: Matrix3f,
+    translation: Vector3f,
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Transform {
+            linear: Matrix3f::identity(),
+            inverse_linear: Matrix3f::identity(),
+            translation: Vector3f::zero(),
+        }
+    }
+
+    /// Construit la transformation d'un objet : on applique d'abord l'échelle, puis les rotations
+    /// autour des axes x, y et z (dans cet ordre), et enfin la translation.
+    pub fn new(position: &Vector3f, scale: &Vector3f, rotation: &Vector3<Deg<f32>>) -> Self {
+        let rot_x: Rad<f32> = (&rotation.x).into();
+        let rot_y: Rad<f32> = (&rotation.y).into();
+        let rot_z: Rad<f32> = (&rotation.z).into();
+
+        let linear = Matrix3f::rotation(&Vector3f::new(0.0, 0.0, 1.0), rot_z.0) *
+                     Matrix3f::rotation(&Vector3f::new(0.0, 1.0, 0.0), rot_y.0) *
+                     Matrix3f::rotation(&Vector3f::new(1.0, 0.0, 0.0), rot_x.0) *
+                     Matrix3f::scale(scale);
+
+        Transform {
+            linear: linear,
+            inverse_linear: linear
+                .inverse()
+                .expect("Error, an object can't have a null scale"),
+            translation: *position,
+        }
+    }
+
+    pub fn apply_to_point(&self, p: &Vector3f) -> Vector3f {
+        self.linear * *p + self.translation
+    }
+
+    pub fn apply_to_vector(&self, v: &Vector3f) -> Vector3f {
+        self.linear * *v
+    }
+
+    /// Les normales se transforment avec la transposée de l'inverse de la partie linéaire.
+    pub fn apply_to_normal(&self, n: &Vector3f) -> Vector3f {
+        self.inverse_linear.transpose() * *n
+    }
+
+    pub fn inverse_point(&self, p: &Vector3f) -> Vector3f {
+        self.inverse_linear * (*p - self.translation)
+    }
+
+    pub fn inverse_vector(&self, v: &Vector3f) -> Vector3f {
+        self.inverse_linear * *v
+    }
+
+    /// Ramène un rayon du monde dans l'espace de l'objet. Le vecteur directeur n'est pas
+    /// normalisé : le paramètre `t` d'un point est donc le même dans les deux espaces, et
+    /// `max_t` peut être conservé tel quel.
+    pub fn ray_to_local(&self, ray: &Ray) -> Ray {
+        let mut result = Ray::new(self.inverse_point(&ray.origin()),
+                                  self.inverse_vector(&ray.slope()));
+        result.max_t = ray.max_t;
+        result.time = ray.time;
+        result
+    }
+
+    /// Exprime dans l'espace du monde un fragment calculé dans l'espace de l'objet.
+    pub fn fragment_to_world(&self, frag: &Fragment) -> Fragment {
+        let mut result = *frag;
+        result.position = self.apply_to_point(&frag.position);
+        result.normal = self.apply_to_normal(&frag.normal);
+        result.du = self.apply_to_vector(&frag.du);
+        result.dv = self.apply_to_vector(&frag.dv);
+        result
+    }
+
+    /// La Bounding Box (alignée sur les axes du monde) qui contient la Bounding Box `bbox`
+    /// exprimée dans l'espace de l'objet.
+    pub fn apply_to_bounding_box(&self, bbox: &BoundingBox) -> BoundingBox {
+        let mut result = BoundingBox::new();
+        if bbox.is_empty() {
+            return result;
+        }
+        let (min, max) = (bbox.min(), bbox.max());
+        for i in 0..8 {
+            let corner = Vector3f::new(if i & 1 == 0 { min.x } else { max.x },
+                                       if i & 2 == 0 { min.y } else { max.y },
+                                       if i & 4 == 0 { min.z } else { max.z });
+            result.add_point(self.apply_to_point(&corner));
+        }
+        result
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use math::{Vector3, Vector3f, VectorialOperations};
+    use angle::Deg;
+    use ray::{Ray, Fragment};
+    use super::Transform;
+
+    // Une transformation avec une échelle non uniforme et une rotation autour des trois axes
+    fn make_transform() -> Transform {
+        Transform::new(&Vector3f::new(1.0, -2.0, 3.0),
+                       &Vector3f::new(2.0, 0.5, 3.0),
+                       &Vector3 {
+                            x: Deg(30.0),
+                            y: Deg(45.0),
+                            z: Deg(60.0),
+                        })
+    }
+
+    fn assert_close(a: Vector3f, b: Vector3f) {
+        assert!((a - b).norm() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_ray_to_local() {
+        let transform = make_transform();
+        let mut ray = Ray::new(Vector3f::new(0.5, 4.0, -1.0), Vector3f::new(0.3, -0.8, 0.5));
+        ray.max_t = 7.0;
+        ray.time = 0.25;
+        let local = transform.ray_to_local(&ray);
+        assert_eq!(local.max_t, 7.0);
+        assert_eq!(local.time, 0.25);
+
+        // Le point de paramètre t est le même dans les deux espaces.
+        for &t in &[0.0, 1.0, 2.5] {
+            let world_point = ray.origin() + ray.slope() * t;
+            let local_point = local.origin() + local.slope() * t;
+            assert_close(transform.apply_to_point(&local_point), world_point);
+            assert_close(transform.inverse_point(&world_point), local_point);
+        }
+    }
+
+    #[test]
+    fn test_fragment_to_world() {
+        let transform = make_transform();
+        let du = Vector3f::new(1.0, 0.0, 0.5);
+        let dv = Vector3f::new(0.0, 1.0, -0.25);
+        let mut frag = Fragment::new(Vector3f::new(0.2, 0.4, -0.6), 1.0, du, dv);
+        frag.normal = du.cross_product(&dv);
+
+        let world = transform.fragment_to_world(&frag);
+        assert_close(world.position, transform.apply_to_point(&frag.position));
+        assert_close(transform.inverse_point(&world.position), frag.position);
+        assert_close(transform.inverse_vector(&world.du), du);
+        assert_close(transform.inverse_vector(&world.dv), dv);
+        // Malgré l'échelle non uniforme, la normale reste orthogonale à la surface.
+        let normal = world.normal / world.normal.norm();
+        assert!(normal.dot_product(&(world.du / world.du.norm())).abs() < 1e-5);
+        assert!(normal.dot_product(&(world.dv / world.dv.norm())).abs() < 1e-5);
+        assert!(normal.dot_product(&world.du.cross_product(&world.dv)) > 0.0);
+    }
+}
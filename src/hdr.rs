@@ -0,0 +1,701 @@
+/// Lecture et écriture des images à grande dynamique : le format Radiance (`.hdr`, pixels RGBE
+/// compressés par plages), le format PFM (flottants 32 bits) et, en écriture seulement, le format
+/// OpenEXR (`.exr`, flottants 16 ou 32 bits, plusieurs calques). Les pixels sont des radiances
+/// linéaires.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::f32;
+use color_float::{LinearColor, FloatColor, Color};
+use img::Image;
+
+/// Lit une image HDR, dont le format est choisi selon l'extension du fichier (`.hdr` ou `.pfm`).
+pub fn read_hdr_file(pathname: &str) -> Result<Image<LinearColor>, String> {
+    let mut bytes = vec![];
+    File::open(pathname)
+        .and_then(|mut file| file.read_to_end(&mut bytes))
+        .map_err(|e| format!("Could not read {} : {}", pathname, e))?;
+    match file_extension(pathname).as_ref().map(|ext| ext.as_str()) {
+        Some("hdr") => read_radiance(&bytes),
+        Some("pfm") => read_pfm(&bytes),
+        _ => Err(format!("Unknown HDR image format : {}", pathname)),
+    }
+    .map_err(|e| format!("Error while reading {} : {}", pathname, e))
+}
+
+// Construit l'image à partir des lignes de pixels, de haut en bas.
+fn image_from_rows(rows: Vec<Vec<LinearColor>>, width: usize) -> Image<LinearColor> {
+    let columns: Vec<Vec<LinearColor>> = (0..width)
+        .map(|x| rows.iter().map(|row| row[x]).collect())
+        .collect();
+    Image::from_vec_vec(&columns)
+}
+
+// Lit une ligne d'en-tête terminée par '\n' à partir de `*pos`.
+fn read_line(bytes: &[u8], pos: &mut usize) -> Result<String, String> {
+    let start = *pos;
+    while *pos < bytes.len() && bytes[*pos] != b'\n' {
+        *pos += 1;
+    }
+    if *pos >= bytes.len() {
+        return Err("unexpected end of header".to_string());
+    }
+    *pos += 1;
+    Ok(String::from_utf8_lossy(&bytes[start..*pos - 1]).into_owned())
+}
+
+/// Lit une image au format Radiance RGBE. Seule l'orientation standard `-Y h +X w` (lignes de
+/// haut en bas) est gérée.
+pub fn read_radiance(bytes: &[u8]) -> Result<Image<LinearColor>, String> {
+    let mut pos = 0;
+    let magic = read_line(bytes, &mut pos)?;
+    if !magic.starts_with("#?") {
+        return Err("not a Radiance file".to_string());
+    }
+    loop {
+        let line = read_line(bytes, &mut pos)?;
+        if line.is_empty() {
+            break;
+        }
+        if line.starts_with("FORMAT=") && line != "FORMAT=32-bit_rle_rgbe" {
+            return Err(format!("unsupported format {}", line));
+        }
+    }
+    let resolution = read_line(bytes, &mut pos)?;
+    let tokens: Vec<&str> = resolution.split_whitespace().collect();
+    if tokens.len() != 4 || tokens[0] != "-Y" || tokens[2] != "+X" {
+        return Err(format!("unsupported resolution line {}", resolution));
+    }
+    let height: usize = tokens[1].parse().map_err(|_| "invalid height".to_string())?;
+    let width: usize = tokens[3].parse().map_err(|_| "invalid width".to_string())?;
+    if width == 0 || height == 0 {
+        return Err("empty image".to_string());
+    }
+
+    let mut rows = Vec::with_capacity(height);
+    let mut scanline = vec![[0u8; 4]; width];
+    for _ in 0..height {
+        read_scanline(bytes, &mut pos, &mut scanline)?;
+        rows.push(scanline.iter().map(rgbe_to_color).collect());
+    }
+    Ok(image_from_rows(rows, width))
+}
+
+// Lit une ligne de pixels RGBE, compressée par plages (une composante après l'autre) ou non.
+fn read_scanline(bytes: &[u8], pos: &mut usize, scanline: &mut [[u8; 4]]) -> Result<(), String> {
+    let width = scanline.len();
+    let eof = || "unexpected end of data".to_string();
+    if *pos + 4 > bytes.len() {
+        return Err(eof());
+    }
+    let header = &bytes[*pos..*pos + 4];
+    let compressed = width >= 8 && width < 0x8000 && header[0] == 2 && header[1] == 2 &&
+                     header[2] & 0x80 == 0;
+    if !compressed {
+        for pixel in scanline.iter_mut() {
+            if *pos + 4 > bytes.len() {
+                return Err(eof());
+            }
+            pixel.copy_from_slice(&bytes[*pos..*pos + 4]);
+            *pos += 4;
+        }
+        return Ok(());
+    }
+
+    if ((header[2] as usize) << 8 | header[3] as usize) != width {
+        return Err("invalid scanline width".to_string());
+    }
+    *pos += 4;
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let count = *bytes.get(*pos).ok_or_else(&eof)? as usize;
+            *pos += 1;
+            if count > 128 {
+                // Une plage de `count - 128` fois la même valeur
+                let count = count - 128;
+                let value = *bytes.get(*pos).ok_or_else(&eof)?;
+                *pos += 1;
+                if x + count > width {
+                    return Err("run overflows the scanline".to_string());
+                }
+                for pixel in &mut scanline[x..x + count] {
+                    pixel[channel] = value;
+                }
+                x += count;
+            } else {
+                // `count` valeurs différentes
+                if count == 0 || x + count > width {
+                    return Err("invalid run".to_string());
+                }
+                if *pos + count > bytes.len() {
+                    return Err(eof());
+                }
+                for (pixel, &value) in scanline[x..x + count]
+                        .iter_mut()
+                        .zip(&bytes[*pos..*pos + count]) {
+                    pixel[channel] = value;
+                }
+                *pos += count;
+                x += count;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Les trois mantisses partagent l'exposant e : composante = (mantisse + 0.5) * 2^(e - 136).
+fn rgbe_to_color(rgbe: &[u8; 4]) -> LinearColor {
+    if rgbe[3] == 0 {
+        return LinearColor::new_black();
+    }
+    let factor = 2f32.powi(rgbe[3] as i32 - 136);
+    LinearColor::new(FloatColor::new((rgbe[0] as f32 + 0.5) * factor,
+                                     (rgbe[1] as f32 + 0.5) * factor,
+                                     (rgbe[2] as f32 + 0.5) * factor))
+}
+
+// Lit un élément de l'en-tête PFM, séparé des autres par des blancs.
+fn read_token(bytes: &[u8], pos: &mut usize) -> Result<String, String> {
+    while *pos < bytes.len() && (bytes[*pos] as char).is_whitespace() {
+        *pos += 1;
+    }
+    let start = *pos;
+    while *pos < bytes.len() && !(bytes[*pos] as char).is_whitespace() {
+        *pos += 1;
+    }
+    if start == *pos || *pos >= bytes.len() {
+        return Err("unexpected end of header".to_string());
+    }
+    Ok(String::from_utf8_lossy(&bytes[start..*pos]).into_owned())
+}
+
+/// Lit une image au format PFM : en couleur (`PF`) ou en niveaux de gris (`Pf`). Le signe du
+/// facteur d'échelle donne le boutisme des flottants, et les lignes sont stockées de bas en
+/// haut.
+pub fn read_pfm(bytes: &[u8]) -> Result<Image<LinearColor>, String> {
+    let mut pos = 0;
+    let channels = match read_token(bytes, &mut pos)?.as_str() {
+        "PF" => 3,
+        "Pf" => 1,
+        _ => return Err("not a PFM file".to_string()),
+    };
+    let width: usize = read_token(bytes, &mut pos)?
+        .parse()
+        .map_err(|_| "invalid width".to_string())?;
+    let height: usize = read_token(bytes, &mut pos)?
+        .parse()
+        .map_err(|_| "invalid height".to_string())?;
+    let scale: f32 = read_token(bytes, &mut pos)?
+        .parse()
+        .map_err(|_| "invalid scale".to_string())?;
+    if width == 0 || height == 0 {
+        return Err("empty image".to_string());
+    }
+    // Un seul blanc sépare l'en-tête des données.
+    pos += 1;
+    if pos + width * height * channels * 4 > bytes.len() {
+        return Err("unexpected end of data".to_string());
+    }
+
+    let little_endian = scale < 0.0;
+    let read_float = |offset: usize| -> f32 {
+        let b = &bytes[offset..offset + 4];
+        let bits = if little_endian {
+            (b[0] as u32) | (b[1] as u32) << 8 | (b[2] as u32) << 16 | (b[3] as u32) << 24
+        } else {
+            (b[3] as u32) | (b[2] as u32) << 8 | (b[1] as u32) << 16 | (b[0] as u32) << 24
+        };
+        float_from_bits(bits)
+    };
+    let mut rows = Vec::with_capacity(height);
+    for y in 0..height {
+        let row_start = pos + (height - 1 - y) * width * channels * 4;
+        rows.push((0..width)
+                      .map(|x| {
+            let offset = row_start + x * channels * 4;
+            let (r, g, b) = if channels == 3 {
+                (read_float(offset), read_float(offset + 4), read_float(offset + 8))
+            } else {
+                let value = read_float(offset);
+                (value, value, value)
+            };
+            LinearColor::new(FloatColor::new(r, g, b))
+        })
+                      .collect());
+    }
+    Ok(image_from_rows(rows, width))
+}
+
+// Reconstruit un flottant IEEE 754 simple précision à partir de sa représentation binaire.
+fn float_from_bits(bits: u32) -> f32 {
+    let sign = if bits >> 31 == 0 { 1.0 } else { -1.0 };
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+    match exponent {
+        0 => sign * mantissa as f32 * 2f32.powi(-126) / 8_388_608.0,
+        0xff if mantissa == 0 => sign * f32::INFINITY,
+        0xff => f32::NAN,
+        _ => sign * (mantissa | 0x80_0000) as f32 * 2f32.powi(exponent - 127) / 8_388_608.0,
+    }
+}
+
+/// Le type des composantes d'une image OpenEXR.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ExrPixelType {
+    /// Flottants 16 bits : la moitié de la taille, et une précision suffisante pour l'étalonnage.
+    Half,
+    /// Flottants 32 bits.
+    Float,
+}
+
+impl Default for ExrPixelType {
+    fn default() -> Self {
+        ExrPixelType::Half
+    }
+}
+
+// L'extension du fichier, en minuscules.
+fn file_extension(pathname: &str) -> Option<String> {
+    Path::new(pathname)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+}
+
+/// Indique si l'extension du fichier est celle d'un format à grande dynamique que l'on sait
+/// écrire (`.exr`, `.hdr` ou `.pfm`).
+pub fn is_hdr_file(pathname: &str) -> bool {
+    match file_extension(pathname).as_ref().map(|ext| ext.as_str()) {
+        Some("exr") | Some("hdr") | Some("pfm") => true,
+        _ => false,
+    }
+}
+
+/// Indique si l'extension du fichier est celle d'une image OpenEXR (`.exr`).
+pub fn is_exr_file(pathname: &str) -> bool {
+    file_extension(pathname).map_or(false, |ext| ext == "exr")
+}
+
+/// Écrit une image HDR, dont le format est choisi selon l'extension du fichier (`.exr`, `.hdr`
+/// ou `.pfm`). `pixel_type` n'est utilisé que pour l'OpenEXR.
+pub fn write_hdr_file(pathname: &str,
+                      image: &Image<LinearColor>,
+                      pixel_type: ExrPixelType)
+                      -> Result<(), String> {
+    let bytes = match file_extension(pathname).as_ref().map(|ext| ext.as_str()) {
+        Some("exr") => return write_exr_file(pathname, &[("", image)], pixel_type),
+        Some("hdr") => write_radiance(image),
+        Some("pfm") => write_pfm(image),
+        _ => return Err(format!("Unknown HDR image format : {}", pathname)),
+    };
+    write_bytes(pathname, &bytes)
+}
+
+/// Écrit une image OpenEXR contenant les calques `layers` (voir `write_exr`).
+pub fn write_exr_file(pathname: &str,
+                      layers: &[(&str, &Image<LinearColor>)],
+                      pixel_type: ExrPixelType)
+                      -> Result<(), String> {
+    let bytes = write_exr(layers, pixel_type)?;
+    write_bytes(pathname, &bytes)
+}
+
+// Écrit le contenu d'un fichier.
+fn write_bytes(pathname: &str, bytes: &[u8]) -> Result<(), String> {
+    File::create(pathname)
+        .and_then(|mut file| file.write_all(bytes))
+        .map_err(|e| format!("Could not write {} : {}", pathname, e))
+}
+
+/// Écrit une image au format Radiance RGBE, les lignes compressées par plages quand leur largeur
+/// le permet.
+pub fn write_radiance(image: &Image<LinearColor>) -> Vec<u8> {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let mut bytes = format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n", height, width)
+        .into_bytes();
+    let compressed = width >= 8 && width < 0x8000;
+    for y in 0..height {
+        let scanline: Vec<[u8; 4]> = (0..width)
+            .map(|x| color_to_rgbe(&image.get_pixel_at(x as u32, y as u32)))
+            .collect();
+        if !compressed {
+            for pixel in &scanline {
+                bytes.extend_from_slice(pixel);
+            }
+            continue;
+        }
+        bytes.extend_from_slice(&[2, 2, (width >> 8) as u8, (width & 0xff) as u8]);
+        for channel in 0..4 {
+            let values: Vec<u8> = scanline.iter().map(|pixel| pixel[channel]).collect();
+            write_runs(&values, &mut bytes);
+        }
+    }
+    bytes
+}
+
+// Compresse une composante d'une ligne : les plages d'au moins 4 valeurs identiques sont codées
+// par leur longueur (+ 128) et leur valeur, le reste par paquets de valeurs littérales.
+fn write_runs(values: &[u8], bytes: &mut Vec<u8>) {
+    let mut x = 0;
+    while x < values.len() {
+        // Le début de la prochaine plage assez longue, et sa longueur
+        let mut run_start = x;
+        let mut run_length = 0;
+        while run_start < values.len() {
+            run_length = 1;
+            while run_start + run_length < values.len() && run_length < 127 &&
+                  values[run_start + run_length] == values[run_start] {
+                run_length += 1;
+            }
+            if run_length >= 4 {
+                break;
+            }
+            run_start += run_length;
+        }
+        // Les valeurs littérales qui précèdent la plage
+        while x < run_start {
+            let count = ::std::cmp::min(128, run_start - x);
+            bytes.push(count as u8);
+            bytes.extend_from_slice(&values[x..x + count]);
+            x += count;
+        }
+        if run_length >= 4 {
+            bytes.push(128 + run_length as u8);
+            bytes.push(values[run_start]);
+            x = run_start + run_length;
+        }
+    }
+}
+
+// Les trois composantes partagent l'exposant de la plus grande : c = m * 2^e avec m dans
+// [0.5, 1[ pour la plus grande composante.
+fn color_to_rgbe(color: &LinearColor) -> [u8; 4] {
+    let color = color.get_internal_color();
+    let (r, g, b) = (color.r().max(0.0), color.g().max(0.0), color.b().max(0.0));
+    let max = r.max(g).max(b);
+    if max < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+    let mut exponent = max.log2().floor() as i32 + 1;
+    if max >= 2f32.powi(exponent) {
+        exponent += 1;
+    } else if max < 2f32.powi(exponent - 1) {
+        exponent -= 1;
+    }
+    let factor = 256.0 / 2f32.powi(exponent);
+    [(r * factor) as u8, (g * factor) as u8, (b * factor) as u8, (exponent + 128) as u8]
+}
+
+/// Écrit une image au format PFM en couleur, en petit boutiste : les lignes sont stockées de bas
+/// en haut.
+pub fn write_pfm(image: &Image<LinearColor>) -> Vec<u8> {
+    let (width, height) = (image.width(), image.height());
+    let mut bytes = format!("PF\n{} {}\n-1.0\n", width, height).into_bytes();
+    for y in (0..height).rev() {
+        for x in 0..width {
+            let color = image.get_pixel_at(x, y);
+            let color = color.get_internal_color();
+            for &value in &[color.r(), color.g(), color.b()] {
+                push_u32(&mut bytes, float_to_bits(value));
+            }
+        }
+    }
+    bytes
+}
+
+/// Écrit une image OpenEXR non compressée, ligne par ligne, contenant les calques `layers` : des
+/// couples (nom, image) dont les composantes sont nommées `<nom>.R`, `<nom>.G` et `<nom>.B`, ou
+/// `R`, `G` et `B` pour le calque sans nom. Toutes les images doivent avoir la même taille.
+pub fn write_exr(layers: &[(&str, &Image<LinearColor>)],
+                 pixel_type: ExrPixelType)
+                 -> Result<Vec<u8>, String> {
+    let (width, height) = match layers.first() {
+        Some(&(_, image)) => (image.width(), image.height()),
+        None => return Err("no layer to write".to_string()),
+    };
+    if layers.iter().any(|&(_, image)| (image.width(), image.height()) != (width, height)) {
+        return Err("the layers must have the same size".to_string());
+    }
+    if width == 0 || height == 0 {
+        return Err("empty image".to_string());
+    }
+
+    // Les composantes doivent être triées par nom, dans l'en-tête comme dans les lignes.
+    let mut channels: Vec<(String, &Image<LinearColor>, usize)> = vec![];
+    for &(name, image) in layers {
+        for (component, suffix) in ["R", "G", "B"].iter().enumerate() {
+            let channel = if name.is_empty() {
+                suffix.to_string()
+            } else {
+                format!("{}.{}", name, suffix)
+            };
+            channels.push((channel, image, component));
+        }
+    }
+    channels.sort_by(|a, b| a.0.cmp(&b.0));
+    if channels.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+        return Err("two layers have the same name".to_string());
+    }
+
+    let (type_code, value_size) = match pixel_type {
+        ExrPixelType::Half => (1, 2),
+        ExrPixelType::Float => (2, 4),
+    };
+    let long_names = channels.iter().any(|channel| channel.0.len() > 31);
+
+    let mut bytes = vec![0x76, 0x2f, 0x31, 0x01];
+    push_u32(&mut bytes, if long_names { 2 | 0x400 } else { 2 });
+
+    let mut channel_list = vec![];
+    for channel in &channels {
+        channel_list.extend_from_slice(channel.0.as_bytes());
+        channel_list.push(0);
+        push_u32(&mut channel_list, type_code);
+        // pLinear, trois octets réservés, puis l'échantillonnage en x et en y
+        channel_list.extend_from_slice(&[0, 0, 0, 0]);
+        push_u32(&mut channel_list, 1);
+        push_u32(&mut channel_list, 1);
+    }
+    channel_list.push(0);
+    let mut window = vec![];
+    for &value in &[0, 0, width - 1, height - 1] {
+        push_u32(&mut window, value);
+    }
+    push_attribute(&mut bytes, "channels", "chlist", &channel_list);
+    push_attribute(&mut bytes, "compression", "compression", &[0]);
+    push_attribute(&mut bytes, "dataWindow", "box2i", &window);
+    push_attribute(&mut bytes, "displayWindow", "box2i", &window);
+    push_attribute(&mut bytes, "lineOrder", "lineOrder", &[0]);
+    push_attribute(&mut bytes, "pixelAspectRatio", "float", &[0, 0, 0x80, 0x3f]);
+    push_attribute(&mut bytes, "screenWindowCenter", "v2f", &[0; 8]);
+    push_attribute(&mut bytes, "screenWindowWidth", "float", &[0, 0, 0x80, 0x3f]);
+    bytes.push(0);
+
+    // La table des positions des lignes dans le fichier, puis les lignes : leur ordonnée, leur
+    // taille, et chaque composante l'une après l'autre.
+    let line_size = width as usize * channels.len() * value_size;
+    let table_end = bytes.len() + height as usize * 8;
+    for y in 0..height as usize {
+        let offset = (table_end + y * (8 + line_size)) as u64;
+        push_u32(&mut bytes, offset as u32);
+        push_u32(&mut bytes, (offset >> 32) as u32);
+    }
+    for y in 0..height {
+        push_u32(&mut bytes, y);
+        push_u32(&mut bytes, line_size as u32);
+        for &(_, image, component) in &channels {
+            for x in 0..width {
+                let color = image.get_pixel_at(x, y);
+                let color = color.get_internal_color();
+                let value = [color.r(), color.g(), color.b()][component];
+                match pixel_type {
+                    ExrPixelType::Half => {
+                        let half = float_to_half(value);
+                        bytes.extend_from_slice(&[half as u8, (half >> 8) as u8]);
+                    }
+                    ExrPixelType::Float => push_u32(&mut bytes, float_to_bits(value)),
+                }
+            }
+        }
+    }
+    Ok(bytes)
+}
+
+// Ajoute un attribut de l'en-tête OpenEXR : son nom, son type, sa taille et sa valeur.
+fn push_attribute(bytes: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    bytes.extend_from_slice(name.as_bytes());
+    bytes.push(0);
+    bytes.extend_from_slice(kind.as_bytes());
+    bytes.push(0);
+    push_u32(bytes, value.len() as u32);
+    bytes.extend_from_slice(value);
+}
+
+// Ajoute un entier de 32 bits en petit boutiste.
+fn push_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&[value as u8, (value >> 8) as u8, (value >> 16) as u8,
+                              (value >> 24) as u8]);
+}
+
+// L'exposant e tel que 2^e <= value < 2^(e + 1), pour un flottant positif normalisé.
+fn exponent_of(value: f32) -> i32 {
+    let exponent = value.log2().floor() as i32;
+    if 2f32.powi(exponent) > value {
+        exponent - 1
+    } else if 2f32.powi(exponent + 1) <= value {
+        exponent + 1
+    } else {
+        exponent
+    }
+}
+
+// La représentation binaire IEEE 754 simple précision d'un flottant (l'inverse de
+// `float_from_bits`).
+fn float_to_bits(value: f32) -> u32 {
+    if value.is_nan() {
+        return 0x7fc0_0000;
+    }
+    let sign = if value.is_sign_negative() { 1 << 31 } else { 0 };
+    let value = value.abs();
+    if value == 0.0 {
+        sign
+    } else if value.is_infinite() {
+        sign | 0x7f80_0000
+    } else if value < 2f32.powi(-126) {
+        // Dénormalisé : la mantisse est la valeur en multiples de 2^-149.
+        sign | (value * 2f32.powi(126) * 8_388_608.0) as u32
+    } else {
+        let exponent = exponent_of(value);
+        let mantissa = (value / 2f32.powi(exponent) - 1.0) * 8_388_608.0;
+        sign | ((exponent + 127) as u32) << 23 | mantissa as u32
+    }
+}
+
+// La représentation binaire IEEE 754 demi-précision d'un flottant, arrondie au plus proche. Les
+// valeurs trop grandes deviennent infinies.
+fn float_to_half(value: f32) -> u16 {
+    if value.is_nan() {
+        return 0x7e00;
+    }
+    let sign = if value.is_sign_negative() { 0x8000 } else { 0 };
+    let value = value.abs();
+    if value >= 65_520.0 {
+        sign | 0x7c00
+    } else if value < 2f32.powi(-14) {
+        // Dénormalisé (ou le plus petit normalisé si l'arrondi y mène) : multiples de 2^-24.
+        sign | (value * 2f32.powi(24)).round() as u16
+    } else {
+        let exponent = exponent_of(value);
+        let mantissa = ((value / 2f32.powi(exponent) - 1.0) * 1024.0).round() as u16;
+        // Une mantisse arrondie à 1024 passe à l'exposant suivant.
+        sign | (((exponent + 15) as u16) << 10) + mantissa
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use color_float::{LinearColor, FloatColor, Color};
+    use img::Image;
+    use super::{read_radiance, read_pfm, write_radiance, write_pfm, write_exr, float_to_bits,
+                float_from_bits, float_to_half, ExrPixelType};
+
+    // Une image de 10x3 en dégradé, avec des valeurs au-delà de 1.
+    fn gradient() -> Image<LinearColor> {
+        let columns: Vec<Vec<LinearColor>> = (0..10)
+            .map(|x| {
+                (0..3)
+                    .map(|y| {
+                        let value = x as f32 * 0.75 + y as f32 * 10.0;
+                        LinearColor::new(FloatColor::new(value, 0.5, 1000.0))
+                    })
+                    .collect()
+            })
+            .collect();
+        Image::from_vec_vec(&columns)
+    }
+
+    #[test]
+    fn test_read_radiance() {
+        // Une image de 8x2 : la première ligne compressée (une plage puis des valeurs
+        // littérales), la seconde non compressée.
+        let mut bytes = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 2 +X 8\n".to_vec();
+        bytes.extend_from_slice(&[2, 2, 0, 8]);
+        bytes.extend_from_slice(&[136, 128]);
+        bytes.extend_from_slice(&[136, 64]);
+        bytes.extend_from_slice(&[4, 0, 0, 0, 0, 132, 255]);
+        bytes.extend_from_slice(&[136, 128]);
+        for _ in 0..8 {
+            bytes.extend_from_slice(&[64, 0, 0, 130]);
+        }
+        let image = read_radiance(&bytes).unwrap();
+        assert_eq!((image.width(), image.height()), (8, 2));
+
+        let top = image.get_pixel_at(0, 0);
+        let top = top.get_internal_color();
+        assert!((top.r() - 128.5 / 256.0).abs() < 1e-6);
+        assert!((top.g() - 64.5 / 256.0).abs() < 1e-6);
+        assert!((top.b() - 0.5 / 256.0).abs() < 1e-6);
+        let top_right = image.get_pixel_at(7, 0);
+        assert!((top_right.get_internal_color().b() - 255.5 / 256.0).abs() < 1e-6);
+        let bottom = image.get_pixel_at(3, 1);
+        assert!((bottom.get_internal_color().r() - 64.5 * 4.0 / 256.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_read_pfm() {
+        // Une image de 2x2 en petit boutiste : la première ligne stockée est celle du bas.
+        let mut bytes = b"PF\n2 2\n-1.0\n".to_vec();
+        // Les représentations binaires de 1, 2, 3, 0, 0, 0, 4, 5, 6, 0.5, 0.5, 0.5
+        for &bits in &[0x3f80_0000u32, 0x4000_0000, 0x4040_0000, 0, 0, 0, 0x4080_0000,
+                       0x40a0_0000, 0x40c0_0000, 0x3f00_0000, 0x3f00_0000, 0x3f00_0000] {
+            bytes.extend_from_slice(&[bits as u8, (bits >> 8) as u8, (bits >> 16) as u8,
+                                      (bits >> 24) as u8]);
+        }
+        let image = read_pfm(&bytes).unwrap();
+        let bottom_left = image.get_pixel_at(0, 1);
+        assert_eq!(bottom_left.get_internal_color().b(), 3.0);
+        let top_left = image.get_pixel_at(0, 0);
+        assert_eq!(top_left.get_internal_color().g(), 5.0);
+        assert_eq!(image.get_pixel_at(1, 0).get_internal_color().r(), 0.5);
+    }
+
+    #[test]
+    fn test_float_representations() {
+        for &value in &[1.0f32, -2.5, 0.1, 3.0e-40, 65_504.0, 1.0e30, 0.0] {
+            assert_eq!(float_from_bits(float_to_bits(value)), value);
+        }
+        assert_eq!(float_to_bits(1.0), 0x3f80_0000);
+        assert_eq!(float_to_half(1.0), 0x3c00);
+        assert_eq!(float_to_half(-2.0), 0xc000);
+        assert_eq!(float_to_half(65_504.0), 0x7bff);
+        assert_eq!(float_to_half(1.0e6), 0x7c00);
+        assert_eq!(float_to_half(2f32.powi(-24)), 0x0001);
+        assert_eq!(float_to_half(1.9999), 0x4000);
+    }
+
+    #[test]
+    fn test_write_radiance_and_pfm() {
+        let image = gradient();
+        let radiance = read_radiance(&write_radiance(&image)).unwrap();
+        let pfm = read_pfm(&write_pfm(&image)).unwrap();
+        assert_eq!((pfm.width(), pfm.height()), (10, 3));
+        for x in 0..10 {
+            for y in 0..3 {
+                let expected = image.get_pixel_at(x, y);
+                let expected = expected.get_internal_color();
+                let pixel = pfm.get_pixel_at(x, y);
+                assert_eq!(pixel.get_internal_color().r(), expected.r());
+                assert_eq!(pixel.get_internal_color().b(), expected.b());
+                // Les mantisses RGBE ont 8 bits, relativement à la plus grande composante.
+                let pixel = radiance.get_pixel_at(x, y);
+                assert!((pixel.get_internal_color().r() - expected.r()).abs() < 1000.0 / 128.0);
+                assert!((pixel.get_internal_color().b() - expected.b()).abs() < 1000.0 / 128.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_exr() {
+        let image = gradient();
+        let bytes = write_exr(&[("", &image), ("diffuse", &image)], ExrPixelType::Half).unwrap();
+        assert_eq!(&bytes[0..4], &[0x76, 0x2f, 0x31, 0x01]);
+        // Les composantes sont triées par nom dans l'en-tête.
+        let header = String::from_utf8_lossy(&bytes);
+        let positions: Vec<usize> = ["\0B\0", "\0G\0", "\0R\0", "diffuse.B", "diffuse.R"]
+            .iter()
+            .map(|name| header.find(name).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        // Les lignes contiennent 3 composantes de 10 pixels, en demi-flottants ou en flottants.
+        let half = write_exr(&[("", &image)], ExrPixelType::Half).unwrap();
+        let float = write_exr(&[("", &image)], ExrPixelType::Float).unwrap();
+        assert_eq!(float.len() - half.len(), 3 * 3 * 10 * 2);
+        // La dernière valeur est la composante R du dernier pixel de la dernière ligne.
+        let end = float.len();
+        let last = (float[end - 4] as u32) | (float[end - 3] as u32) << 8 |
+                   (float[end - 2] as u32) << 16 | (float[end - 1] as u32) << 24;
+        assert_eq!(float_from_bits(last), 9.0 * 0.75 + 20.0);
+    }
+}
@@ -5,6 +5,7 @@ use image::Pixel as ImgPixel;
 use std::vec;
 use std::path::Path;
 use std::fs::File;
+use color_float::LinearColor;
 
 pub trait Pixel: Copy {
     fn to_rgb_pixel(&self) -> (u8, u8, u8);
@@ -34,6 +35,18 @@ impl Pixel for RGBAPixel {
     }
 }
 
+// Les images à grande dynamique (cartes d'environnement HDR) : la couleur est bornée à
+// l'export.
+impl Pixel for LinearColor {
+    fn to_rgb_pixel(&self) -> (u8, u8, u8) {
+        (*self).into()
+    }
+
+    fn to_rgba_pixel(&self) -> (u8, u8, u8, u8) {
+        (*self).into()
+    }
+}
+
 #[derive(Clone)]
 pub struct Image<T: Pixel> {
     width: usize,
@@ -51,6 +64,8 @@ impl<T: Pixel> Image<T> {
         }
     }
 
+    /** Écrit l'image sur 8 bits, en JPEG si l'extension du fichier est `.jpg` ou `.jpeg`, en PNG
+     * sinon. Les pixels sont écrits tels quels : ils doivent déjà être encodés en sRGB. */
     pub fn write_to_file(&self, pathname: &str) {
         let mut buffer = image::ImageBuffer::new(self.width as u32, self.height as u32);
 
@@ -59,9 +74,16 @@ impl<T: Pixel> Image<T> {
             *pixel = image::Rgb::from_channels(render_pix.0, render_pix.1, render_pix.2, 0);
         }
 
-        let file_output = &mut File::create(&Path::new(pathname)).unwrap();
+        let path = Path::new(pathname);
+        let format = match path.extension()
+                  .and_then(|ext| ext.to_str())
+                  .map(|ext| ext.to_lowercase()) {
+            Some(ref ext) if ext == "jpg" || ext == "jpeg" => image::JPEG,
+            _ => image::PNG,
+        };
+        let file_output = &mut File::create(path).unwrap();
         image::ImageRgb8(buffer)
-            .save(file_output, image::PNG)
+            .save(file_output, format)
             .expect("Error while saving file");
     }
 
@@ -0,0 +1,65 @@
+use scene::World;
+use ray::Ray;
+use color_float::LinearColor;
+use renderer::TextureRegister;
+use sampler::Sampler;
+use material::ShadingCoordinateSystem;
+use tools::monte_carlo;
+use integrator::{Integrator, facing_normal, escaped_radiance, RAY_EPSILON};
+
+/** Un intégrateur qui calcule l'occlusion ambiante au premier point touché : la proportion de
+l'hémisphère (pondérée par un cosinus) qui n'est pas masquée par un objet à moins de `radius`. */
+pub struct AmbientOcclusionIntegrator {
+    background_color: LinearColor,
+
+    /// La distance à partir de laquelle on arrête de regarder l'occlusion ambiante
+    radius: f32,
+
+    /// Le nombre de rayons lancés pour chaque point
+    samples: u32,
+}
+
+impl AmbientOcclusionIntegrator {
+    pub fn new(background_color: LinearColor, radius: f32, samples: u32) -> Self {
+        AmbientOcclusionIntegrator {
+            background_color: background_color,
+            radius: radius,
+            samples: samples,
+        }
+    }
+}
+
+impl Integrator for AmbientOcclusionIntegrator {
+    fn radiance(&self,
+                ray: &Ray,
+                world: &World,
+                _: &TextureRegister,
+                sampler: &mut Sampler)
+                -> LinearColor {
+        let mut ray = *ray;
+        let intersection = match world.get_intersection_point(&mut ray) {
+            Some(intersection) => intersection,
+            None => return escaped_radiance(&ray, world, self.background_color),
+        };
+
+        let frag = intersection.fragment();
+        let normal = facing_normal(frag, &ray);
+        let frame = ShadingCoordinateSystem::new_from_normal(&normal);
+        let origin = frag.position + normal * RAY_EPSILON;
+
+        let mut visible: u32 = 0;
+        for _ in 0..self.samples {
+            // Les directions sont unitaires : max_t est donc une distance.
+            let direction = frame.local_into_world_space(&monte_carlo::sample_cosine_direction(
+                sampler.get_2d()));
+            let mut occlusion_ray = Ray::new(origin, direction);
+            occlusion_ray.max_t = self.radius;
+            occlusion_ray.time = ray.time;
+            if !world.is_occluded(&mut occlusion_ray) {
+                visible += 1;
+            }
+        }
+
+        LinearColor::new_white() * (visible as f32 / self.samples as f32)
+    }
+}
@@ -0,0 +1,33 @@
+use scene::World;
+use ray::Ray;
+use color_float::LinearColor;
+use renderer::TextureRegister;
+use sampler::Sampler;
+use integrator::{Integrator, escaped_radiance};
+
+/** L'intégrateur historique : on demande simplement au matériau du premier objet touché sa
+couleur. */
+pub struct DirectIntegrator {
+    background_color: LinearColor,
+}
+
+impl DirectIntegrator {
+    pub fn new(background_color: LinearColor) -> Self {
+        DirectIntegrator { background_color: background_color }
+    }
+}
+
+impl Integrator for DirectIntegrator {
+    fn radiance(&self,
+                ray: &Ray,
+                world: &World,
+                textures: &TextureRegister,
+                sampler: &mut Sampler)
+                -> LinearColor {
+        let mut ray = *ray;
+        match world.get_intersection_point(&mut ray) {
+            Some(intersection) => intersection.get_point_color(world, textures, sampler),
+            None => escaped_radiance(&ray, world, self.background_color),
+        }
+    }
+}
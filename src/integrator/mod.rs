@@ -0,0 +1,96 @@
+pub mod direct;
+pub mod ambient_occlusion;
+pub mod normals;
+pub mod whitted;
+pub mod path_tracer;
+
+use scene::World;
+use ray::{Ray, Fragment};
+use color_float::LinearColor;
+use renderer::TextureRegister;
+use sampler::Sampler;
+use math::{Vector3f, VectorialOperations};
+use integrator::direct::DirectIntegrator;
+use integrator::ambient_occlusion::AmbientOcclusionIntegrator;
+use integrator::normals::NormalsIntegrator;
+use integrator::whitted::WhittedIntegrator;
+use integrator::path_tracer::PathTracer;
+
+/// Le décalage appliqué à l'origine des rayons secondaires pour éviter qu'ils intersectent la
+/// surface dont ils partent.
+pub const RAY_EPSILON: f32 = 1e-4;
+
+/** Un intégrateur calcule la radiance qui arrive le long d'un rayon émis par la caméra. C'est
+lui qui décide comment la lumière est transportée dans la scène. */
+pub trait Integrator {
+    fn radiance(&self,
+                ray: &Ray,
+                world: &World,
+                textures: &TextureRegister,
+                sampler: &mut Sampler)
+                -> LinearColor;
+}
+
+/// Les différentes méthodes de calcul du transport de la lumière. Le choix se fait dans le
+/// fichier de configuration du renderer, par exemple :
+/// `"integrator": {"PathTracer": {"max_depth": 8}}`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum IntegratorFactory {
+    /// L'éclairage direct historique : couleur du matériau au premier point d'intersection.
+    Direct,
+    /// L'occlusion ambiante, calculée avec `samples` rayons de longueur `radius`.
+    AmbientOcclusion { radius: f32, samples: u32 },
+    /// Affiche les normales des surfaces, pour déboguer la géométrie.
+    Normals,
+    /// Le lancer de rayon de Whitted : réflexions et réfractions parfaites, jusqu'à
+    /// `max_depth` intersections.
+    Whitted { max_depth: u32 },
+    /// Un path tracer, qui suit les rebonds de la lumière jusqu'à `max_depth` intersections.
+    PathTracer { max_depth: u32 },
+}
+
+impl IntegratorFactory {
+    /// Crée l'intégrateur. `background_color` est la radiance des rayons qui ne touchent rien,
+    /// quand la scène n'a pas de lumière d'environnement.
+    pub fn create_integrator(&self, background_color: LinearColor) -> Box<Integrator> {
+        match *self {
+            IntegratorFactory::Direct => Box::new(DirectIntegrator::new(background_color)),
+            IntegratorFactory::AmbientOcclusion { radius, samples } => {
+                Box::new(AmbientOcclusionIntegrator::new(background_color, radius, samples))
+            }
+            IntegratorFactory::Normals => Box::new(NormalsIntegrator::new(background_color)),
+            IntegratorFactory::Whitted { max_depth } => {
+                Box::new(WhittedIntegrator::new(background_color, max_depth))
+            }
+            IntegratorFactory::PathTracer { max_depth } => {
+                Box::new(PathTracer::new(background_color, max_depth))
+            }
+        }
+    }
+}
+
+impl Default for IntegratorFactory {
+    fn default() -> Self {
+        IntegratorFactory::Direct
+    }
+}
+
+/// Renvoie la normale unitaire du fragment, orientée du côté d'où vient le rayon : les surfaces
+/// sont éclairées des deux côtés.
+pub fn facing_normal(frag: &Fragment, ray: &Ray) -> Vector3f {
+    let normal = frag.normal / frag.normal.norm();
+    if normal.dot_product(&ray.slope()) > 0.0 {
+        -normal
+    } else {
+        normal
+    }
+}
+
+/// La radiance d'un rayon qui ne touche rien : celle de la lumière d'environnement s'il y en a
+/// une, `background_color` sinon.
+pub fn escaped_radiance(ray: &Ray, world: &World, background_color: LinearColor) -> LinearColor {
+    match world.environment() {
+        Some(environment) => environment.radiance(&ray.slope()),
+        None => background_color,
+    }
+}
@@ -0,0 +1,40 @@
+use scene::World;
+use ray::Ray;
+use color_float::{LinearColor, FloatColor, Color};
+use renderer::TextureRegister;
+use sampler::Sampler;
+use math::VectorialOperations;
+use integrator::{Integrator, escaped_radiance};
+
+/** Un intégrateur de débogage, qui affiche la normale des surfaces touchées : chaque composante
+de la normale, comprise entre -1 et 1, est ramenée entre 0 et 1. */
+pub struct NormalsIntegrator {
+    background_color: LinearColor,
+}
+
+impl NormalsIntegrator {
+    pub fn new(background_color: LinearColor) -> Self {
+        NormalsIntegrator { background_color: background_color }
+    }
+}
+
+impl Integrator for NormalsIntegrator {
+    fn radiance(&self,
+                ray: &Ray,
+                world: &World,
+                _: &TextureRegister,
+                _: &mut Sampler)
+                -> LinearColor {
+        let mut ray = *ray;
+        match world.get_intersection_point(&mut ray) {
+            Some(intersection) => {
+                let frag = intersection.fragment();
+                let normal = frag.normal / frag.normal.norm();
+                LinearColor::new(FloatColor::new(0.5 * (1f32 + normal.x),
+                                                 0.5 * (1f32 + normal.y),
+                                                 0.5 * (1f32 + normal.z)))
+            }
+            None => escaped_radiance(&ray, world, self.background_color),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+/// Un path tracer : à chaque intersection on ajoute la lumière émise par la surface et
+/// l'éclairage direct des lumières (next event estimation), puis on continue le chemin dans une
+/// direction tirée selon la BSDF du matériau. Les chemins sont arrêtés par roulette russe.
+
+use scene::World;
+use ray::Ray;
+use color_float::{LinearColor, Color};
+use renderer::TextureRegister;
+use sampler::Sampler;
+use math::VectorialOperations;
+use material::ShadingCoordinateSystem;
+use material::bsdf::{estimate_direct_lighting, cos_theta, abs_cos_theta};
+use integrator::{Integrator, escaped_radiance, RAY_EPSILON};
+
+/// La profondeur à partir de laquelle on commence à tuer des chemins par roulette russe.
+const ROULETTE_MIN_DEPTH: u32 = 3;
+
+/// La probabilité maximale de survie d'un chemin à la roulette russe.
+const ROULETTE_MAX_SURVIVAL: f32 = 0.95;
+
+pub struct PathTracer {
+    background_color: LinearColor,
+    max_depth: u32,
+}
+
+impl PathTracer {
+    pub fn new(background_color: LinearColor, max_depth: u32) -> Self {
+        PathTracer {
+            background_color: background_color,
+            max_depth: max_depth,
+        }
+    }
+}
+
+impl Integrator for PathTracer {
+    fn radiance(&self,
+                camera_ray: &Ray,
+                world: &World,
+                textures: &TextureRegister,
+                sampler: &mut Sampler)
+                -> LinearColor {
+        let mut result = LinearColor::new_black();
+        // Le produit des BSDF, des cosinus et des inverses des pdf le long du chemin
+        let mut throughput = LinearColor::new_white();
+        let mut ray = *camera_ray;
+        let mut specular_bounce = false;
+
+        for depth in 0..self.max_depth {
+            let intersection = match world.get_intersection_point(&mut ray) {
+                Some(intersection) => intersection,
+                // Le fond n'est vu que depuis la caméra ou après un rebond spéculaire : la
+                // lumière d'environnement est sinon déjà comptée par l'éclairage direct.
+                None => {
+                    if depth == 0 || specular_bounce {
+                        result += &(throughput *
+                                    escaped_radiance(&ray, world, self.background_color));
+                    }
+                    break;
+                }
+            };
+
+            let frag = *intersection.fragment();
+            let normal = frag.normal / frag.normal.norm();
+            let frame = ShadingCoordinateSystem::new_from_normal(&normal);
+            let wo = frame.world_into_local_space(&(-ray.slope() / ray.slope().norm()));
+            let bsdf = intersection.get_bsdf(world, textures);
+
+            // Lumière émise par la surface touchée. Celle des lumières surfaciques est déjà
+            // comptée par l'éclairage direct du point précédent, sauf si on vient de la caméra
+            // ou d'un rebond spéculaire.
+            if depth == 0 || specular_bounce ||
+               intersection.material().uniform_emission().is_none() {
+                result += &(throughput * intersection.get_emission(world, textures));
+            }
+
+            // Éclairage direct (toujours nul pour une BSDF spéculaire, dont f est nulle)
+            let direct = estimate_direct_lighting(&*bsdf,
+                                                  &frame,
+                                                  &wo,
+                                                  &frag.position,
+                                                  ray.time,
+                                                  world,
+                                                  sampler);
+            result += &(throughput * direct);
+
+            if depth + 1 == self.max_depth {
+                break;
+            }
+
+            // Rebond : on tire une direction selon la BSDF.
+            let sample = match bsdf.sample_f(&wo, sampler.get_2d()) {
+                Some(sample) => sample,
+                None => break,
+            };
+            throughput = throughput * sample.f * (abs_cos_theta(&sample.wi) / sample.pdf);
+            specular_bounce = sample.specular;
+
+            // Roulette russe : les chemins qui transportent peu d'énergie ont plus de chances
+            // d'être arrêtés, et ceux qui survivent sont renforcés pour rester non biaisés.
+            if depth >= ROULETTE_MIN_DEPTH {
+                let color = throughput.get_internal_color();
+                let survival = f32::min(ROULETTE_MAX_SURVIVAL,
+                                        f32::max(color.r(), f32::max(color.g(), color.b())));
+                if sampler.get_1d() >= survival {
+                    break;
+                }
+                throughput = throughput / survival;
+            }
+
+            // On décale l'origine du côté de la surface où part le rayon (il la traverse dans
+            // le cas d'une réfraction).
+            let offset = if cos_theta(&sample.wi) > 0.0 {
+                normal * RAY_EPSILON
+            } else {
+                -normal * RAY_EPSILON
+            };
+            let time = ray.time;
+            ray = Ray::new(frag.position + offset, frame.local_into_world_space(&sample.wi));
+            ray.time = time;
+        }
+        result
+    }
+}
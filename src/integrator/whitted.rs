@@ -0,0 +1,110 @@
+use scene::World;
+use ray::Ray;
+use color_float::LinearColor;
+use renderer::TextureRegister;
+use sampler::Sampler;
+use math::VectorialOperations;
+use tools::{reflect, refract, fresnel_dielectric};
+use integrator::{Integrator, escaped_radiance, RAY_EPSILON};
+
+/// L'indice de réfraction du milieu dans lequel baignent les objets (l'air).
+const OUTSIDE_INDEX_OF_REFRACTION: f32 = 1.0;
+
+/** Le lancer de rayon de Whitted : au point touché, la couleur du matériau est complétée par
+un rayon réfléchi et un rayon réfracté, lancés récursivement jusqu'à `max_depth` intersections.
+La part réfléchie et la part transmise sont données par la réflectivité et la transmissivité du
+matériau, la transmission étant répartie entre réflexion et réfraction selon les équations de
+Fresnel, et chacune est teintée par la couleur de réflexion ou de transmission du matériau. */
+pub struct WhittedIntegrator {
+    background_color: LinearColor,
+    max_depth: u32,
+}
+
+impl WhittedIntegrator {
+    pub fn new(background_color: LinearColor, max_depth: u32) -> Self {
+        WhittedIntegrator {
+            background_color: background_color,
+            max_depth: max_depth,
+        }
+    }
+
+    fn trace(&self,
+             ray: &Ray,
+             world: &World,
+             textures: &TextureRegister,
+             sampler: &mut Sampler,
+             depth: u32)
+             -> LinearColor {
+        let mut ray = *ray;
+        let intersection = match world.get_intersection_point(&mut ray) {
+            Some(intersection) => intersection,
+            None => return escaped_radiance(&ray, world, self.background_color),
+        };
+
+        let frag = intersection.fragment();
+        let material = intersection.material();
+        let reflectivity = material.reflectivity();
+        let transmissivity = material.transmissivity();
+
+        let local_weight = f32::max(0.0, 1.0 - reflectivity - transmissivity);
+        let mut result = intersection.get_point_color(world, textures, sampler) * local_weight;
+
+        if depth + 1 >= self.max_depth || (reflectivity <= 0.0 && transmissivity <= 0.0) {
+            return result;
+        }
+
+        let direction = ray.slope() / ray.slope().norm();
+        let mut normal = frag.normal / frag.normal.norm();
+
+        // On rentre dans l'objet si le rayon arrive du côté de la normale.
+        let entering = direction.dot_product(&normal) < 0.0;
+        let (eta_i, eta_t) = if entering {
+            (OUTSIDE_INDEX_OF_REFRACTION, material.index_of_refraction())
+        } else {
+            normal = -normal;
+            (material.index_of_refraction(), OUTSIDE_INDEX_OF_REFRACTION)
+        };
+
+        let mut reflected_weight = reflectivity;
+        if transmissivity > 0.0 {
+            match refract(&direction, &normal, eta_i / eta_t) {
+                Some(refracted) => {
+                    let fresnel = fresnel_dielectric(-direction.dot_product(&normal),
+                                                     eta_i,
+                                                     eta_t);
+                    reflected_weight += transmissivity * fresnel;
+                    let mut refracted_ray = Ray::new(frag.position - normal * RAY_EPSILON,
+                                                     refracted);
+                    refracted_ray.time = ray.time;
+                    let tint = material.transmission_color(frag, world, Some(textures));
+                    let transmitted =
+                        self.trace(&refracted_ray, world, textures, sampler, depth + 1);
+                    result += &(transmitted * tint * (transmissivity * (1.0 - fresnel)));
+                }
+                // Réflexion totale interne : toute la lumière transmise est réfléchie.
+                None => reflected_weight += transmissivity,
+            }
+        }
+
+        if reflected_weight > 0.0 {
+            let mut reflected_ray = Ray::new(frag.position + normal * RAY_EPSILON,
+                                             reflect(&direction, &normal));
+            reflected_ray.time = ray.time;
+            let tint = material.reflection_color(frag, world, Some(textures));
+            let reflected = self.trace(&reflected_ray, world, textures, sampler, depth + 1);
+            result += &(reflected * tint * reflected_weight);
+        }
+        result
+    }
+}
+
+impl Integrator for WhittedIntegrator {
+    fn radiance(&self,
+                ray: &Ray,
+                world: &World,
+                textures: &TextureRegister,
+                sampler: &mut Sampler)
+                -> LinearColor {
+        self.trace(ray, world, textures, sampler, 0)
+    }
+}
@@ -15,11 +15,14 @@ pub mod sampler;
 pub mod ray;
 pub mod color;
 pub mod img;
+pub mod hdr;
+pub mod animation;
 pub mod io_utils;
 pub mod geometry;
 pub mod filter;
 pub mod light;
 pub mod material;
+pub mod integrator;
 pub mod color_float;
 pub mod tools;
 #[macro_use]
@@ -35,6 +38,7 @@ pub extern crate pbr;
 pub extern crate rand;
 
 pub use scene::Scene;
+pub use animation::frame_file_path;
 pub use img::{Image, RGBAPixel};
 pub use color::{RGBA8, RGBA32};
 pub use math::Vector3;
@@ -43,3 +47,5 @@ pub use std::env;
 pub use io_utils::*;
 pub use material::channel::{Channel, TextureMap, Texture};
 pub use material::flat_material;
+pub use material::MaterialObject;
+pub use material::phong::PhongMaterial;
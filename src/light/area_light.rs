@@ -0,0 +1,240 @@
+use std::f32;
+use math::{Vector2f, Vector3f, VectorialOperations};
+use scene::World;
+use light::{Light, LightLiSample, LightHit, DEFAULT_AREA_LIGHT_SAMPLES};
+use ray::Ray;
+use color_float::LinearColor;
+use geometry::obj3d::{Object, Triangle};
+use geometry::transform::Transform;
+use integrator::RAY_EPSILON;
+use geometry::bounding_box::BoundingBox;
+
+/** Une lumière surfacique, créée à partir d'un objet dont le matériau émet de la lumière. Les
+points sont tirés uniformément sur la surface des triangles de l'objet, ce qui donne des ombres
+douces. La surface émet des deux côtés. Si l'objet bouge, ses triangles sont placés à l'instant
+de chaque rayon. */
+#[derive(Debug)]
+pub struct AreaLight {
+    // L'objet, dont le mesh et son BVH servent aux intersections
+    object: Object,
+    // Les aires cumulées des triangles à l'instant 0, pour tirer un triangle proportionnellement
+    // à son aire
+    cumulative_areas: Vec<f32>,
+    // L'aire totale de la lumière à l'instant 0
+    area: f32,
+    // La radiance émise en chaque point de la surface
+    radiance: LinearColor,
+    samples: u32,
+}
+
+// Les sommets du triangle `triangle` dans l'espace du monde, avec la transformation `transform`.
+fn world_triangle(triangle: &Triangle, transform: &Transform) -> [Vector3f; 3] {
+    [transform.apply_to_point(&triangle.u_pos()),
+     transform.apply_to_point(&triangle.v_pos()),
+     transform.apply_to_point(&triangle.w_pos())]
+}
+
+// Le produit vectoriel de deux côtés d'un triangle, normal au triangle et de norme le double de
+// son aire.
+fn triangle_normal(triangle: &[Vector3f; 3]) -> Vector3f {
+    (triangle[1] - triangle[0]).cross_product(&(triangle[2] - triangle[0]))
+}
+
+impl AreaLight {
+    /// Crée la lumière surfacique correspondant à l'objet `object`, qui émet une radiance
+    /// `radiance`. Renvoie `None` si l'objet n'a pas de surface.
+    pub fn new(object: &Object, radiance: LinearColor) -> Option<AreaLight> {
+        let transform = object.transform();
+        let mut cumulative_areas = Vec::with_capacity(object.triangle_count());
+        let mut area = 0.0;
+        for triangle in object.triangles() {
+            area += triangle_normal(&world_triangle(triangle, transform)).norm() / 2.0;
+            cumulative_areas.push(area);
+        }
+
+        if area <= 0.0 {
+            return None;
+        }
+        Some(AreaLight {
+                 object: object.clone(),
+                 cumulative_areas: cumulative_areas,
+                 area: area,
+                 radiance: radiance,
+                 samples: DEFAULT_AREA_LIGHT_SAMPLES,
+             })
+    }
+
+    pub fn area(&self) -> f32 {
+        self.area
+    }
+
+    // L'aire cumulée des triangles qui précèdent le triangle `index`.
+    fn area_before(&self, index: usize) -> f32 {
+        if index == 0 {
+            0.0
+        } else {
+            self.cumulative_areas[index - 1]
+        }
+    }
+
+    // La densité par rapport à l'aire d'un point du triangle `index` de normale `normal` (voir
+    // `triangle_normal`). Le triangle est choisi avec les aires de l'instant 0, puis le point
+    // uniformément dans le triangle, même si l'objet bouge.
+    fn pdf_area(&self, index: usize, normal: &Vector3f) -> f32 {
+        let probability = (self.cumulative_areas[index] - self.area_before(index)) / self.area;
+        probability * 2.0 / normal.norm()
+    }
+
+    /// Tire un point uniformément sur la surface de la lumière à l'instant `time`, à partir
+    /// d'un point `u` de [0;1[ x [0;1[. Renvoie le point, la normale unitaire de la surface en
+    /// ce point, et la densité du point par rapport à l'aire.
+    fn sample_point(&self, u: Vector2f, time: f32) -> Option<(Vector3f, Vector3f, f32)> {
+        // On choisit le triangle proportionnellement à son aire...
+        let target = u.x * self.area;
+        let index = match self.cumulative_areas
+                  .binary_search_by(|area| area.partial_cmp(&target).unwrap()) {
+            Ok(index) | Err(index) => ::std::cmp::min(index, self.cumulative_areas.len() - 1),
+        };
+        let triangle = world_triangle(self.object.triangle(index),
+                                      &self.object.transform_at(time));
+        let (a, b, c) = (triangle[0], triangle[1], triangle[2]);
+
+        // ...puis un point uniformément dans le triangle. La position de u.x dans l'intervalle
+        // du triangle choisi est elle-même uniforme, on la réutilise.
+        let lower = self.area_before(index);
+        let u0 = f32::min(1.0, (target - lower) / (self.cumulative_areas[index] - lower));
+        let su = u0.sqrt();
+        let (b0, b1) = (1.0 - su, u.y * su);
+        let position = a * b0 + b * b1 + c * (1.0 - b0 - b1);
+        let normal = triangle_normal(&triangle);
+        if normal.norm() <= 0.0 {
+            return None;
+        }
+        Some((position, normal / normal.norm(), self.pdf_area(index, &normal)))
+    }
+}
+
+impl Light for AreaLight {
+    // Un point tiré sur la surface avec une densité p par rapport à l'aire donne une direction
+    // de densité p * distance² / |cos|.
+    fn sample_li(&self,
+                 point: &Vector3f,
+                 u: Vector2f,
+                 time: f32,
+                 _: &World)
+                 -> Option<LightLiSample> {
+        let (position, normal, pdf_area) = match self.sample_point(u, time) {
+            Some(sample) => sample,
+            None => return None,
+        };
+        let to_point = *point - position;
+        let distance2 = to_point.dot_product_ref(&to_point);
+        if distance2 <= 0.0 {
+            return None;
+        }
+        let cos_light = normal.dot_product_ref(&to_point) / distance2.sqrt();
+        if cos_light == 0.0 {
+            return None;
+        }
+
+        // On décale l'origine du rayon pour qu'il ne touche pas la lumière elle-même.
+        let origin = if cos_light > 0.0 {
+            position + normal * RAY_EPSILON
+        } else {
+            position - normal * RAY_EPSILON
+        };
+        let mut ray = Ray::new(origin, *point - origin);
+        ray.max_t = 0.999;
+        ray.time = time;
+        Some(LightLiSample {
+                 ray: ray,
+                 radiance: self.radiance,
+                 pdf: pdf_area * distance2 / cos_light.abs(),
+             })
+    }
+
+    fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    // Le triangle le plus proche touché par le rayon, cherché avec le BVH du mesh dans l'espace
+    // de l'objet.
+    fn intersect_li(&self, ray: &Ray) -> Option<LightHit> {
+        let transform = self.object.transform_at(ray.time);
+        let mut local_ray = transform.ray_to_local(ray);
+        if local_ray.max_t <= 0.0 {
+            local_ray.max_t = f32::MAX;
+        }
+        let index = match self.object.mesh().nearest_triangle(&mut local_ray) {
+            Some(index) => index,
+            None => return None,
+        };
+        // Le vecteur directeur n'est pas normalisé dans l'espace de l'objet : le paramètre du
+        // point touché est donc la distance dans l'espace du monde.
+        let distance = local_ray.max_t;
+        let normal = triangle_normal(&world_triangle(self.object.triangle(index), &transform));
+        let cos_light = normal.dot_product_ref(&ray.slope()).abs() / normal.norm();
+        if cos_light <= 0.0 {
+            return None;
+        }
+        Some(LightHit {
+                 distance: distance,
+                 radiance: self.radiance,
+                 pdf: self.pdf_area(index, &normal) * distance * distance / cos_light,
+             })
+    }
+
+    // La surface émet des deux côtés.
+    fn power(&self) -> f32 {
+        2.0 * f32::consts::PI * self.area * self.radiance.luminance()
+    }
+
+    // La boîte de l'objet englobe tout son mouvement.
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        Some(self.object.bounding_box().clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use math::{Vector2f, Vector3f, VectorialOperations};
+    use scene::World;
+    use ray::Ray;
+    use color_float::LinearColor;
+    use geometry::obj3d::Object;
+    use light::Light;
+    use super::AreaLight;
+
+    #[test]
+    fn test_moving_area_light() {
+        // Un plan de 2x2 perpendiculaire à l'axe y, qui se déplace de 4 selon x
+        let mut plane = Object::new(Vector3f::new(0.0, 0.0, 0.0),
+                                    "models/plane_no_uv.obj".to_string(),
+                                    "plane".to_string());
+        plane.set_motion(Some(Vector3f::new(4.0, 0.0, 0.0)), None, None);
+        let light = AreaLight::new(&plane, LinearColor::new_white()).unwrap();
+
+        let ray_at = |x: f32, time: f32| -> Ray {
+            let mut ray = Ray::new(Vector3f::new(x, -1.0, 0.0), Vector3f::new(0.0, 1.0, 0.0));
+            ray.time = time;
+            ray
+        };
+        assert!(light.intersect_li(&ray_at(0.0, 0.0)).is_some());
+        assert!(light.intersect_li(&ray_at(0.0, 1.0)).is_none());
+        assert!(light.intersect_li(&ray_at(4.0, 1.0)).is_some());
+
+        let world = World::new_empty();
+        let point = Vector3f::new(4.0, -1.0, 0.0);
+        for &u in &[Vector2f::new(0.1, 0.3), Vector2f::new(0.6, 0.9)] {
+            let sample = light.sample_li(&point, u, 1.0, &world).unwrap();
+            let origin = sample.ray.origin();
+            assert!(origin.x > 2.99 && origin.x < 5.01 && origin.y.abs() < 1e-2);
+            // La densité est la même que celle de la direction touchée par un rayon.
+            let direction = -sample.ray.slope() / sample.ray.slope().norm();
+            let mut ray = Ray::new(point, direction);
+            ray.time = 1.0;
+            let hit = light.intersect_li(&ray).unwrap();
+            assert!((hit.pdf - sample.pdf).abs() < 1e-3 * sample.pdf);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use math::{Vector2f, Vector3f, VectorialOperations};
+use scene::World;
+use light::{Light, LightLiSample, shadow_ray_from_infinity};
+use ray::Ray;
+use sampler::Sampler;
+use color_float::{RGBColor, LinearColor};
+use geometry::bounding_box::BoundingBox;
+
+/** Represente une lumière directionnelle, comme le soleil : tous les rayons sont parallèles à
+`direction`, et l'éclairement ne dépend pas de la distance. Une surface qui fait face à la
+lumière reçoit un éclairement `color` * `intensity`, en W/m². */
+#[derive(Serialize,Deserialize, Debug)]
+pub struct DirectionalLight {
+    // La direction dans laquelle la lumière se propage
+    direction: Vector3f,
+    color: RGBColor,
+    intensity: f32,
+}
+
+impl DirectionalLight {
+    // Le rayon d'ombre qui arrive en `point`, depuis l'extérieur de la scène.
+    fn shadow_ray(&self, point: &Vector3f, world: &World) -> Ray {
+        shadow_ray_from_infinity(&(self.direction / self.direction.norm()), point, world)
+    }
+}
+
+impl Light for DirectionalLight {
+    fn visible(&self, point: &Vector3f, time: f32, world: &World, _: &mut Sampler) -> bool {
+        let mut ray = self.shadow_ray(point, world);
+        ray.time = time;
+        !world.is_occluded(&mut ray)
+    }
+
+    fn sample_li(&self,
+                 point: &Vector3f,
+                 _: Vector2f,
+                 _: f32,
+                 world: &World)
+                 -> Option<LightLiSample> {
+        let color: LinearColor = self.color.into();
+        Some(LightLiSample {
+                 ray: self.shadow_ray(point, world),
+                 radiance: color * self.intensity,
+                 pdf: 1.0,
+             })
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    // Une lumière à l'infini n'est jamais tirée : sa puissance ne sert pas.
+    fn power(&self) -> f32 {
+        0.0
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        None
+    }
+}
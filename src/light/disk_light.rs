@@ -0,0 +1,125 @@
+use std::f32;
+use math::{Vector2f, Vector3f, VectorialOperations};
+use scene::World;
+use light::{Light, LightLiSample, LightHit, LightPower, default_area_light_samples,
+            light_intensity, check_light_units};
+use ray::Ray;
+use color_float::{RGBColor, LinearColor};
+use material::ShadingCoordinateSystem;
+use tools::monte_carlo;
+use integrator::RAY_EPSILON;
+use geometry::bounding_box::BoundingBox;
+
+/** Une lumière en forme de disque, de centre `position` et de rayon `radius`, qui émet une
+radiance uniforme `color` * `intensity` (en W/(sr.m²)) du côté de `direction` seulement, ou la
+radiance qui correspond à sa puissance `power`. À chaque calcul d'éclairage, `samples` points
+sont tirés sur le disque. La lumière elle-même n'est pas visible par la caméra. */
+#[derive(Serialize,Deserialize, Debug)]
+pub struct DiskLight {
+    position: Vector3f,
+    direction: Vector3f,
+    radius: f32,
+    color: RGBColor,
+    #[serde(default)]
+    intensity: Option<f32>,
+    #[serde(default)]
+    power: Option<LightPower>,
+    #[serde(default = "default_area_light_samples")]
+    samples: u32,
+}
+
+impl DiskLight {
+    fn normal(&self) -> Vector3f {
+        self.direction / self.direction.norm()
+    }
+
+    fn area(&self) -> f32 {
+        f32::consts::PI * self.radius * self.radius
+    }
+
+    // Une surface de radiance L émet une puissance π * L par unité d'aire, d'un seul côté.
+    fn intensity(&self) -> f32 {
+        let color: LinearColor = self.color.into();
+        light_intensity(self.intensity, self.power, &color, f32::consts::PI * self.area())
+    }
+
+    /// Vérifie que la lumière est réglée soit par son intensité, soit par sa puissance.
+    pub fn check_units(&self) -> Result<(), String> {
+        check_light_units(self.intensity, self.power)
+    }
+}
+
+impl Light for DiskLight {
+    // Un point tiré uniformément sur le disque donne une direction de densité
+    // distance² / (cos * aire).
+    fn sample_li(&self,
+                 point: &Vector3f,
+                 u: Vector2f,
+                 _: f32,
+                 _: &World)
+                 -> Option<LightLiSample> {
+        let frame = ShadingCoordinateSystem::new_from_normal(&self.direction);
+        let normal = self.normal();
+        let d = monte_carlo::sample_disk_concentric(u) * self.radius;
+        let position = self.position +
+                       frame.local_into_world_space(&Vector3f::new(d.x, d.y, 0.0));
+        let to_point = *point - position;
+        let distance2 = to_point.dot_product_ref(&to_point);
+        if distance2 <= 0.0 {
+            return None;
+        }
+        let cos_light = normal.dot_product_ref(&to_point) / distance2.sqrt();
+        if cos_light <= 0.0 {
+            return None;
+        }
+        let origin = position + normal * RAY_EPSILON;
+        let mut ray = Ray::new(origin, *point - origin);
+        ray.max_t = 0.999;
+        let color: LinearColor = self.color.into();
+        Some(LightLiSample {
+                 ray: ray,
+                 radiance: color * self.intensity(),
+                 pdf: distance2 / (cos_light * self.area()),
+             })
+    }
+
+    fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    fn intersect_li(&self, ray: &Ray) -> Option<LightHit> {
+        let normal = self.normal();
+        let cos_light = -ray.slope().dot_product_ref(&normal);
+        if cos_light <= 0.0 {
+            return None;
+        }
+        let distance = (ray.origin() - self.position).dot_product_ref(&normal) / cos_light;
+        if distance <= 0.0 {
+            return None;
+        }
+        let local = ray.origin() + ray.slope() * distance - self.position;
+        if local.dot_product_ref(&local) > self.radius * self.radius {
+            return None;
+        }
+        let color: LinearColor = self.color.into();
+        Some(LightHit {
+                 distance: distance,
+                 radiance: color * self.intensity(),
+                 pdf: distance * distance / (cos_light * self.area()),
+             })
+    }
+
+    fn power(&self) -> f32 {
+        let color: LinearColor = self.color.into();
+        f32::consts::PI * self.area() * self.intensity() * color.luminance()
+    }
+
+    // La boîte de la sphère qui contient le disque
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let extent = Vector3f::new(self.radius, self.radius, self.radius);
+        let mut bbox = BoundingBox::new();
+        bbox.add_point(self.position - extent);
+        bbox.add_point(self.position + extent);
+        Some(bbox)
+    }
+}
@@ -0,0 +1,265 @@
+use std::f32;
+use std::fmt;
+use std::cmp;
+use math::{Vector2f, Vector3f, VectorialOperations};
+use scene::World;
+use light::{Light, LightLiSample, LightHit, default_area_light_samples,
+            shadow_ray_from_infinity};
+use ray::Ray;
+use color_float::LinearColor;
+use img::Image;
+use geometry::bounding_box::BoundingBox;
+use hdr;
+use tools::distribution::Distribution2D;
+
+fn default_intensity() -> f32 {
+    1.0
+}
+
+/** Une lumière d'environnement : une image HDR équirectangulaire (`.hdr` ou `.pfm`) qui entoure
+la scène à l'infini. Elle éclaire la scène et sert de fond pour les rayons qui ne touchent rien.
+L'axe z est la verticale : le haut de l'image est au zénith, et la longitude croît de x vers y
+avec les colonnes. `rotation` tourne l'image autour de la verticale, en degrés. Les directions
+sont tirées selon la luminance des pixels. */
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EnvironmentLight {
+    path: String,
+    #[serde(default)]
+    rotation: f32,
+    #[serde(default = "default_intensity")]
+    intensity: f32,
+    #[serde(default = "default_area_light_samples")]
+    samples: u32,
+    // L'image et sa distribution, chargées après la désérialisation
+    #[serde(skip_serializing, skip_deserializing, default = "Option::default")]
+    map: Option<EnvironmentMap>,
+}
+
+struct EnvironmentMap {
+    image: Image<LinearColor>,
+    distribution: Distribution2D,
+}
+
+impl fmt::Debug for EnvironmentMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "EnvironmentMap {{ {}x{} }}",
+               self.image.width(),
+               self.image.height())
+    }
+}
+
+impl EnvironmentLight {
+    /// Lit l'image et construit la distribution des directions. La densité d'un pixel est
+    /// proportionnelle à sa luminance multipliée par le sinus de sa latitude, qui compense
+    /// l'étirement des pôles par la projection équirectangulaire.
+    pub fn load(&mut self) -> Result<(), String> {
+        let image = hdr::read_hdr_file(&self.path)?;
+        let (width, height) = (image.width(), image.height());
+        let func = (0..height)
+            .map(|y| {
+                let sin_theta = ((y as f32 + 0.5) / height as f32 * f32::consts::PI).sin();
+                (0..width)
+                    .map(|x| image.get_pixel_at(x, y).luminance().max(0.0) * sin_theta)
+                    .collect()
+            })
+            .collect();
+        println!("Environment map {} loaded ({}x{})", self.path, width, height);
+        self.map = Some(EnvironmentMap {
+                            image: image,
+                            distribution: Distribution2D::new(func),
+                        });
+        Ok(())
+    }
+
+    // La direction qui correspond au point (u, v) de l'image.
+    fn direction(&self, uv: &Vector2f) -> Vector3f {
+        let theta = uv.y * f32::consts::PI;
+        let phi = uv.x * 2.0 * f32::consts::PI + self.rotation.to_radians();
+        Vector3f::new(theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos())
+    }
+
+    // Le point (u, v) de l'image qui correspond à la direction unitaire `direction`.
+    fn image_coordinates(&self, direction: &Vector3f) -> Vector2f {
+        let theta = f32::max(-1.0, f32::min(1.0, direction.z)).acos();
+        let phi = direction.y.atan2(direction.x) - self.rotation.to_radians();
+        let u = phi / (2.0 * f32::consts::PI);
+        Vector2f::new(u - u.floor(), theta / f32::consts::PI)
+    }
+
+    // La radiance du pixel qui contient le point (u, v).
+    fn radiance_at(&self, map: &EnvironmentMap, uv: &Vector2f) -> LinearColor {
+        let width = map.image.width();
+        let height = map.image.height();
+        let x = cmp::min((uv.x * width as f32) as u32, width - 1);
+        let y = cmp::min((uv.y * height as f32) as u32, height - 1);
+        map.image.get_pixel_at(x, y) * self.intensity
+    }
+
+    /// La radiance qui arrive de l'infini dans la direction opposée à `direction`, c'est à dire
+    /// celle vue par un rayon de direction `direction` qui ne touche rien.
+    pub fn radiance(&self, direction: &Vector3f) -> LinearColor {
+        match self.map {
+            Some(ref map) => {
+                let direction = *direction / direction.norm();
+                self.radiance_at(map, &self.image_coordinates(&direction))
+            }
+            None => LinearColor::new_black(),
+        }
+    }
+}
+
+// La densité par rapport à l'angle solide de la direction qui correspond au point (u, v) de
+// l'image, tiré avec la densité `pdf_uv`.
+fn direction_pdf(pdf_uv: f32, uv: &Vector2f) -> f32 {
+    let sin_theta = (uv.y * f32::consts::PI).sin();
+    if pdf_uv <= 0.0 || sin_theta <= 0.0 {
+        0.0
+    } else {
+        pdf_uv / (2.0 * f32::consts::PI * f32::consts::PI * sin_theta)
+    }
+}
+
+impl Light for EnvironmentLight {
+    // Un point (u, v) de densité p(u, v) donne une direction de densité p(u, v) / (2π² sin θ).
+    fn sample_li(&self,
+                 point: &Vector3f,
+                 u: Vector2f,
+                 _: f32,
+                 world: &World)
+                 -> Option<LightLiSample> {
+        let map = match self.map {
+            Some(ref map) => map,
+            None => return None,
+        };
+        let (uv, pdf_uv) = map.distribution.sample_continuous(u);
+        let pdf = direction_pdf(pdf_uv, &uv);
+        if pdf <= 0.0 {
+            return None;
+        }
+        let towards_light = self.direction(&uv);
+        Some(LightLiSample {
+                 ray: shadow_ray_from_infinity(&-towards_light, point, world),
+                 radiance: self.radiance_at(map, &uv),
+                 pdf: pdf,
+             })
+    }
+
+    fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    fn intersect_li(&self, ray: &Ray) -> Option<LightHit> {
+        let map = match self.map {
+            Some(ref map) => map,
+            None => return None,
+        };
+        let direction = ray.slope() / ray.slope().norm();
+        let uv = self.image_coordinates(&direction);
+        Some(LightHit {
+                 distance: -1.0,
+                 radiance: self.radiance_at(map, &uv),
+                 pdf: direction_pdf(map.distribution.pdf(&uv), &uv),
+             })
+    }
+
+    // Une lumière à l'infini n'est jamais tirée : sa puissance ne sert pas.
+    fn power(&self) -> f32 {
+        0.0
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32;
+    use math::{Vector2f, Vector3f, VectorialOperations};
+    use color_float::{LinearColor, Color};
+    use img::Image;
+    use ray::Ray;
+    use scene::World;
+    use sampler::samplers::DefaultSampler;
+    use light::Light;
+    use tools::distribution::Distribution2D;
+    use super::{EnvironmentLight, EnvironmentMap};
+
+    // Une lumière d'environnement dont seule la moitié haute de l'image est allumée, avec une
+    // radiance 1 : elle simule un ciel uniforme.
+    fn sky(rotation: f32) -> EnvironmentLight {
+        let (width, height) = (16, 8);
+        let columns: Vec<Vec<LinearColor>> = (0..width)
+            .map(|_| {
+                (0..height)
+                    .map(|y| if y < height / 2 {
+                             LinearColor::new_white()
+                         } else {
+                             LinearColor::new_black()
+                         })
+                    .collect()
+            })
+            .collect();
+        let func = (0..height)
+            .map(|y| {
+                let sin_theta = ((y as f32 + 0.5) / height as f32 * f32::consts::PI).sin();
+                vec![if y < height / 2 { sin_theta } else { 0.0 }; width]
+            })
+            .collect();
+        EnvironmentLight {
+            path: String::new(),
+            rotation: rotation,
+            intensity: 1.0,
+            samples: 16,
+            map: Some(EnvironmentMap {
+                          image: Image::from_vec_vec(&columns),
+                          distribution: Distribution2D::new(func),
+                      }),
+        }
+    }
+
+    #[test]
+    fn test_environment_directions() {
+        let light = sky(30.0);
+        for &(u, v) in &[(0.1, 0.2), (0.7, 0.4), (0.95, 0.9)] {
+            let uv = light.image_coordinates(&light.direction(&Vector2f::new(u, v)));
+            assert!((uv.x - u).abs() < 1e-4 && (uv.y - v).abs() < 1e-4);
+        }
+        let up = Vector3f::new(0.0, 0.0, 1.0);
+        assert_eq!(light.radiance(&up).get_internal_color().r(), 1.0);
+        assert_eq!(light.radiance(&-up).get_internal_color().r(), 0.0);
+
+        // La longueur de la direction du rayon ne change ni la radiance ni la densité.
+        let origin = Vector3f::new(0.0, 0.0, 0.0);
+        let direction = Vector3f::new(0.3, -0.2, 0.5);
+        let unit = light.intersect_li(&Ray::new(origin, direction / direction.norm())).unwrap();
+        let long = light.intersect_li(&Ray::new(origin, direction * 4.0)).unwrap();
+        assert_eq!(unit.radiance.get_internal_color().r(), 1.0);
+        assert_eq!(long.radiance.get_internal_color().r(), 1.0);
+        assert!((unit.pdf - long.pdf).abs() < 1e-4 * unit.pdf);
+    }
+
+    #[test]
+    fn test_environment_irradiance() {
+        // Un ciel uniforme de radiance 1 donne un éclairement π sur un sol horizontal.
+        let light = sky(0.0);
+        let world = World::new_empty();
+        let mut sampler = DefaultSampler::new(1);
+        let normal = Vector3f::new(0.0, 0.0, 1.0);
+        let calls = 256;
+        let mut irradiance = 0.0;
+        for _ in 0..calls {
+            let origin = Vector3f::new(0.0, 0.0, 0.0);
+            for sample in light.emit_rays(&origin, 0.0, &world, &mut sampler) {
+                let w = -sample.ray.slope() / sample.ray.slope().norm();
+                irradiance += sample.radiance.get_internal_color().g() *
+                              f32::max(0.0, w.dot_product_ref(&normal));
+            }
+        }
+        irradiance /= calls as f32;
+        assert!((irradiance - f32::consts::PI).abs() < 0.03 * f32::consts::PI,
+                "irradiance = {}",
+                irradiance);
+    }
+}
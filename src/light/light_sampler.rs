@@ -0,0 +1,318 @@
+/// Le choix des lumières évaluées en chaque point. Avec des centaines de lumières, les évaluer
+/// toutes en chaque point est trop coûteux : on n'en tire que quelques unes, et on divise leur
+/// contribution par leur probabilité d'être tirées pour que l'estimation reste non biaisée. Les
+/// lumières à l'infini (sans boîte englobante) sont toujours évaluées.
+
+use std::cmp::Ordering;
+use std::f32;
+use geometry::bounding_box::BoundingBox;
+use light::Light;
+use math::{Vector3f, VectorialOperations};
+use tools::alias_table::AliasTable;
+
+/// Les stratégies d'échantillonnage des lumières. Le choix se fait dans le fichier de
+/// configuration du renderer, par exemple : `"light_sampling": {"Power": {"samples": 4}}`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum LightSamplingStrategy {
+    /// Toutes les lumières sont évaluées en chaque point.
+    All,
+    /// `samples` lumières tirées uniformément.
+    Uniform { samples: u32 },
+    /// `samples` lumières tirées proportionnellement à leur puissance.
+    Power { samples: u32 },
+    /// `samples` lumières tirées dans un BVH de lumières, selon leur contribution estimée au
+    /// point éclairé : leur puissance divisée par le carré de leur distance.
+    BVH { samples: u32 },
+}
+
+impl Default for LightSamplingStrategy {
+    fn default() -> Self {
+        LightSamplingStrategy::All
+    }
+}
+
+/// Un noeud du BVH de lumières. Comme pour le BVH des objets, le premier enfant d'un noeud
+/// intérieur est le noeud qui le suit dans le vecteur.
+#[derive(Debug, Clone)]
+enum LightNode {
+    Leaf {
+        bbox: BoundingBox,
+        power: f32,
+        light: usize,
+    },
+    Interior {
+        bbox: BoundingBox,
+        power: f32,
+        second_child: usize,
+    },
+}
+
+impl LightNode {
+    // L'importance du noeud pour un point : la puissance des lumières qu'il contient divisée par
+    // le carré de la distance à son centre, bornée par la taille du noeud pour ne pas
+    // privilégier à l'excès les noeuds proches.
+    fn importance(&self, point: &Vector3f) -> f32 {
+        let (bbox, power) = match *self {
+            LightNode::Leaf { ref bbox, power, .. } |
+            LightNode::Interior { ref bbox, power, .. } => (bbox, power),
+        };
+        let to_center = bbox.centroid() - *point;
+        let half_diagonal = (bbox.max() - bbox.min()).norm() * 0.5;
+        let distance2 = f32::max(to_center.dot_product_ref(&to_center),
+                                 half_diagonal * half_diagonal);
+        power / f32::max(distance2, 1e-6)
+    }
+}
+
+// Les informations sur une lumière nécessaires à la construction du BVH.
+struct BuildLight {
+    index: usize,
+    bbox: BoundingBox,
+    power: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct LightSampler {
+    strategy: LightSamplingStrategy,
+    // Le nombre de lumières
+    count: usize,
+    // Les indices des lumières à l'infini, toujours évaluées
+    infinite: Vec<usize>,
+    // Les indices des lumières finies, parmi lesquelles on tire
+    finite: Vec<usize>,
+    // La distribution des lumières finies selon leur puissance (stratégie Power)
+    power_table: AliasTable,
+    // Le BVH des lumières finies (stratégie BVH)
+    nodes: Vec<LightNode>,
+}
+
+impl LightSampler {
+    /// Un sampler qui évalue toutes les lumières.
+    pub fn new_empty() -> Self {
+        LightSampler {
+            strategy: LightSamplingStrategy::All,
+            count: 0,
+            infinite: vec![],
+            finite: vec![],
+            power_table: AliasTable::new(&[]),
+            nodes: vec![],
+        }
+    }
+
+    /// Prépare l'échantillonnage des lumières `lights` selon la stratégie `strategy`. Les
+    /// lumières sont ensuite désignées par leur indice dans `lights`.
+    pub fn new(strategy: LightSamplingStrategy, lights: &[&Light]) -> Self {
+        let mut sampler = LightSampler::new_empty();
+        sampler.strategy = strategy;
+        sampler.count = lights.len();
+        let mut build_lights = vec![];
+        for (index, light) in lights.iter().enumerate() {
+            match light.bounding_box() {
+                Some(bbox) => {
+                    sampler.finite.push(index);
+                    build_lights.push(BuildLight {
+                                          index: index,
+                                          bbox: bbox,
+                                          power: f32::max(0.0, light.power()),
+                                      });
+                }
+                None => sampler.infinite.push(index),
+            }
+        }
+
+        match strategy {
+            LightSamplingStrategy::Power { .. } => {
+                let powers: Vec<f32> = build_lights.iter().map(|light| light.power).collect();
+                sampler.power_table = AliasTable::new(&powers);
+            }
+            LightSamplingStrategy::BVH { .. } if !build_lights.is_empty() => {
+                let mut nodes = Vec::with_capacity(2 * build_lights.len());
+                build_recursive(&mut build_lights, &mut nodes);
+                sampler.nodes = nodes;
+            }
+            _ => {}
+        }
+        sampler
+    }
+
+    pub fn strategy(&self) -> LightSamplingStrategy {
+        self.strategy
+    }
+
+    /// Choisit les lumières à évaluer au point `point`, à partir d'un nombre `u` de [0;1[ tiré
+    /// par le sampler de l'intégrateur. Chaque lumière est renvoyée par son indice, avec le poids
+    /// par lequel multiplier sa contribution.
+    pub fn sample(&self, point: &Vector3f, u: f32) -> Vec<(usize, f32)> {
+        let samples = match self.strategy {
+            LightSamplingStrategy::All => {
+                return (0..self.count).map(|index| (index, 1.0)).collect();
+            }
+            LightSamplingStrategy::Uniform { samples } |
+            LightSamplingStrategy::Power { samples } |
+            LightSamplingStrategy::BVH { samples } => samples,
+        };
+
+        let mut result: Vec<(usize, f32)> =
+            self.infinite.iter().map(|&index| (index, 1.0)).collect();
+        if self.finite.is_empty() || samples == 0 {
+            return result;
+        }
+
+        // Les nombres aléatoires sont stratifiés entre les lumières tirées : chaque strate est
+        // décalée de `u`.
+        for i in 0..samples {
+            let u = (i as f32 + u) / samples as f32;
+            let (index, pmf) = match self.strategy {
+                LightSamplingStrategy::Power { .. } => {
+                    let (index, pmf) = self.power_table.sample(u);
+                    (self.finite[index], pmf)
+                }
+                LightSamplingStrategy::BVH { .. } => self.sample_bvh(u, point),
+                _ => {
+                    let count = self.finite.len();
+                    let index = ::std::cmp::min((u * count as f32) as usize, count - 1);
+                    (self.finite[index], 1.0 / count as f32)
+                }
+            };
+            if pmf > 0.0 {
+                result.push((index, 1.0 / (pmf * samples as f32)));
+            }
+        }
+        result
+    }
+
+    // Descend dans le BVH en choisissant à chaque noeud un enfant proportionnellement à son
+    // importance. Renvoie l'indice de la lumière atteinte et sa probabilité.
+    fn sample_bvh(&self, u: f32, point: &Vector3f) -> (usize, f32) {
+        let mut u = u;
+        let mut pmf = 1.0;
+        let mut current = 0;
+        loop {
+            match self.nodes[current] {
+                LightNode::Leaf { light, .. } => return (light, pmf),
+                LightNode::Interior { second_child, .. } => {
+                    let first = self.nodes[current + 1].importance(point);
+                    let second = self.nodes[second_child].importance(point);
+                    if first + second <= 0.0 {
+                        return (0, 0.0);
+                    }
+                    let probability = first / (first + second);
+                    // La position de u dans l'intervalle choisi est uniforme : on la réutilise.
+                    if u < probability {
+                        u = f32::min(u / probability, 1.0 - f32::EPSILON);
+                        pmf *= probability;
+                        current += 1;
+                    } else {
+                        u = f32::min((u - probability) / (1.0 - probability),
+                                     1.0 - f32::EPSILON);
+                        pmf *= 1.0 - probability;
+                        current = second_child;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Construit le sous-arbre des lumières `lights` en coupant au milieu selon l'axe où leurs centres
+// sont le plus étendus. Les lumières sont indexées dans la liste des lumières finies.
+fn build_recursive(lights: &mut [BuildLight], nodes: &mut Vec<LightNode>) {
+    let mut bbox = BoundingBox::new();
+    let mut centroids = BoundingBox::new();
+    let mut power = 0.0;
+    for light in lights.iter() {
+        bbox.add_bounding_box(&light.bbox);
+        centroids.add_point(light.bbox.centroid());
+        power += light.power;
+    }
+
+    if lights.len() == 1 {
+        nodes.push(LightNode::Leaf {
+                       bbox: bbox,
+                       power: power,
+                       light: lights[0].index,
+                   });
+        return;
+    }
+
+    let axis = centroids.maximum_extent();
+    lights.sort_by(|a, b| {
+                       let a = BoundingBox::axis_value(&a.bbox.centroid(), axis);
+                       let b = BoundingBox::axis_value(&b.bbox.centroid(), axis);
+                       a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+                   });
+    let middle = lights.len() / 2;
+
+    let index = nodes.len();
+    nodes.push(LightNode::Interior {
+                   bbox: bbox,
+                   power: power,
+                   second_child: 0,
+               });
+    let (left, right) = lights.split_at_mut(middle);
+    build_recursive(left, nodes);
+    let second = nodes.len();
+    build_recursive(right, nodes);
+    if let LightNode::Interior { ref mut second_child, .. } = nodes[index] {
+        *second_child = second;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json;
+    use math::Vector3f;
+    use light::{Light, LightObject};
+    use sampler::Sampler;
+    use sampler::samplers::DefaultSampler;
+    use super::{LightSampler, LightSamplingStrategy};
+
+    // Une rangée de lumières ponctuelles le long de l'axe x, de puissances croissantes
+    fn lights() -> Vec<LightObject> {
+        (0..8)
+            .map(|i| {
+                serde_json::from_str(&format!(r#"{{"point": {{
+                        "position": {{"x": {}, "y": 0, "z": 0}},
+                        "color": {{"r": 255, "g": 255, "b": 255}}, "intensity": {}}}}}"#,
+                                              4 * i,
+                                              i + 1))
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_light_sampling_weights() {
+        let lights = lights();
+        let lights: Vec<&Light> = lights.iter().map(|light| light.as_trait()).collect();
+        let point = Vector3f::new(1.0, 1.0, 0.0);
+        let strategies = [LightSamplingStrategy::Uniform { samples: 4 },
+                          LightSamplingStrategy::Power { samples: 4 },
+                          LightSamplingStrategy::BVH { samples: 4 }];
+        for strategy in &strategies {
+            let sampler = LightSampler::new(*strategy, &lights);
+            let mut rng = DefaultSampler::new(1);
+            // La somme des poids estime le nombre de lumières.
+            let calls = 4000;
+            let mut total = 0.0;
+            for _ in 0..calls {
+                let sampled = sampler.sample(&point, rng.get_1d());
+                assert_eq!(sampled.len(), 4);
+                total += sampled.iter().map(|&(_, weight)| weight).sum::<f32>();
+            }
+            let estimate = total / calls as f32;
+            assert!((estimate - 8.0).abs() < 0.4, "{:?} : {}", strategy, estimate);
+        }
+    }
+
+    #[test]
+    fn test_light_bvh_prefers_close_lights() {
+        let lights = lights();
+        let lights: Vec<&Light> = lights.iter().map(|light| light.as_trait()).collect();
+        let sampler = LightSampler::new(LightSamplingStrategy::BVH { samples: 1 }, &lights);
+        let (index, pmf) = sampler.sample_bvh(0.01, &Vector3f::new(0.0, 0.5, 0.0));
+        assert_eq!(index, 0);
+        let (_, far_pmf) = sampler.sample_bvh(0.99, &Vector3f::new(0.0, 0.5, 0.0));
+        assert!(pmf > far_pmf);
+    }
+}
@@ -1,14 +1,203 @@
 pub mod point_light;
+pub mod directional_light;
+pub mod spot_light;
+pub mod rect_light;
+pub mod disk_light;
+pub mod sphere_light;
+pub mod area_light;
+pub mod environment_light;
+pub mod light_sampler;
 use scene::World;
-use math::Vector3f;
+use math::{Vector2f, Vector3f, VectorialOperations};
 use ray::Ray;
+use color_float::LinearColor;
+use sampler::Sampler;
+use geometry::bounding_box::BoundingBox;
+use sampler::samplers::get_halton;
 use light::point_light::PointLight;
+use light::directional_light::DirectionalLight;
+use light::spot_light::SpotLight;
+use light::rect_light::RectLight;
+use light::disk_light::DiskLight;
+use light::sphere_light::SphereLight;
+use light::environment_light::EnvironmentLight;
+use integrator::RAY_EPSILON;
+
+/// Le nombre de points tirés par défaut sur une lumière surfacique à chaque calcul d'éclairage
+/// direct.
+pub const DEFAULT_AREA_LIGHT_SAMPLES: u32 = 4;
+
+fn default_area_light_samples() -> u32 {
+    DEFAULT_AREA_LIGHT_SAMPLES
+}
+
+/// L'efficacité lumineuse maximale, en lumens par watt, atteinte pour une lumière
+/// monochromatique de 555 nm.
+pub const LUMINOUS_EFFICACY: f32 = 683.0;
+
+/// La puissance totale émise par une lumière, donnée à la place de son `intensity`. Par exemple
+/// `"power": {"Watts": 100.0}` ou `"power": {"Lumens": 800.0}`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum LightPower {
+    /// Une puissance rayonnée, que la couleur de la lumière filtre
+    Watts(f32),
+    /// Un flux lumineux : une lumière colorée paraît aussi lumineuse qu'une lumière blanche de
+    /// même flux.
+    Lumens(f32),
+}
+
+impl LightPower {
+    /// La puissance en watts, pour une lumière de couleur `color`.
+    pub fn watts(&self, color: &LinearColor) -> f32 {
+        match *self {
+            LightPower::Watts(watts) => watts,
+            LightPower::Lumens(lumens) => {
+                let luminance = color.luminance();
+                if luminance > 0.0 {
+                    lumens / (LUMINOUS_EFFICACY * luminance)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// L'intensité d'une lumière : `intensity` si elle est donnée, sinon sa puissance en watts
+/// divisée par `power_per_intensity`, la puissance émise par une lumière blanche d'intensité 1
+/// (qui dépend de sa forme).
+fn light_intensity(intensity: Option<f32>,
+                   power: Option<LightPower>,
+                   color: &LinearColor,
+                   power_per_intensity: f32)
+                   -> f32 {
+    match (intensity, power) {
+        (Some(intensity), _) => intensity,
+        (None, Some(power)) => power.watts(color) / power_per_intensity,
+        (None, None) => 0.0,
+    }
+}
+
+/// Vérifie qu'une lumière est réglée soit par son intensité, soit par sa puissance : sans l'une
+/// ni l'autre elle serait noire, et avec les deux l'une serait ignorée.
+fn check_light_units(intensity: Option<f32>, power: Option<LightPower>) -> Result<(), String> {
+    match (intensity, power) {
+        (Some(_), None) | (None, Some(_)) => Ok(()),
+        (None, None) => Err("The light needs an intensity or a power".to_string()),
+        (Some(_), Some(_)) => {
+            Err("The light can't have both an intensity and a power".to_string())
+        }
+    }
+}
+
+/// Un rayon d'ombre émis par une lumière vers un point, avec la radiance qu'il apporte. La
+/// lumière réfléchie vers l'observateur par une surface de BSDF f est f * radiance * cos, où cos
+/// est le cosinus entre le rayon et la normale de la surface.
+#[derive(Debug, Clone, Copy)]
+pub struct LightSample {
+    pub ray: Ray,
+    pub radiance: LinearColor,
+}
+
+/// Une direction vers une lumière tirée par `Light::sample_li` : le rayon d'ombre, la radiance
+/// incidente au point éclairé, et la densité de probabilité de la direction par rapport à
+/// l'angle solide (1 pour une lumière de Dirac, dont la direction est imposée).
+#[derive(Debug, Clone, Copy)]
+pub struct LightLiSample {
+    pub ray: Ray,
+    pub radiance: LinearColor,
+    pub pdf: f32,
+}
+
+/// L'intersection d'un rayon de direction unitaire avec une lumière : la distance du point
+/// touché (négative pour une lumière à l'infini), la radiance émise vers l'origine du rayon, et
+/// la densité avec laquelle `Light::sample_li` aurait tiré cette direction.
+#[derive(Debug, Clone, Copy)]
+pub struct LightHit {
+    pub distance: f32,
+    pub radiance: LinearColor,
+    pub pdf: f32,
+}
 
 /** Un trait qui represente une lumière */
 pub trait Light {
-    fn visible(&self, point: &Vector3f, world: &World) -> bool;
-    fn emit_rays(&self, point: &Vector3f, world: &World) -> Vec<Ray>;
-    fn intensity(&self) -> f32;
+    /// Vrai si l'un des rayons d'ombre de `emit_rays` atteint `point` à l'instant `time` sans
+    /// être arrêté.
+    fn visible(&self, point: &Vector3f, time: f32, world: &World, sampler: &mut Sampler) -> bool {
+        self.emit_rays(point, time, world, sampler)
+            .iter()
+            .any(|sample| !world.is_occluded(&mut sample.ray.clone()))
+    }
+
+    /// Tire une direction vers la lumière depuis `point`, à partir d'un point `u` de
+    /// [0;1[ x [0;1[. `time` est l'instant du rayon, dans l'intervalle d'ouverture de
+    /// l'obturateur, pour les lumières qui bougent. Renvoie `None` si la lumière n'éclaire pas
+    /// `point` dans cette direction.
+    fn sample_li(&self,
+                 point: &Vector3f,
+                 u: Vector2f,
+                 time: f32,
+                 world: &World)
+                 -> Option<LightLiSample>;
+
+    /// Le nombre de directions tirées à chaque calcul d'éclairage direct.
+    fn samples(&self) -> u32 {
+        1
+    }
+
+    /// Vrai pour une lumière ponctuelle ou directionnelle : un rayon ne peut pas la toucher par
+    /// hasard, elle n'est atteinte qu'en tirant une direction sur la lumière.
+    fn is_delta(&self) -> bool {
+        false
+    }
+
+    /// Intersecte le rayon de direction unitaire `ray` avec la lumière, à l'instant `ray.time`.
+    fn intersect_li(&self, _: &Ray) -> Option<LightHit> {
+        None
+    }
+
+    /// La densité par rapport à l'angle solide avec laquelle `sample_li` tire la direction
+    /// unitaire `direction` depuis `point`, sans tenir compte des occultations.
+    fn pdf_li(&self, point: &Vector3f, direction: &Vector3f) -> f32 {
+        self.intersect_li(&Ray::new(*point, *direction)).map_or(0.0, |hit| hit.pdf)
+    }
+
+    /// Renvoie `samples()` rayons d'ombre vers `point`, avec la radiance que chacun apporte
+    /// divisée par sa densité et par le nombre de rayons. Les directions sont tirées à partir
+    /// de `sampler`, à l'instant `time`.
+    fn emit_rays(&self,
+                 point: &Vector3f,
+                 time: f32,
+                 world: &World,
+                 sampler: &mut Sampler)
+                 -> Vec<LightSample> {
+        let points = if self.is_delta() {
+            vec![Vector2f::new(0.5, 0.5)]
+        } else {
+            halton_unit_square(self.samples(), sampler)
+        };
+        let count = points.len() as f32;
+        points.into_iter()
+            .filter_map(|u| self.sample_li(point, u, time, world))
+            .filter(|sample| sample.pdf > 0.0)
+            .map(|sample| {
+                     let mut ray = sample.ray;
+                     ray.time = time;
+                     LightSample {
+                         ray: ray,
+                         radiance: sample.radiance / (sample.pdf * count),
+                     }
+                 })
+            .collect()
+    }
+
+    /// La puissance émise par la lumière, pondérée par la luminance de sa couleur. Elle sert à
+    /// choisir quelles lumières finies échantillonner.
+    fn power(&self) -> f32;
+
+    /// La boîte englobante de la lumière, ou `None` pour une lumière à l'infini, qui est alors
+    /// évaluée en chaque point quelle que soit la stratégie d'échantillonnage des lumières.
+    fn bounding_box(&self) -> Option<BoundingBox>;
 }
 
 // Pour la sérialisation
@@ -17,12 +206,184 @@ pub trait Light {
 #[serde(untagged)]
 pub enum LightObject {
     Point { point: PointLight },
+    Directional { directional: DirectionalLight },
+    Spot { spot: SpotLight },
+    Rect { rect: RectLight },
+    Disk { disk: DiskLight },
+    Sphere { sphere: SphereLight },
+    Environment { environment: EnvironmentLight },
 }
 
 impl LightObject {
     pub fn as_trait(&self) -> &Light {
         match *self {
             LightObject::Point { ref point } => point,
+            LightObject::Directional { ref directional } => directional,
+            LightObject::Spot { ref spot } => spot,
+            LightObject::Rect { ref rect } => rect,
+            LightObject::Disk { ref disk } => disk,
+            LightObject::Sphere { ref sphere } => sphere,
+            LightObject::Environment { ref environment } => environment,
+        }
+    }
+
+    /// Vérifie les paramètres de la lumière, et charge les données qui ne sont pas dans le
+    /// fichier de scène (l'image d'une lumière d'environnement). Utilisé en fin de
+    /// désérialisation.
+    pub fn load(&mut self) -> Result<(), String> {
+        match *self {
+            LightObject::Point { ref point } => point.check_units(),
+            LightObject::Spot { ref spot } => spot.check_units(),
+            LightObject::Rect { ref rect } => rect.check_units(),
+            LightObject::Disk { ref disk } => disk.check_units(),
+            LightObject::Sphere { ref sphere } => sphere.check_units(),
+            LightObject::Environment { ref mut environment } => environment.load(),
+            LightObject::Directional { .. } => Ok(()),
         }
     }
+
+    /// Renvoie la lumière d'environnement, s'il s'agit d'une lumière d'environnement.
+    pub fn as_environment(&self) -> Option<&EnvironmentLight> {
+        match *self {
+            LightObject::Environment { ref environment } => Some(environment),
+            _ => None,
+        }
+    }
+}
+
+/// Renvoie le rayon d'ombre qui arrive en `point` en se propageant dans la direction unitaire
+/// `direction`, pour une lumière située à l'infini. Il part de l'extérieur de la scène pour que
+/// tous les objets puissent faire de l'ombre, et s'arrête juste avant `point`.
+fn shadow_ray_from_infinity(direction: &Vector3f, point: &Vector3f, world: &World) -> Ray {
+    let bbox = world.bounding_box();
+    let distance = if bbox.is_empty() {
+        1.0
+    } else {
+        (bbox.max() - bbox.min()).norm() + (*point - bbox.centroid()).norm() + 1.0
+    };
+    let origin = *point - *direction * distance;
+    let mut ray = Ray::new(origin, *point - origin);
+    ray.max_t = 1.0 - RAY_EPSILON / distance;
+    ray
+}
+
+/// Renvoie `samples` points bien répartis dans [0;1[ x [0;1[ : les premiers points de la suite de
+/// Halton, tous décalés d'un même vecteur tiré par `sampler` (rotation de Cranley-Patterson). Les
+/// points changent ainsi d'un appel à l'autre sans perdre leur répartition.
+pub fn halton_unit_square(samples: u32, sampler: &mut Sampler) -> Vec<Vector2f> {
+    let shift = sampler.get_2d();
+    (0..samples)
+        .map(|i| {
+                 Vector2f::new((get_halton(i, 2) + shift.x) % 1.0,
+                               (get_halton(i, 3) + shift.y) % 1.0)
+             })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32;
+    use serde_json;
+    use color_float::Color;
+    use math::{Vector3f, VectorialOperations};
+    use scene::World;
+    use sampler::samplers::DefaultSampler;
+    use super::LightObject;
+
+    // L'éclairement reçu en `point` par une surface de normale `normal`, estimé en sommant les
+    // contributions de plusieurs appels à emit_rays.
+    fn irradiance(light: &LightObject, point: &Vector3f, normal: &Vector3f) -> f32 {
+        let world = World::new_empty();
+        let mut sampler = DefaultSampler::new(1);
+        let calls = 64;
+        let mut result = 0.0;
+        for _ in 0..calls {
+            for sample in light.as_trait().emit_rays(point, 0.0, &world, &mut sampler) {
+                let w = -sample.ray.slope() / sample.ray.slope().norm();
+                result += sample.radiance.get_internal_color().g() *
+                          f32::max(0.0, w.dot_product_ref(normal));
+            }
+        }
+        result / calls as f32
+    }
+
+    #[test]
+    fn test_area_lights_irradiance() {
+        let point = Vector3f::new(0.0, 0.0, 0.0);
+        let normal = Vector3f::new(0.0, 0.0, 1.0);
+        let white = r#""color": {"r": 255, "g": 255, "b": 255}, "intensity": 1.0, "samples": 16"#;
+
+        // Une sphère de rayon r à distance d : E = π r² / d²
+        let sphere: LightObject = serde_json::from_str(&format!(
+            r#"{{"sphere": {{"position": {{"x": 0, "y": 0, "z": 4}}, "radius": 1, {}}}}}"#,
+            white)).unwrap();
+        let expected = f32::consts::PI / 16.0;
+        assert!((irradiance(&sphere, &point, &normal) - expected).abs() < 0.03 * expected);
+
+        // Un disque de rayon r, face au point, à distance h : E = π r² / (h² + r²)
+        let disk: LightObject = serde_json::from_str(&format!(
+            r#"{{"disk": {{"position": {{"x": 0, "y": 0, "z": 2}},
+                          "direction": {{"x": 0, "y": 0, "z": -1}}, "radius": 1, {}}}}}"#,
+            white)).unwrap();
+        let expected = f32::consts::PI / 5.0;
+        assert!((irradiance(&disk, &point, &normal) - expected).abs() < 0.03 * expected);
+
+        // Un rectangle n'éclaire que du côté de edge_u x edge_v.
+        let rect = |edge_u: &str, edge_v: &str| -> LightObject {
+            serde_json::from_str(&format!(
+                r#"{{"rect": {{"position": {{"x": 0, "y": 0, "z": 2}},
+                              "edge_u": {}, "edge_v": {}, {}}}}}"#,
+                edge_u, edge_v, white)).unwrap()
+        };
+        let x = r#"{"x": 1, "y": 0, "z": 0}"#;
+        let y = r#"{"x": 0, "y": 1, "z": 0}"#;
+        assert!(irradiance(&rect(y, x), &point, &normal) > 0.0);
+        assert_eq!(irradiance(&rect(x, y), &point, &normal), 0.0);
+    }
+
+    #[test]
+    fn test_light_power() {
+        let point = Vector3f::new(0.0, 0.0, 0.0);
+        let normal = Vector3f::new(0.0, 0.0, 1.0);
+        let point_light = |distance: f32, color: &str, emission: &str| -> LightObject {
+            serde_json::from_str(&format!(
+                r#"{{"point": {{"position": {{"x": 0, "y": 0, "z": {}}},
+                               "color": {}, {}}}}}"#,
+                distance, color, emission)).unwrap()
+        };
+        let white = r#"{"r": 255, "g": 255, "b": 255}"#;
+
+        // L'éclairement décroît comme l'inverse du carré de la distance.
+        let near = irradiance(&point_light(1.0, white, r#""intensity": 2.0"#), &point, &normal);
+        let far = irradiance(&point_light(2.0, white, r#""intensity": 2.0"#), &point, &normal);
+        assert!((near - 2.0).abs() < 1e-4 && (far - 0.5).abs() < 1e-4);
+
+        // Une puissance de 4π watts correspond à une intensité de 1 W/sr.
+        let watts = format!(r#""power": {{"Watts": {}}}"#, 4.0 * f32::consts::PI);
+        let light = point_light(1.0, white, &watts);
+        assert!((irradiance(&light, &point, &normal) - 1.0).abs() < 1e-4);
+
+        // À flux lumineux égal, une lumière verte émet moins de watts qu'une lumière blanche,
+        // mais sa luminance est la même.
+        let lumens = r#""power": {"Lumens": 1000.0}"#;
+        let green = r#"{"r": 0, "g": 255, "b": 0}"#;
+        let white_light = point_light(1.0, white, lumens);
+        let green_light = point_light(1.0, green, lumens);
+        let world = World::new_empty();
+        let mut sampler = DefaultSampler::new(1);
+        let mut sample = |light: &LightObject| {
+            light.as_trait().emit_rays(&point, 0.0, &world, &mut sampler)[0]
+        };
+        let white_radiance = sample(&white_light).radiance;
+        let green_radiance = sample(&green_light).radiance;
+        assert!((white_radiance.luminance() - green_radiance.luminance()).abs() < 1e-3);
+        assert!(green_radiance.get_internal_color().g() > white_radiance.get_internal_color().g());
+
+        // Une lumière est réglée soit par son intensité, soit par sa puissance.
+        assert!(point_light(1.0, white, r#""intensity": 2.0"#).load().is_ok());
+        assert!(point_light(1.0, white, lumens).load().is_ok());
+        let both = format!(r#""intensity": 2.0, {}"#, lumens);
+        assert!(point_light(1.0, white, &both).load().is_err());
+        assert!(point_light(1.0, white, r#""samples": 1"#).load().is_err());
+    }
 }
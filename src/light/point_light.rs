@@ -1,36 +1,81 @@
-use math::Vector3f;
+use math::{Vector2f, Vector3f, VectorialOperations};
 use scene::World;
-use light::Light;
+use light::{Light, LightLiSample, LightPower, light_intensity, check_light_units};
 use ray::Ray;
-use color_float::RGBColor;
+use sampler::Sampler;
+use color_float::{RGBColor, LinearColor};
+use std::f32;
+use geometry::bounding_box::BoundingBox;
 
-/** Represente une lumière ponctuelle */
+/** Represente une lumière ponctuelle, qui émet la même intensité `intensity` (en W/sr) dans
+toutes les directions : une surface qui lui fait face à une distance d reçoit un éclairement
+`color` * `intensity` / d². La lumière peut être réglée à la place par sa puissance totale
+`power`. */
 #[derive(Serialize,Deserialize, Debug)]
 pub struct PointLight {
     position: Vector3f,
     color: RGBColor,
-    intensity: f32,
+    #[serde(default)]
+    intensity: Option<f32>,
+    #[serde(default)]
+    power: Option<LightPower>,
 }
 
+impl PointLight {
+    // Une lumière ponctuelle d'intensité I émet une puissance 4π * I.
+    fn intensity(&self) -> f32 {
+        let color: LinearColor = self.color.into();
+        light_intensity(self.intensity, self.power, &color, 4.0 * f32::consts::PI)
+    }
+
+    /// Vérifie que la lumière est réglée soit par son intensité, soit par sa puissance.
+    pub fn check_units(&self) -> Result<(), String> {
+        check_light_units(self.intensity, self.power)
+    }
+}
 
 impl Light for PointLight {
-    fn visible(&self, point: &Vector3f, world: &World) -> bool {
+    fn visible(&self, point: &Vector3f, time: f32, world: &World, _: &mut Sampler) -> bool {
         let slope = *point - self.position;
         let mut ray: Ray = Ray::new(self.position, slope);
         ray.max_t = 0.999;
+        ray.time = time;
         !world.is_occluded(&mut ray)
     }
 
-    fn emit_rays(&self, point: &Vector3f, _: &World) -> Vec<Ray> {
-        let mut result: Vec<Ray> = vec![];
+    fn sample_li(&self,
+                 point: &Vector3f,
+                 _: Vector2f,
+                 _: f32,
+                 _: &World)
+                 -> Option<LightLiSample> {
         let slope = *point - self.position;
+        let distance2 = slope.dot_product_ref(&slope);
+        if distance2 <= 0.0 {
+            return None;
+        }
         let mut ray: Ray = Ray::new(self.position, slope);
         ray.max_t = 0.999;
-        result.push(ray);
-        result
+        let color: LinearColor = self.color.into();
+        Some(LightLiSample {
+                 ray: ray,
+                 radiance: color * (self.intensity() / distance2),
+                 pdf: 1.0,
+             })
     }
 
-    fn intensity(&self) -> f32 {
-        self.intensity
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    fn power(&self) -> f32 {
+        let color: LinearColor = self.color.into();
+        4.0 * f32::consts::PI * self.intensity() * color.luminance()
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let mut bbox = BoundingBox::new();
+        bbox.add_point(self.position);
+        Some(bbox)
     }
 }
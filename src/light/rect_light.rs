@@ -0,0 +1,131 @@
+use std::f32;
+use math::{Vector2f, Vector3f, VectorialOperations};
+use scene::World;
+use light::{Light, LightLiSample, LightHit, LightPower, default_area_light_samples,
+            light_intensity, check_light_units};
+use ray::Ray;
+use color_float::{RGBColor, LinearColor};
+use integrator::RAY_EPSILON;
+use geometry::bounding_box::BoundingBox;
+
+/** Une lumière rectangulaire, de centre `position` et de côtés `edge_u` et `edge_v`. Elle émet
+une radiance uniforme `color` * `intensity` (en W/(sr.m²)), du côté de `edge_u` x `edge_v`
+seulement, ou la radiance qui correspond à sa puissance `power`. À chaque calcul d'éclairage,
+`samples` points sont tirés sur le rectangle. La lumière elle-même n'est pas visible par la
+caméra. */
+#[derive(Serialize,Deserialize, Debug)]
+pub struct RectLight {
+    position: Vector3f,
+    edge_u: Vector3f,
+    edge_v: Vector3f,
+    color: RGBColor,
+    #[serde(default)]
+    intensity: Option<f32>,
+    #[serde(default)]
+    power: Option<LightPower>,
+    #[serde(default = "default_area_light_samples")]
+    samples: u32,
+}
+
+impl RectLight {
+    fn normal(&self) -> Vector3f {
+        let normal = self.edge_u.cross_product_ref(&self.edge_v);
+        normal / normal.norm()
+    }
+
+    fn corner(&self) -> Vector3f {
+        self.position - self.edge_u * 0.5 - self.edge_v * 0.5
+    }
+
+    fn area(&self) -> f32 {
+        self.edge_u.cross_product_ref(&self.edge_v).norm()
+    }
+
+    // Une surface de radiance L émet une puissance π * L par unité d'aire, d'un seul côté.
+    fn intensity(&self) -> f32 {
+        let color: LinearColor = self.color.into();
+        light_intensity(self.intensity, self.power, &color, f32::consts::PI * self.area())
+    }
+
+    /// Vérifie que la lumière est réglée soit par son intensité, soit par sa puissance.
+    pub fn check_units(&self) -> Result<(), String> {
+        check_light_units(self.intensity, self.power)
+    }
+}
+
+impl Light for RectLight {
+    // Un point tiré uniformément sur le rectangle donne une direction de densité
+    // distance² / (cos * aire).
+    fn sample_li(&self,
+                 point: &Vector3f,
+                 u: Vector2f,
+                 _: f32,
+                 _: &World)
+                 -> Option<LightLiSample> {
+        let normal = self.normal();
+        let position = self.corner() + self.edge_u * u.x + self.edge_v * u.y;
+        let to_point = *point - position;
+        let distance2 = to_point.dot_product_ref(&to_point);
+        if distance2 <= 0.0 {
+            return None;
+        }
+        let cos_light = normal.dot_product_ref(&to_point) / distance2.sqrt();
+        if cos_light <= 0.0 {
+            return None;
+        }
+        let origin = position + normal * RAY_EPSILON;
+        let mut ray = Ray::new(origin, *point - origin);
+        ray.max_t = 0.999;
+        let color: LinearColor = self.color.into();
+        Some(LightLiSample {
+                 ray: ray,
+                 radiance: color * self.intensity(),
+                 pdf: distance2 / (cos_light * self.area()),
+             })
+    }
+
+    fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    fn intersect_li(&self, ray: &Ray) -> Option<LightHit> {
+        let normal = self.normal();
+        let cos_light = -ray.slope().dot_product_ref(&normal);
+        if cos_light <= 0.0 {
+            return None;
+        }
+        let distance = (ray.origin() - self.position).dot_product_ref(&normal) / cos_light;
+        if distance <= 0.0 {
+            return None;
+        }
+        // Les coordonnées du point touché dans le repère (coin, edge_u, edge_v)
+        let local = ray.origin() + ray.slope() * distance - self.corner();
+        let cross = self.edge_u.cross_product_ref(&self.edge_v).dot_product_ref(&normal);
+        let a = local.cross_product_ref(&self.edge_v).dot_product_ref(&normal) / cross;
+        let b = self.edge_u.cross_product_ref(&local).dot_product_ref(&normal) / cross;
+        if a < 0.0 || a > 1.0 || b < 0.0 || b > 1.0 {
+            return None;
+        }
+        let color: LinearColor = self.color.into();
+        Some(LightHit {
+                 distance: distance,
+                 radiance: color * self.intensity(),
+                 pdf: distance * distance / (cos_light * self.area()),
+             })
+    }
+
+    fn power(&self) -> f32 {
+        let color: LinearColor = self.color.into();
+        f32::consts::PI * self.area() * self.intensity() * color.luminance()
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let corner = self.corner();
+        let mut bbox = BoundingBox::new();
+        bbox.add_point(corner);
+        bbox.add_point(corner + self.edge_u);
+        bbox.add_point(corner + self.edge_v);
+        bbox.add_point(corner + self.edge_u + self.edge_v);
+        Some(bbox)
+    }
+}
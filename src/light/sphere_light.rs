@@ -0,0 +1,135 @@
+use std::f32;
+use math::{Vector2f, Vector3f, VectorialOperations};
+use scene::World;
+use light::{Light, LightLiSample, LightHit, LightPower, default_area_light_samples,
+            light_intensity, check_light_units};
+use ray::Ray;
+use color_float::{RGBColor, LinearColor};
+use material::ShadingCoordinateSystem;
+use integrator::RAY_EPSILON;
+use geometry::bounding_box::BoundingBox;
+
+/** Une lumière sphérique, de centre `position` et de rayon `radius`, qui émet une radiance
+uniforme `color` * `intensity` (en W/(sr.m²)) dans toutes les directions, ou la radiance qui
+correspond à sa puissance `power`. Les directions sont tirées uniformément dans le cône sous
+lequel la sphère est vue depuis le point éclairé. La lumière elle-même n'est pas visible par la
+caméra. */
+#[derive(Serialize,Deserialize, Debug)]
+pub struct SphereLight {
+    position: Vector3f,
+    radius: f32,
+    color: RGBColor,
+    #[serde(default)]
+    intensity: Option<f32>,
+    #[serde(default)]
+    power: Option<LightPower>,
+    #[serde(default = "default_area_light_samples")]
+    samples: u32,
+}
+
+impl SphereLight {
+    // Une surface de radiance L émet une puissance π * L par unité d'aire.
+    fn intensity(&self) -> f32 {
+        let color: LinearColor = self.color.into();
+        let area = 4.0 * f32::consts::PI * self.radius * self.radius;
+        light_intensity(self.intensity, self.power, &color, f32::consts::PI * area)
+    }
+
+    /// Vérifie que la lumière est réglée soit par son intensité, soit par sa puissance.
+    pub fn check_units(&self) -> Result<(), String> {
+        check_light_units(self.intensity, self.power)
+    }
+
+    // Le cosinus du demi-angle du cône sous lequel la sphère est vue depuis `point`, ou `None`
+    // si le point est à l'intérieur de la lumière.
+    fn cos_max(&self, point: &Vector3f) -> Option<f32> {
+        let to_center = self.position - *point;
+        let distance2 = to_center.dot_product_ref(&to_center);
+        let radius2 = self.radius * self.radius;
+        if distance2 <= radius2 {
+            None
+        } else {
+            Some((1.0 - radius2 / distance2).sqrt())
+        }
+    }
+}
+
+impl Light for SphereLight {
+    // Toutes les directions du cône ont la même probabilité : la densité est l'inverse de
+    // l'angle solide du cône.
+    fn sample_li(&self,
+                 point: &Vector3f,
+                 u: Vector2f,
+                 _: f32,
+                 _: &World)
+                 -> Option<LightLiSample> {
+        let to_center = self.position - *point;
+        let distance2 = to_center.dot_product_ref(&to_center);
+        let radius2 = self.radius * self.radius;
+        let cos_max = match self.cos_max(point) {
+            Some(cos_max) => cos_max,
+            None => return None,
+        };
+        let distance = distance2.sqrt();
+        let frame = ShadingCoordinateSystem::new_from_normal(&to_center);
+        let cos_theta = 1.0 - u.x * (1.0 - cos_max);
+        let sin_theta = f32::max(0.0, 1.0 - cos_theta * cos_theta).sqrt();
+        let phi = 2.0 * f32::consts::PI * u.y;
+        let w = frame.local_into_world_space(&Vector3f::new(sin_theta * phi.cos(),
+                                                             sin_theta * phi.sin(),
+                                                             cos_theta));
+        // Le point de la sphère le plus proche dans la direction w
+        let along = distance * cos_theta -
+                    f32::max(0.0, radius2 - distance2 * sin_theta * sin_theta).sqrt();
+        let position = *point + w * along;
+        let normal = (position - self.position) / self.radius;
+        let origin = position + normal * RAY_EPSILON;
+        let mut ray = Ray::new(origin, *point - origin);
+        ray.max_t = 0.999;
+        let color: LinearColor = self.color.into();
+        Some(LightLiSample {
+                 ray: ray,
+                 radiance: color * self.intensity(),
+                 pdf: 1.0 / (2.0 * f32::consts::PI * (1.0 - cos_max)),
+             })
+    }
+
+    fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    fn intersect_li(&self, ray: &Ray) -> Option<LightHit> {
+        let cos_max = match self.cos_max(&ray.origin()) {
+            Some(cos_max) => cos_max,
+            None => return None,
+        };
+        // L'intersection la plus proche avec la sphère, vue de l'extérieur
+        let to_center = self.position - ray.origin();
+        let along = to_center.dot_product_ref(&ray.slope());
+        let discriminant = along * along - to_center.dot_product_ref(&to_center) +
+                           self.radius * self.radius;
+        if along <= 0.0 || discriminant < 0.0 {
+            return None;
+        }
+        let color: LinearColor = self.color.into();
+        Some(LightHit {
+                 distance: along - discriminant.sqrt(),
+                 radiance: color * self.intensity(),
+                 pdf: 1.0 / (2.0 * f32::consts::PI * (1.0 - cos_max)),
+             })
+    }
+
+    fn power(&self) -> f32 {
+        let color: LinearColor = self.color.into();
+        let area = 4.0 * f32::consts::PI * self.radius * self.radius;
+        f32::consts::PI * area * self.intensity() * color.luminance()
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let extent = Vector3f::new(self.radius, self.radius, self.radius);
+        let mut bbox = BoundingBox::new();
+        bbox.add_point(self.position - extent);
+        bbox.add_point(self.position + extent);
+        Some(bbox)
+    }
+}
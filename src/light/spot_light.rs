@@ -0,0 +1,182 @@
+use math::{Vector2f, Vector3f, VectorialOperations};
+use scene::World;
+use light::{Light, LightLiSample, LightPower, light_intensity, check_light_units};
+use ray::Ray;
+use sampler::Sampler;
+use color_float::{RGBColor, LinearColor};
+use std::f32;
+use geometry::bounding_box::BoundingBox;
+
+/** Represente un spot : une lumière ponctuelle qui n'éclaire que dans un cône autour de
+`direction`. L'intensité est maximale à l'intérieur du cône de demi-angle `inner_angle`, puis
+décroît jusqu'à s'annuler au bord du cône de demi-angle `outer_angle` (en degrés). `falloff`
+règle la forme de cette décroissance. Dans l'axe, une surface à une distance d reçoit un
+éclairement `color` * `intensity` / d², où `intensity` est en W/sr. La lumière peut être réglée
+à la place par sa puissance totale `power`. */
+#[derive(Serialize,Deserialize, Debug)]
+pub struct SpotLight {
+    position: Vector3f,
+    // La direction de l'axe du spot
+    direction: Vector3f,
+    color: RGBColor,
+    #[serde(default)]
+    intensity: Option<f32>,
+    #[serde(default)]
+    power: Option<LightPower>,
+    inner_angle: f32,
+    outer_angle: f32,
+    #[serde(default = "default_falloff")]
+    falloff: f32,
+}
+
+fn default_falloff() -> f32 {
+    1.0
+}
+
+impl SpotLight {
+    // L'atténuation due au cône pour une direction (unitaire) partant du spot.
+    fn cone_attenuation(&self, w: &Vector3f) -> f32 {
+        let axis = self.direction / self.direction.norm();
+        let cos_theta = w.dot_product_ref(&axis);
+        let cos_inner = self.inner_angle.to_radians().cos();
+        let cos_outer = self.outer_angle.to_radians().cos();
+        if cos_theta >= cos_inner {
+            1.0
+        } else if cos_theta <= cos_outer {
+            0.0
+        } else {
+            ((cos_theta - cos_outer) / (cos_inner - cos_outer)).powf(self.falloff)
+        }
+    }
+
+    // Avec l'atténuation de `cone_attenuation`, un spot d'intensité I émet une puissance
+    // 2π * I * [(1 - cos inner) + (cos inner - cos outer) / (falloff + 1)].
+    fn intensity(&self) -> f32 {
+        let color: LinearColor = self.color.into();
+        let cos_inner = self.inner_angle.to_radians().cos();
+        let cos_outer = self.outer_angle.to_radians().cos();
+        light_intensity(self.intensity,
+                        self.power,
+                        &color,
+                        2.0 * f32::consts::PI *
+                        ((1.0 - cos_inner) + (cos_inner - cos_outer) / (self.falloff + 1.0)))
+    }
+
+    /// Vérifie que la lumière est réglée soit par son intensité, soit par sa puissance.
+    pub fn check_units(&self) -> Result<(), String> {
+        check_light_units(self.intensity, self.power)
+    }
+
+    fn shadow_ray(&self, point: &Vector3f) -> Ray {
+        let mut ray: Ray = Ray::new(self.position, *point - self.position);
+        ray.max_t = 0.999;
+        ray
+    }
+}
+
+impl Light for SpotLight {
+    fn visible(&self, point: &Vector3f, time: f32, world: &World, _: &mut Sampler) -> bool {
+        let to_point = *point - self.position;
+        let mut ray = self.shadow_ray(point);
+        ray.time = time;
+        self.cone_attenuation(&(to_point / to_point.norm())) > 0.0 && !world.is_occluded(&mut ray)
+    }
+
+    fn sample_li(&self,
+                 point: &Vector3f,
+                 _: Vector2f,
+                 _: f32,
+                 _: &World)
+                 -> Option<LightLiSample> {
+        let to_point = *point - self.position;
+        let distance2 = to_point.dot_product_ref(&to_point);
+        if distance2 <= 0.0 {
+            return None;
+        }
+        let attenuation = self.cone_attenuation(&(to_point / distance2.sqrt()));
+        if attenuation <= 0.0 {
+            return None;
+        }
+        let color: LinearColor = self.color.into();
+        Some(LightLiSample {
+                 ray: self.shadow_ray(point),
+                 radiance: color * (self.intensity() * attenuation / distance2),
+                 pdf: 1.0,
+             })
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    fn power(&self) -> f32 {
+        let color: LinearColor = self.color.into();
+        let cos_inner = self.inner_angle.to_radians().cos();
+        let cos_outer = self.outer_angle.to_radians().cos();
+        2.0 * f32::consts::PI * (1.0 - 0.5 * (cos_inner + cos_outer)) * self.intensity() *
+        color.luminance()
+    }
+
+    fn bounding_box(&self) -> Option<BoundingBox> {
+        let mut bbox = BoundingBox::new();
+        bbox.add_point(self.position);
+        Some(bbox)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f32;
+    use serde_json;
+    use math::Vector3f;
+    use super::SpotLight;
+
+    #[test]
+    fn test_spot_cone_attenuation() {
+        let spot = SpotLight {
+            position: Vector3f::new(0.0, 0.0, 0.0),
+            direction: Vector3f::new(0.0, 0.0, -2.0),
+            color: (255u8, 255u8, 255u8).into(),
+            intensity: Some(1.0),
+            power: None,
+            inner_angle: 20.0,
+            outer_angle: 40.0,
+            falloff: 1.0,
+        };
+        let at_angle = |angle: f32| {
+            let angle = angle.to_radians();
+            spot.cone_attenuation(&Vector3f::new(angle.sin(), 0.0, -angle.cos()))
+        };
+        assert_eq!(at_angle(0.0), 1.0);
+        assert_eq!(at_angle(19.0), 1.0);
+        assert!(at_angle(30.0) > 0.0 && at_angle(30.0) < 1.0);
+        assert!(at_angle(35.0) < at_angle(25.0));
+        assert_eq!(at_angle(41.0), 0.0);
+        assert_eq!(at_angle(120.0), 0.0);
+    }
+
+    // La puissance émise, intégrée numériquement sur la sphère, est celle demandée quelle que
+    // soit la forme de la décroissance.
+    #[test]
+    fn test_spot_power() {
+        for &falloff in &[1.0, 4.0] {
+            let spot: SpotLight = serde_json::from_str(&format!(
+                r#"{{"position": {{"x": 0, "y": 0, "z": 0}},
+                    "direction": {{"x": 0, "y": 0, "z": 1}},
+                    "color": {{"r": 255, "g": 255, "b": 255}}, "power": {{"Watts": 100.0}},
+                    "inner_angle": 20.0, "outer_angle": 50.0, "falloff": {}}}"#,
+                falloff)).unwrap();
+            // L'atténuation ne dépend que de cos θ, uniforme sur la sphère.
+            let steps = 100000;
+            let mut power = 0.0;
+            for i in 0..steps {
+                let cos_theta = -1.0 + 2.0 * (i as f32 + 0.5) / steps as f32;
+                let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+                let w = Vector3f::new(sin_theta, 0.0, cos_theta);
+                power += spot.cone_attenuation(&w);
+            }
+            power *= spot.intensity() * 4.0 * f32::consts::PI / steps as f32;
+            assert!((power - 100.0).abs() < 0.1, "falloff {} : {} W", falloff, power);
+        }
+    }
+}
@@ -21,14 +21,14 @@ fn generate_template(path: &str) {
 
     scene.save_to_file(path);
 
-    let material_solid = material::flat_material::FlatMaterial::new_empty();
+    let material_solid = MaterialObject::new_empty();
     material_solid.save_to_file("template_material_solid.json");
 
-    let mut material_tex = material::flat_material::FlatMaterial::new_empty();
+    let mut material_tex = PhongMaterial::new_empty();
     material_tex.diffuse = Channel::TextureMap { texture: TextureMap::new_empty() };
     material_tex.specular = Channel::TextureMap { texture: TextureMap::new_empty() };
     material_tex.ambient = Channel::TextureMap { texture: TextureMap::new_empty() };
-    material_tex.save_to_file("template_material_texture.json");
+    MaterialObject::Phong(material_tex).save_to_file("template_material_texture.json");
 
 }
 
@@ -42,6 +42,12 @@ fn generate_template(path: &str) {
 //      -r [PATH] or --read [PATH]
 //      Specify the output file
 //      -w [PATH] or --write output [PATH] (optional)
+//      Choose the camera, by name or index (optional, the first camera by default)
+//      -c [CAMERA] or --camera [CAMERA]
+//      Render every camera, the output path being a pattern (optional)
+//      -a or --all-cameras
+//      Render every frame of the animation, the output path being a pattern (optional)
+//      -f or --frames
 fn parse_arg() {
     let mut options = Options::new();
 
@@ -61,6 +67,13 @@ fn parse_arg() {
     // -> Set the output file
     options.optflagopt("w", "write", "Save the rendered image to a file", "FILE");
 
+    // -> Choose the camera
+    options.optopt("c", "camera", "Render the camera with this name or index", "CAMERA");
+    options.optflag("a", "all-cameras", "Render every camera to its own file");
+
+    // -> Render an animation
+    options.optflag("f", "frames", "Render every frame of the animation");
+
     // Collecting the argument from the environnement
     let args: Vec<String> = env::args().collect();
     let program = args[0].clone();
@@ -103,7 +116,20 @@ fn parse_arg() {
                     return;
                 }
             };
-            render(&input_path, &output_path);
+            // La scène n'est chargée qu'une fois, même si plusieurs caméras ou plusieurs images
+            // sont rendues.
+            let mut scene = Scene::load_from_file(&input_path);
+            let camera = matches.opt_str("c");
+            let camera = camera.as_ref().map(|camera| camera.as_str());
+            let all_cameras = matches.opt_present("a");
+            let result = if matches.opt_present("f") {
+                scene.render_frames(camera, all_cameras, &output_path)
+            } else {
+                scene.render_cameras(camera, all_cameras, &output_path)
+            };
+            if let Err(e) = result {
+                println!("{}", e);
+            }
         } else {
             show_usage(&program);
         }
@@ -118,6 +144,14 @@ fn show_usage(program: &str) {
     println!("-r FILE or --read FILE : Read FILE to load the scene before rendering. Needed for \
               rendering, without a scene specified, the program will not render.");
     println!("-w FILE or --write FILE : Write the output to FILE. The default is 'untitled.png'");
+    println!("-c CAMERA or --camera CAMERA : Render the camera named CAMERA, or with the index \
+              CAMERA. The default is the first camera");
+    println!("-a or --all-cameras : Render every camera. FILE is then a pattern where {{name}} \
+              and {{index}} are replaced by the name and the index of each camera, for example \
+              'out_{{name}}.png'. Without them, the name is added before the extension");
+    println!("-f or --frames : Render every frame of the animation. FILE is then a pattern where \
+              {{frame}} is replaced by the number of the frame, for example 'out_{{frame}}.png'. \
+              Without it, the number is added before the extension : 'out_0001.png'");
 }
 
 fn test_image() {
@@ -125,12 +159,6 @@ fn test_image() {
     image.write_to_file("object.png");
 }
 
-// La fonction que l'on appelle pour effectuer le rendu.
-fn render(input: &str, output: &str) {
-    let scene = Scene::load_from_file(input);
-    scene.render_to_file(output);
-}
-
 fn main() {
     parse_arg();
 }
@@ -1,2 +1,321 @@
+use std::f32;
+use color_float::LinearColor;
+use math::{Vector2f, Vector3f, VectorialOperations};
+use material::ShadingCoordinateSystem;
+use scene::World;
+use light::{Light, halton_unit_square};
+use ray::Ray;
+use sampler::Sampler;
+use integrator::RAY_EPSILON;
 
-pub trait BSDF {}
+/// Une direction tirée par une BSDF, avec la valeur de la BSDF et la densité de probabilité
+/// associées.
+#[derive(Debug, Clone, Copy)]
+pub struct BSDFSample {
+    /// La direction incidente tirée, dans le système de coordonnées de shading
+    pub wi: Vector3f,
+    /// La valeur de la BSDF pour le couple (wo, wi)
+    pub f: LinearColor,
+    /// La densité de probabilité de wi, par rapport à l'angle solide
+    pub pdf: f32,
+    /// Vrai si la direction a été tirée dans une distribution de Dirac (miroir, verre) : dans ce
+    /// cas `f` et `pdf` ne peuvent pas être évalués pour une autre direction.
+    pub specular: bool,
+}
+
+/// Une fonction de distribution de la réflectance et de la transmittance bidirectionnelle. Toutes
+/// les directions sont unitaires et exprimées dans le système de coordonnées de shading
+/// (voir `ShadingCoordinateSystem`), où la normale est l'axe z. `wo` est la direction vers
+/// l'observateur, `wi` la direction d'où vient la lumière.
+pub trait BSDF {
+    /// La valeur de la BSDF pour le couple de directions (wo, wi).
+    fn f(&self, wo: &Vector3f, wi: &Vector3f) -> LinearColor;
+
+    /// Tire une direction incidente `wi` à partir de `wo` et d'un point `u` de [0;1[ x [0;1[.
+    /// Renvoie `None` si aucune direction valide n'a pu être tirée.
+    fn sample_f(&self, wo: &Vector3f, u: Vector2f) -> Option<BSDFSample>;
+
+    /// La densité de probabilité avec laquelle `sample_f` tire `wi` à partir de `wo`.
+    fn pdf(&self, wo: &Vector3f, wi: &Vector3f) -> f32;
+}
+
+/// Une BSDF qui ne réfléchit aucune lumière, pour les matériaux qui ne font qu'émettre.
+pub struct BlackBSDF;
+
+impl BSDF for BlackBSDF {
+    fn f(&self, _: &Vector3f, _: &Vector3f) -> LinearColor {
+        LinearColor::new_black()
+    }
+
+    fn sample_f(&self, _: &Vector3f, _: Vector2f) -> Option<BSDFSample> {
+        None
+    }
+
+    fn pdf(&self, _: &Vector3f, _: &Vector3f) -> f32 {
+        0.0
+    }
+}
+
+/// La manière d'estimer l'éclairage direct. Le choix se fait dans le fichier de configuration du
+/// renderer, par exemple : `"direct_lighting": {"MIS": {"heuristic": "Power"}}`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum DirectLightingStrategy {
+    /// Les directions sont tirées sur les lumières seulement.
+    LightSampling,
+    /// Les directions sont tirées selon la BSDF seulement. Les lumières ponctuelles et
+    /// directionnelles, qu'aucune direction tirée ne peut toucher, sont toujours tirées sur la
+    /// lumière.
+    BSDFSampling,
+    /// Les deux stratégies sont combinées par échantillonnage multiple préférentiel (MIS) : chaque
+    /// direction est pondérée selon l'heuristique `heuristic`, ce qui évite le bruit des surfaces
+    /// brillantes éclairées par de grandes lumières.
+    MIS { heuristic: MISHeuristic },
+}
+
+impl Default for DirectLightingStrategy {
+    fn default() -> Self {
+        DirectLightingStrategy::MIS { heuristic: MISHeuristic::Power }
+    }
+}
+
+/// Le poids donné à une direction tirée avec la densité `pdf`, quand l'autre stratégie l'aurait
+/// tirée avec la densité `other_pdf`. Les deux stratégies tirent le même nombre de directions.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum MISHeuristic {
+    /// pdf / (pdf + other_pdf)
+    Balance,
+    /// pdf² / (pdf² + other_pdf²)
+    Power,
+}
+
+impl MISHeuristic {
+    pub fn weight(&self, pdf: f32, other_pdf: f32) -> f32 {
+        let (a, b) = match *self {
+            MISHeuristic::Balance => (pdf, other_pdf),
+            MISHeuristic::Power => (pdf * pdf, other_pdf * other_pdf),
+        };
+        if a + b > 0.0 { a / (a + b) } else { 0.0 }
+    }
+}
+
+/// Calcule la lumière des lumières réfléchie vers `wo` par une surface de BSDF `bsdf` en
+/// `position`, en tenant compte des ombres. Les lumières évaluées sont choisies par
+/// `World::sample_lights`, et les directions tirées selon `World::direct_lighting`. `frame` est
+/// le système de coordonnées de shading de la surface, et `time` l'instant du rayon qui a touché
+/// la surface, auquel sont lancés les rayons d'ombre. Les nombres aléatoires sont tirés par
+/// `sampler`.
+pub fn estimate_direct_lighting(bsdf: &BSDF,
+                                frame: &ShadingCoordinateSystem,
+                                wo: &Vector3f,
+                                position: &Vector3f,
+                                time: f32,
+                                world: &World,
+                                sampler: &mut Sampler)
+                                -> LinearColor {
+    let strategy = world.direct_lighting();
+    let mut result = LinearColor::new_black();
+    for (light, weight) in world.sample_lights(position, sampler.get_1d()) {
+        let heuristic = match strategy {
+            _ if light.is_delta() => None,
+            DirectLightingStrategy::LightSampling => None,
+            DirectLightingStrategy::BSDFSampling => {
+                let radiance =
+                    sample_bsdf(bsdf, frame, wo, position, time, light, None, world, sampler);
+                result += &(radiance * weight);
+                continue;
+            }
+            DirectLightingStrategy::MIS { heuristic } => Some(heuristic),
+        };
+        let mut radiance =
+            sample_light(bsdf, frame, wo, position, time, light, heuristic, world, sampler);
+        if heuristic.is_some() {
+            radiance +=
+                &sample_bsdf(bsdf, frame, wo, position, time, light, heuristic, world, sampler);
+        }
+        result += &(radiance * weight);
+    }
+    result
+}
+
+// L'estimation de l'éclairage direct par `light` à partir de directions tirées sur la lumière,
+// pondérées selon `heuristic` si elles sont combinées avec des directions tirées selon la BSDF.
+#[allow(too_many_arguments)]
+fn sample_light(bsdf: &BSDF,
+                frame: &ShadingCoordinateSystem,
+                wo: &Vector3f,
+                position: &Vector3f,
+                time: f32,
+                light: &Light,
+                heuristic: Option<MISHeuristic>,
+                world: &World,
+                sampler: &mut Sampler)
+                -> LinearColor {
+    let points = if light.is_delta() {
+        vec![Vector2f::new(0.5, 0.5)]
+    } else {
+        halton_unit_square(light.samples(), sampler)
+    };
+    let count = points.len() as f32;
+    let mut result = LinearColor::new_black();
+    for u in points {
+        let mut sample = match light.sample_li(position, u, time, world) {
+            Some(sample) => sample,
+            None => continue,
+        };
+        if sample.pdf <= 0.0 {
+            continue;
+        }
+        let light_vect = -sample.ray.slope() / sample.ray.slope().norm();
+        let wi = frame.world_into_local_space(&light_vect);
+        let f = bsdf.f(wo, &wi);
+        sample.ray.time = time;
+        if f.is_black() || world.is_occluded(&mut sample.ray) {
+            continue;
+        }
+        let mis = match heuristic {
+            Some(heuristic) => heuristic.weight(sample.pdf, bsdf.pdf(wo, &wi)),
+            None => 1.0,
+        };
+        result += &(f * sample.radiance * (abs_cos_theta(&wi) * mis / (sample.pdf * count)));
+    }
+    result
+}
+
+// L'estimation de l'éclairage direct par `light` à partir de directions tirées selon la BSDF,
+// pondérées selon `heuristic` si elles sont combinées avec des directions tirées sur la
+// lumière.
+#[allow(too_many_arguments)]
+fn sample_bsdf(bsdf: &BSDF,
+               frame: &ShadingCoordinateSystem,
+               wo: &Vector3f,
+               position: &Vector3f,
+               time: f32,
+               light: &Light,
+               heuristic: Option<MISHeuristic>,
+               world: &World,
+               sampler: &mut Sampler)
+               -> LinearColor {
+    let normal = frame.local_into_world_space(&Vector3f::new(0.0, 0.0, 1.0));
+    let points = halton_unit_square(light.samples(), sampler);
+    let count = points.len() as f32;
+    let mut result = LinearColor::new_black();
+    for u in points {
+        let sample = match bsdf.sample_f(wo, u) {
+            Some(sample) => sample,
+            None => continue,
+        };
+        if sample.specular || sample.pdf <= 0.0 || sample.f.is_black() {
+            continue;
+        }
+        let wi = frame.local_into_world_space(&sample.wi);
+        let wi = wi / wi.norm();
+        // On décale l'origine du rayon du côté de la surface où part la direction.
+        let origin = if cos_theta(&sample.wi) > 0.0 {
+            *position + normal * RAY_EPSILON
+        } else {
+            *position - normal * RAY_EPSILON
+        };
+        let mut ray = Ray::new(origin, wi);
+        ray.time = time;
+        let hit = match light.intersect_li(&ray) {
+            Some(hit) => hit,
+            None => continue,
+        };
+        ray.max_t = if hit.distance < 0.0 {
+            f32::MAX
+        } else {
+            hit.distance * 0.999
+        };
+        if hit.pdf <= 0.0 || world.is_occluded(&mut ray) {
+            continue;
+        }
+        let mis = match heuristic {
+            Some(heuristic) => heuristic.weight(sample.pdf, hit.pdf),
+            None => 1.0,
+        };
+        result += &(sample.f * hit.radiance *
+                    (abs_cos_theta(&sample.wi) * mis / (sample.pdf * count)));
+    }
+    result
+}
+
+/// Le cosinus de l'angle entre une direction du système de coordonnées de shading et la
+/// normale.
+pub fn cos_theta(w: &Vector3f) -> f32 {
+    w.z
+}
+
+/// La valeur absolue de `cos_theta`.
+pub fn abs_cos_theta(w: &Vector3f) -> f32 {
+    w.z.abs()
+}
+
+/// Vrai si les deux directions sont du même côté de la surface.
+pub fn same_hemisphere(w: &Vector3f, wp: &Vector3f) -> bool {
+    w.z * wp.z > 0.0
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json;
+    use color_float::{LinearColor, Color};
+    use math::Vector3f;
+    use material::ShadingCoordinateSystem;
+    use material::lambert::LambertianBSDF;
+    use light::LightObject;
+    use scene::World;
+    use sampler::samplers::DefaultSampler;
+    use super::{MISHeuristic, sample_light, sample_bsdf};
+
+    #[test]
+    fn test_mis_heuristics() {
+        for heuristic in &[MISHeuristic::Balance, MISHeuristic::Power] {
+            let sum = heuristic.weight(0.3, 2.0) + heuristic.weight(2.0, 0.3);
+            assert!((sum - 1.0).abs() < 1e-6);
+            assert_eq!(heuristic.weight(0.0, 0.0), 0.0);
+        }
+        assert!(MISHeuristic::Power.weight(2.0, 1.0) > MISHeuristic::Balance.weight(2.0, 1.0));
+    }
+
+    #[test]
+    fn test_direct_lighting_strategies() {
+        // Une sphère de rayon 1 et de radiance 1 à distance 4 d'une surface blanche lambertienne :
+        // l'éclairement est π / 16, la radiance réfléchie 1 / 16.
+        let light: LightObject = serde_json::from_str(
+            r#"{"sphere": {"position": {"x": 0, "y": 0, "z": 4}, "radius": 1,
+                           "color": {"r": 255, "g": 255, "b": 255}, "intensity": 1.0,
+                           "samples": 16}}"#).unwrap();
+        let light = light.as_trait();
+        let world = World::new_empty();
+        let bsdf = LambertianBSDF::new(LinearColor::new_white());
+        let frame = ShadingCoordinateSystem::new_from_normal(&Vector3f::new(0.0, 0.0, 1.0));
+        let wo = Vector3f::new(0.0, 0.0, 1.0);
+        let point = Vector3f::new(0.0, 0.0, 0.0);
+        let mut sampler = DefaultSampler::new(1);
+        let mut estimate = |light_strategy: bool, heuristic: Option<MISHeuristic>| -> f32 {
+            let calls = 2000;
+            let mut result = 0.0;
+            for _ in 0..calls {
+                let (bsdf, sampler) = (&bsdf, &mut sampler);
+                let radiance = if light_strategy {
+                    sample_light(bsdf, &frame, &wo, &point, 0.0, light, heuristic, &world, sampler)
+                } else {
+                    sample_bsdf(bsdf, &frame, &wo, &point, 0.0, light, heuristic, &world, sampler)
+                };
+                result += radiance.get_internal_color().g();
+            }
+            result / calls as f32
+        };
+
+        let expected = 1.0 / 16.0;
+        assert!((estimate(true, None) - expected).abs() < 0.02 * expected);
+        assert!((estimate(false, None) - expected).abs() < 0.1 * expected);
+        for heuristic in &[MISHeuristic::Balance, MISHeuristic::Power] {
+            let combined = estimate(true, Some(*heuristic)) + estimate(false, Some(*heuristic));
+            assert!((combined - expected).abs() < 0.03 * expected,
+                    "{:?} : {}",
+                    heuristic,
+                    combined);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use color_float::{RGBColor, LinearColor, FloatColor, Color};
+use color_float::{RGBColor, LinearColor, FloatColor, Color, ColorSpace};
 use img::{Image, RGBAPixel};
 use std::collections::HashMap;
 use ray::Fragment;
@@ -25,6 +25,10 @@ pub struct TextureMap {
     map_path: String,
     tiling_x: f32,
     tiling_y: f32,
+    // L'espace de couleur de l'image. Sans précision, l'image est décodée depuis sRGB dans les
+    // canaux de couleur, et lue telle quelle dans les canaux de données (rugosité, métal...).
+    #[serde(default)]
+    color_space: Option<ColorSpace>,
 }
 
 impl TextureMap {
@@ -36,6 +40,7 @@ impl TextureMap {
             map_path: texture_path,
             tiling_x: tiling_x,
             tiling_y: tiling_y,
+            color_space: None,
         }
     }
     pub fn new_empty() -> Self {
@@ -43,8 +48,32 @@ impl TextureMap {
             tiling_x: 1.0,
             tiling_y: 1.0,
             map_path: "/empty/map/path".to_string(),
+            color_space: None,
         }
     }
+
+    /// Force l'espace de couleur de l'image, quel que soit le canal qui l'utilise.
+    pub fn set_color_space(&mut self, color_space: Option<ColorSpace>) {
+        self.color_space = color_space;
+    }
+
+    /// La couleur linéaire de la texture aux coordonnées (u, v). L'image est décodée depuis son
+    /// espace de couleur, ou depuis `default_space` s'il n'est pas précisé.
+    pub fn sample(&self,
+                  u: f32,
+                  v: f32,
+                  texture_registry: &HashMap<String, Image<RGBAPixel>>,
+                  default_space: ColorSpace)
+                  -> LinearColor {
+        let texture = &texture_registry
+                           .get(self.map_path.as_str())
+                           .unwrap();
+        let pixel = texture.get_pixel_at(((u * self.tiling_x * texture.width() as f32) as u32 %
+                                          texture.width()),
+                                         ((v * self.tiling_y * texture.height() as f32) as u32 %
+                                          texture.height()));
+        self.color_space.unwrap_or(default_space).decode(pixel)
+    }
 }
 
 impl Texture for TextureMap {
@@ -55,18 +84,8 @@ impl Texture for TextureMap {
                  texture_registry: Option<&HashMap<String, Image<RGBAPixel>>>,
                  _: &World)
                  -> LinearColor {
-
-        let texture = &texture_registry
-                           .unwrap()
-                           .get(self.map_path.as_str())
-                           .unwrap();
-        texture
-            .get_pixel_at(((u.unwrap() * self.tiling_x * texture.width() as f32) as u32 %
-                           texture.width()),
-                          ((v.unwrap() * self.tiling_y * texture.height() as f32) as u32 %
-                           texture.height()))
-            .into()
-
+        // Utilisée comme couleur, l'image est par défaut en sRGB.
+        self.sample(u.unwrap(), v.unwrap(), texture_registry.unwrap(), ColorSpace::SRGB)
     }
 }
 
@@ -96,6 +115,7 @@ impl Texture for NormalMap {
 #[serde(untagged)]
 pub enum Channel {
     Solid { color: RGBColor },
+    Value { value: f32 },
     TextureMap { texture: TextureMap },
     NormalMap { normal: NormalMap },
     AmbientOcclusionMap { ambient_occlusion: AmbientOcclusionMap, },
@@ -125,17 +145,79 @@ impl Channel {
                 ambient_occlusion.get_color(frag, None, None, None, world)
             }
             (_, _, _, &Channel::Solid { color }) => color.into(),
+            (_, _, _, &Channel::Value { value }) => {
+                LinearColor::new(FloatColor::new(value, value, value))
+            }
 
             _ => panic!("Error get_color"),
         }
     }
 
+    // Renvoie la couleur du canal au point `frag`, en utilisant ses coordonnées de texture s'il
+    // en a et si les textures sont chargées.
+    pub fn color_at(&self,
+                    frag: &Fragment,
+                    texture_registry: Option<&HashMap<String, Image<RGBAPixel>>>,
+                    world: &World)
+                    -> LinearColor {
+        let (u, v, tex_reg) = texture_coordinates(frag, texture_registry);
+        self.get_color(frag, u, v, tex_reg, world)
+    }
+
+    // Renvoie la valeur scalaire du canal au point `frag`.
+    pub fn value_at(&self,
+                    frag: &Fragment,
+                    texture_registry: Option<&HashMap<String, Image<RGBAPixel>>>,
+                    world: &World)
+                    -> f32 {
+        let (u, v, tex_reg) = texture_coordinates(frag, texture_registry);
+        self.get_value(frag, u, v, tex_reg, world)
+    }
+
+    // Renvoie la valeur scalaire du canal (la moyenne des composantes de sa couleur). Utile pour
+    // les canaux qui ne représentent pas une couleur, comme la rugosité : les couleurs unies et
+    // les textures y sont lues dans l'espace linéaire par défaut.
+    pub fn get_value(&self,
+                     frag: &Fragment,
+                     u: Option<f32>,
+                     v: Option<f32>,
+                     texture_registry: Option<&HashMap<String, Image<RGBAPixel>>>,
+                     world: &World)
+                     -> f32 {
+        let color = match (u, v, texture_registry, self) {
+            (_, _, _, &Channel::Value { value }) => return value,
+            // Une couleur unie n'est pas non plus encodée en sRGB dans un canal de données.
+            (_, _, _, &Channel::Solid { color }) => {
+                let (r, g, b): (u8, u8, u8) = color.into();
+                ColorSpace::Linear.decode((r, g, b, 255))
+            }
+            // Une texture de données n'est pas encodée en sRGB, sauf précision contraire.
+            (Some(u), Some(v), Some(texture_registry), &Channel::TextureMap { ref texture }) => {
+                texture.sample(u, v, texture_registry, ColorSpace::Linear)
+            }
+            _ => self.get_color(frag, u, v, texture_registry, world),
+        };
+        let color = color.get_internal_color();
+        (color.r() + color.g() + color.b()) / 3.0
+    }
+
     pub fn is_solid(&self) -> bool {
         match *self {
             Channel::Solid { .. } => true,
             _ => false,
         }
     }
+    // Renvoie la couleur du canal si elle ne dépend pas du point de la surface.
+    pub fn uniform_color(&self) -> Option<LinearColor> {
+        match *self {
+            Channel::Solid { color } => Some(color.into()),
+            Channel::Value { value } => {
+                Some(LinearColor::new(FloatColor::new(value, value, value)))
+            }
+            _ => None,
+        }
+    }
+
     pub fn is_texture(&self) -> bool {
         match *self {
             Channel::TextureMap { .. } => true,
@@ -149,3 +231,44 @@ impl Channel {
         }
     }
 }
+
+// Renvoie les coordonnées de texture du fragment, et le registre de textures, s'ils sont tous les
+// deux disponibles.
+#[allow(type_complexity)]
+fn texture_coordinates<'a>(frag: &Fragment,
+                           texture_registry: Option<&'a HashMap<String, Image<RGBAPixel>>>)
+                           -> (Option<f32>,
+                               Option<f32>,
+                               Option<&'a HashMap<String, Image<RGBAPixel>>>) {
+    match (frag.tex, texture_registry) {
+        (Some(tex_coords), Some(texture_register)) => {
+            (Some(tex_coords.x), Some(tex_coords.y), Some(texture_register))
+        }
+        _ => (None, None, None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use color_float::Color;
+    use math::Vector3f;
+    use ray::Fragment;
+    use scene::World;
+    use super::Channel;
+
+    // Dans un canal de données, une couleur unie est lue telle quelle, comme une valeur.
+    #[test]
+    fn test_solid_data_channel() {
+        let world = World::new_empty();
+        let zero = Vector3f::new(0.0, 0.0, 0.0);
+        let frag = Fragment::new(zero, 1.0, zero, zero);
+        let solid = Channel::Solid { color: (128u8, 128u8, 128u8).into() };
+        let value = Channel::Value { value: 128.0 / 255.0 };
+        assert!((solid.value_at(&frag, None, &world) - 128.0 / 255.0).abs() < 1e-3);
+        assert!((solid.value_at(&frag, None, &world) - value.value_at(&frag, None, &world))
+                    .abs() < 1e-3);
+        // La couleur reste décodée depuis sRGB dans les canaux de couleur.
+        let color = solid.color_at(&frag, None, &world);
+        assert!(color.get_internal_color().g() < 0.3);
+    }
+}
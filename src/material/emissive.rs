@@ -0,0 +1,68 @@
+use color_float::LinearColor;
+use material::channel::Channel;
+use material::Material;
+use material::bsdf::{BSDF, BlackBSDF};
+use scene::World;
+use renderer::TextureRegister;
+use ray::{Fragment, Ray};
+use sampler::Sampler;
+
+/// Un matériau qui émet de la lumière, de la couleur de `emission` multipliée par `intensity`,
+/// sans en réfléchir.
+#[derive(Serialize,Deserialize,Debug,Clone)]
+pub struct EmissiveMaterial {
+    pub emission: Channel,
+    #[serde(default = "default_intensity")]
+    pub intensity: f32,
+}
+
+fn default_intensity() -> f32 {
+    1.0
+}
+
+impl EmissiveMaterial {
+    pub fn new_empty() -> EmissiveMaterial {
+        EmissiveMaterial {
+            emission: Channel::Solid { color: (255u8, 255u8, 255u8).into() },
+            intensity: default_intensity(),
+        }
+    }
+}
+
+impl Material for EmissiveMaterial {
+    fn get_color(&self,
+                 frag: &Fragment,
+                 _: &Ray,
+                 world: &World,
+                 texture_data: Option<&TextureRegister>,
+                 _: &mut Sampler)
+                 -> LinearColor {
+        self.get_emission(frag, world, texture_data)
+    }
+
+    fn get_bsdf(&self, _: &Fragment, _: &World, _: Option<&TextureRegister>) -> Box<BSDF> {
+        Box::new(BlackBSDF)
+    }
+
+    fn get_emission(&self,
+                    frag: &Fragment,
+                    world: &World,
+                    texture_data: Option<&TextureRegister>)
+                    -> LinearColor {
+        self.emission.color_at(frag, texture_data, world) * self.intensity
+    }
+
+    fn uniform_emission(&self) -> Option<LinearColor> {
+        self.emission
+            .uniform_color()
+            .map(|color| color * self.intensity)
+    }
+
+    fn get_texture_paths(&self) -> Vec<String> {
+        if self.emission.is_texture() {
+            vec![self.emission.get_texture_path()]
+        } else {
+            vec![]
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use color_float::LinearColor;
+use material::channel::Channel;
+use material::{Material, specular_lookup};
+use material::bsdf::{BSDF, BSDFSample, cos_theta, abs_cos_theta};
+use math::{Vector2f, Vector3f, VectorialOperations};
+use integrator::{facing_normal, RAY_EPSILON};
+use scene::World;
+use renderer::TextureRegister;
+use ray::{Fragment, Ray};
+use sampler::Sampler;
+use tools::{reflect, refract, fresnel_dielectric};
+
+/// L'indice de réfraction du milieu dans lequel baignent les objets (l'air).
+const OUTSIDE_INDEX_OF_REFRACTION: f32 = 1.0;
+
+/// Un diélectrique parfaitement lisse, comme le verre ou l'eau : la lumière est réfléchie ou
+/// réfractée selon les équations de Fresnel. La couleur teinte la lumière transmise.
+#[derive(Serialize,Deserialize,Debug,Clone)]
+pub struct GlassMaterial {
+    #[serde(default = "default_color")]
+    pub color: Channel,
+    #[serde(default = "default_index_of_refraction")]
+    pub index_of_refraction: f32,
+}
+
+fn default_color() -> Channel {
+    Channel::Solid { color: (255u8, 255u8, 255u8).into() }
+}
+
+fn default_index_of_refraction() -> f32 {
+    1.5
+}
+
+impl GlassMaterial {
+    pub fn new_empty() -> GlassMaterial {
+        GlassMaterial {
+            color: default_color(),
+            index_of_refraction: default_index_of_refraction(),
+        }
+    }
+}
+
+impl Material for GlassMaterial {
+    // Comme le miroir, le verre n'a pas de couleur propre. Sans intégrateur qui suive les
+    // rebonds, on le traite comme une vitre fine : un rayon réfléchi et un rayon qui le traverse
+    // sans être dévié, pondérés par le coefficient de Fresnel (voir `specular_lookup`).
+    fn get_color(&self,
+                 frag: &Fragment,
+                 ray: &Ray,
+                 world: &World,
+                 texture_data: Option<&TextureRegister>,
+                 sampler: &mut Sampler)
+                 -> LinearColor {
+        let direction = ray.slope() / ray.slope().norm();
+        let normal = facing_normal(frag, ray);
+        let fresnel = fresnel_dielectric(direction.dot_product_ref(&normal),
+                                         OUTSIDE_INDEX_OF_REFRACTION,
+                                         self.index_of_refraction);
+
+        let mut reflected_ray = Ray::new(frag.position + normal * RAY_EPSILON,
+                                         reflect(&direction, &normal));
+        reflected_ray.time = ray.time;
+        let mut transmitted_ray = Ray::new(frag.position - normal * RAY_EPSILON, direction);
+        transmitted_ray.time = ray.time;
+
+        &(specular_lookup(&reflected_ray, world, texture_data, sampler) * fresnel) +
+        &(self.color.color_at(frag, texture_data, world) *
+          specular_lookup(&transmitted_ray, world, texture_data, sampler) *
+          (1.0 - fresnel))
+    }
+
+    fn get_bsdf(&self,
+                frag: &Fragment,
+                world: &World,
+                texture_data: Option<&TextureRegister>)
+                -> Box<BSDF> {
+        Box::new(SpecularGlassBSDF {
+                     color: self.color.color_at(frag, texture_data, world),
+                     index_of_refraction: self.index_of_refraction,
+                 })
+    }
+
+    fn get_texture_paths(&self) -> Vec<String> {
+        if self.color.is_texture() {
+            vec![self.color.get_texture_path()]
+        } else {
+            vec![]
+        }
+    }
+
+    fn transmissivity(&self) -> f32 {
+        1.0
+    }
+
+    fn transmission_color(&self,
+                          frag: &Fragment,
+                          world: &World,
+                          texture_data: Option<&TextureRegister>)
+                          -> LinearColor {
+        self.color.color_at(frag, texture_data, world)
+    }
+
+    fn index_of_refraction(&self) -> f32 {
+        self.index_of_refraction
+    }
+}
+
+/// La BSDF d'un diélectrique lisse : on tire la réflexion avec la probabilité donnée par le
+/// coefficient de Fresnel, et la réfraction sinon.
+struct SpecularGlassBSDF {
+    color: LinearColor,
+    index_of_refraction: f32,
+}
+
+impl BSDF for SpecularGlassBSDF {
+    fn f(&self, _: &Vector3f, _: &Vector3f) -> LinearColor {
+        LinearColor::new_black()
+    }
+
+    fn sample_f(&self, wo: &Vector3f, u: Vector2f) -> Option<BSDFSample> {
+        // On rentre dans l'objet si wo est du côté de la normale.
+        let entering = cos_theta(wo) > 0.0;
+        let (eta_i, eta_t, normal) = if entering {
+            (OUTSIDE_INDEX_OF_REFRACTION, self.index_of_refraction, Vector3f::new(0.0, 0.0, 1.0))
+        } else {
+            (self.index_of_refraction, OUTSIDE_INDEX_OF_REFRACTION, Vector3f::new(0.0, 0.0, -1.0))
+        };
+        let fresnel = fresnel_dielectric(cos_theta(wo), eta_i, eta_t);
+
+        if u.x < fresnel {
+            let wi = Vector3f::new(-wo.x, -wo.y, wo.z);
+            return Some(BSDFSample {
+                            wi: wi,
+                            f: LinearColor::new_white() * (fresnel / abs_cos_theta(&wi)),
+                            pdf: fresnel,
+                            specular: true,
+                        });
+        }
+
+        match refract(&-*wo, &normal, eta_i / eta_t) {
+            Some(wi) => {
+                // La radiance est concentrée (ou diluée) par le changement de milieu.
+                let factor = (1.0 - fresnel) * (eta_i * eta_i) / (eta_t * eta_t) /
+                             abs_cos_theta(&wi);
+                Some(BSDFSample {
+                         wi: wi,
+                         f: self.color * factor,
+                         pdf: 1.0 - fresnel,
+                         specular: true,
+                     })
+            }
+            None => None,
+        }
+    }
+
+    fn pdf(&self, _: &Vector3f, _: &Vector3f) -> f32 {
+        0.0
+    }
+}
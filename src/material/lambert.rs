@@ -0,0 +1,111 @@
+use std::f32;
+use color_float::LinearColor;
+use material::channel::Channel;
+use material::{Material, ShadingCoordinateSystem};
+use material::bsdf::{BSDF, BSDFSample, cos_theta, same_hemisphere, estimate_direct_lighting};
+use math::{Vector2f, Vector3f, VectorialOperations};
+use scene::World;
+use renderer::TextureRegister;
+use ray::{Fragment, Ray};
+use sampler::Sampler;
+use tools::monte_carlo;
+
+/// Un matériau parfaitement diffus : la lumière est réfléchie de la même façon dans toutes les
+/// directions.
+#[derive(Serialize,Deserialize,Debug,Clone)]
+pub struct LambertMaterial {
+    pub diffuse: Channel,
+}
+
+impl LambertMaterial {
+    pub fn new_empty() -> LambertMaterial {
+        LambertMaterial { diffuse: Channel::Solid { color: (200u8, 200u8, 200u8).into() } }
+    }
+}
+
+impl Material for LambertMaterial {
+    fn get_color(&self,
+                 frag: &Fragment,
+                 ray: &Ray,
+                 world: &World,
+                 texture_data: Option<&TextureRegister>,
+                 sampler: &mut Sampler)
+                 -> LinearColor {
+        // Sans lumière, on affiche simplement la couleur diffuse.
+        if world.light_count() == 0 {
+            return self.diffuse.color_at(frag, texture_data, world);
+        }
+        let bsdf = self.get_bsdf(frag, world, texture_data);
+        let frame = ShadingCoordinateSystem::new_from_normal(&frag.normal);
+        let wo = frame.world_into_local_space(&(-ray.slope() / ray.slope().norm()));
+        estimate_direct_lighting(&*bsdf,
+                                 &frame,
+                                 &wo,
+                                 &frag.position,
+                                 ray.time,
+                                 world,
+                                 sampler)
+    }
+
+    fn get_bsdf(&self,
+                frag: &Fragment,
+                world: &World,
+                texture_data: Option<&TextureRegister>)
+                -> Box<BSDF> {
+        Box::new(LambertianBSDF::new(self.diffuse.color_at(frag, texture_data, world)))
+    }
+
+    fn get_texture_paths(&self) -> Vec<String> {
+        if self.diffuse.is_texture() {
+            vec![self.diffuse.get_texture_path()]
+        } else {
+            vec![]
+        }
+    }
+}
+
+/// La BSDF lambertienne : réflectance / π, des deux côtés de la surface.
+pub struct LambertianBSDF {
+    reflectance: LinearColor,
+}
+
+impl LambertianBSDF {
+    pub fn new(reflectance: LinearColor) -> Self {
+        LambertianBSDF { reflectance: reflectance }
+    }
+}
+
+impl BSDF for LambertianBSDF {
+    fn f(&self, wo: &Vector3f, wi: &Vector3f) -> LinearColor {
+        if same_hemisphere(wo, wi) {
+            self.reflectance * f32::consts::FRAC_1_PI
+        } else {
+            LinearColor::new_black()
+        }
+    }
+
+    fn sample_f(&self, wo: &Vector3f, u: Vector2f) -> Option<BSDFSample> {
+        let mut wi = monte_carlo::sample_cosine_direction(u);
+        if cos_theta(wo) < 0.0 {
+            wi.z = -wi.z;
+        }
+        let pdf = self.pdf(wo, &wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some(BSDFSample {
+                 wi: wi,
+                 f: self.f(wo, &wi),
+                 pdf: pdf,
+                 specular: false,
+             })
+    }
+
+    fn pdf(&self, wo: &Vector3f, wi: &Vector3f) -> f32 {
+        if same_hemisphere(wo, wi) {
+            monte_carlo::probability_density_function_cosine_hemisphere(cos_theta(wi).abs())
+        } else {
+            0.0
+        }
+    }
+}
@@ -0,0 +1,270 @@
+/// Un matériau physique à base de microfacettes : la surface est vue comme un ensemble de petits
+/// miroirs orientés selon la distribution GGX (Trowbridge-Reitz), avec le terme d'ombrage de
+/// Smith et l'approximation de Schlick pour le coefficient de Fresnel. Le paramètre `metallic`
+/// mélange un diélectrique (une couche diffuse sous un reflet spéculaire peu intense) et un
+/// métal (un reflet spéculaire teinté par la couleur de base, sans diffusion).
+
+use std::f32;
+use color_float::LinearColor;
+use material::Material;
+use material::ShadingCoordinateSystem;
+use material::channel::Channel;
+use material::bsdf::{BSDF, BSDFSample, cos_theta, same_hemisphere, estimate_direct_lighting};
+use math::{Vector2f, Vector3f, VectorialOperations};
+use scene::World;
+use renderer::TextureRegister;
+use ray::{Fragment, Ray};
+use sampler::Sampler;
+use tools::monte_carlo;
+
+/// La réflectance en incidence normale des diélectriques courants
+const DIELECTRIC_REFLECTANCE: f32 = 0.04;
+
+/// La rugosité minimale, en dessous de laquelle la distribution devient numériquement instable
+const MIN_ALPHA: f32 = 1e-3;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MicrofacetMaterial {
+    pub base_color: Channel,
+    pub metallic: Channel,
+    pub roughness: Channel,
+}
+
+impl MicrofacetMaterial {
+    pub fn new_empty() -> MicrofacetMaterial {
+        MicrofacetMaterial {
+            base_color: Channel::Solid { color: (200u8, 200u8, 200u8).into() },
+            metallic: Channel::Value { value: 0.0 },
+            roughness: Channel::Value { value: 0.5 },
+        }
+    }
+
+}
+
+impl Material for MicrofacetMaterial {
+    fn get_color(&self,
+                 frag: &Fragment,
+                 ray: &Ray,
+                 world: &World,
+                 texture_data: Option<&TextureRegister>,
+                 sampler: &mut Sampler)
+                 -> LinearColor {
+        let bsdf = self.get_bsdf(frag, world, texture_data);
+        let frame = ShadingCoordinateSystem::new_from_normal(&frag.normal);
+        let wo = frame.world_into_local_space(&(-ray.slope() / ray.slope().norm()));
+        estimate_direct_lighting(&*bsdf,
+                                 &frame,
+                                 &wo,
+                                 &frag.position,
+                                 ray.time,
+                                 world,
+                                 sampler)
+    }
+
+    /// Évalue les canaux du matériau au point `frag` et renvoie la BSDF correspondante.
+    fn get_bsdf(&self,
+                frag: &Fragment,
+                world: &World,
+                texture_data: Option<&TextureRegister>)
+                -> Box<BSDF> {
+        Box::new(MicrofacetBSDF::new(self.base_color.color_at(frag, texture_data, world),
+                                     self.metallic.value_at(frag, texture_data, world),
+                                     self.roughness.value_at(frag, texture_data, world)))
+    }
+
+    fn get_texture_paths(&self) -> Vec<String> {
+        vec![&self.base_color, &self.metallic, &self.roughness]
+            .into_iter()
+            .filter(|chan| chan.is_texture())
+            .map(|chan| chan.get_texture_path())
+            .collect()
+    }
+}
+
+/// La BSDF d'un matériau à microfacettes, pour des valeurs données de ses canaux. Elle est
+/// définie pour `wo` du côté de la normale ; de l'autre côté, on la retourne pour que les surfaces
+/// soient éclairées des deux côtés.
+#[derive(Debug, Clone, Copy)]
+pub struct MicrofacetBSDF {
+    base_color: LinearColor,
+    metallic: f32,
+    // Le paramètre alpha de la distribution GGX, égal au carré de la rugosité
+    alpha: f32,
+}
+
+impl MicrofacetBSDF {
+    pub fn new(base_color: LinearColor, metallic: f32, roughness: f32) -> Self {
+        let metallic = f32::max(0.0, f32::min(1.0, metallic));
+        let roughness = f32::max(0.0, f32::min(1.0, roughness));
+        MicrofacetBSDF {
+            base_color: base_color,
+            metallic: metallic,
+            alpha: f32::max(MIN_ALPHA, roughness * roughness),
+        }
+    }
+
+    /// La distribution GGX des normales des microfacettes.
+    fn distribution(&self, wh: &Vector3f) -> f32 {
+        let cos2 = cos_theta(wh) * cos_theta(wh);
+        let alpha2 = self.alpha * self.alpha;
+        let denominator = cos2 * (alpha2 - 1.0) + 1.0;
+        alpha2 / (f32::consts::PI * denominator * denominator)
+    }
+
+    /// Le terme d'ombrage de Smith pour une direction.
+    fn smith_g1(&self, w: &Vector3f) -> f32 {
+        let cos2 = cos_theta(w) * cos_theta(w);
+        let tan2 = f32::max(0.0, 1.0 - cos2) / cos2;
+        2.0 / (1.0 + (1.0 + self.alpha * self.alpha * tan2).sqrt())
+    }
+
+    /// La réflectance en incidence normale : 4% pour un diélectrique, la couleur de base pour un
+    /// métal.
+    fn normal_reflectance(&self) -> LinearColor {
+        &(LinearColor::new_white() * (DIELECTRIC_REFLECTANCE * (1.0 - self.metallic))) +
+        &(self.base_color * self.metallic)
+    }
+
+    /// L'approximation de Schlick du coefficient de Fresnel.
+    fn fresnel_schlick(&self, cos_d: f32) -> LinearColor {
+        let f0 = self.normal_reflectance();
+        let weight = (1.0 - f32::max(0.0, f32::min(1.0, cos_d))).powi(5);
+        &(f0 * (1.0 - weight)) + &(LinearColor::new_white() * weight)
+    }
+
+    /// La probabilité de tirer une direction selon le lobe spéculaire plutôt que selon le lobe
+    /// diffus.
+    fn specular_probability(&self) -> f32 {
+        0.5 + 0.5 * self.metallic
+    }
+}
+
+/// Renvoie la direction symétrique de `w` par rapport au plan tangent.
+fn flip(w: &Vector3f) -> Vector3f {
+    Vector3f::new(w.x, w.y, -w.z)
+}
+
+impl BSDF for MicrofacetBSDF {
+    fn f(&self, wo: &Vector3f, wi: &Vector3f) -> LinearColor {
+        if cos_theta(wo) < 0.0 {
+            return self.f(&flip(wo), &flip(wi));
+        }
+        if !same_hemisphere(wo, wi) || cos_theta(wo) == 0.0 {
+            return LinearColor::new_black();
+        }
+        let wh = *wo + *wi;
+        let wh = wh / wh.norm();
+        let fresnel = self.fresnel_schlick(wi.dot_product_ref(&wh));
+        let specular = fresnel *
+                       (self.distribution(&wh) * self.smith_g1(wo) * self.smith_g1(wi) /
+                        (4.0 * cos_theta(wo) * cos_theta(wi)));
+        // La lumière réfléchie par la couche spéculaire n'atteint pas la couche diffuse.
+        let diffuse = self.base_color *
+                      ((1.0 - self.metallic) * (1.0 - DIELECTRIC_REFLECTANCE) *
+                       f32::consts::FRAC_1_PI);
+        &diffuse + &specular
+    }
+
+    fn sample_f(&self, wo: &Vector3f, u: Vector2f) -> Option<BSDFSample> {
+        if cos_theta(wo) < 0.0 {
+            return self.sample_f(&flip(wo), u).map(|sample| {
+                BSDFSample { wi: flip(&sample.wi), ..sample }
+            });
+        }
+        if cos_theta(wo) == 0.0 {
+            return None;
+        }
+        let specular_probability = self.specular_probability();
+        let wi = if u.x < specular_probability {
+            // On tire la normale d'une microfacette selon D(wh) * cos(wh), puis on réfléchit wo.
+            let u1 = u.x / specular_probability;
+            let cos2 = (1.0 - u1) / (1.0 + (self.alpha * self.alpha - 1.0) * u1);
+            let cos = cos2.sqrt();
+            let sin = f32::max(0.0, 1.0 - cos2).sqrt();
+            let phi = 2.0 * f32::consts::PI * u.y;
+            let wh = Vector3f::new(sin * phi.cos(), sin * phi.sin(), cos);
+            2.0 * wo.dot_product_ref(&wh) * wh - *wo
+        } else {
+            let u1 = (u.x - specular_probability) / (1.0 - specular_probability);
+            monte_carlo::sample_cosine_direction(Vector2f::new(u1, u.y))
+        };
+
+        if !same_hemisphere(wo, &wi) {
+            return None;
+        }
+        let pdf = self.pdf(wo, &wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some(BSDFSample {
+                 wi: wi,
+                 f: self.f(wo, &wi),
+                 pdf: pdf,
+                 specular: false,
+             })
+    }
+
+    fn pdf(&self, wo: &Vector3f, wi: &Vector3f) -> f32 {
+        if cos_theta(wo) < 0.0 {
+            return self.pdf(&flip(wo), &flip(wi));
+        }
+        if !same_hemisphere(wo, wi) {
+            return 0.0;
+        }
+        let wh = *wo + *wi;
+        let wh = wh / wh.norm();
+        let specular_pdf = self.distribution(&wh) * cos_theta(&wh) /
+                           (4.0 * wo.dot_product_ref(&wh));
+        let diffuse_pdf = monte_carlo::probability_density_function_cosine_hemisphere(
+            cos_theta(wi));
+        let specular_probability = self.specular_probability();
+        specular_probability * specular_pdf + (1.0 - specular_probability) * diffuse_pdf
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use color_float::{LinearColor, Color};
+    use material::bsdf::BSDF;
+    use math::{Vector2f, Vector3f};
+    use super::MicrofacetBSDF;
+
+    // Vérifie, pour une direction wo donnée, que la densité renvoyée par sample_f est celle de
+    // pdf, et que l'estimateur de l'albédo f * cos / pdf reste inférieur à 1 (conservation de
+    // l'énergie).
+    fn check_sampling(bsdf: &MicrofacetBSDF, wo: &Vector3f) {
+        let n = 64;
+        let mut albedo = 0.0;
+        for i in 0..n {
+            for j in 0..n {
+                let u = Vector2f::new((i as f32 + 0.5) / n as f32, (j as f32 + 0.5) / n as f32);
+                if let Some(sample) = bsdf.sample_f(wo, u) {
+                    let pdf = bsdf.pdf(wo, &sample.wi);
+                    assert!((sample.pdf - pdf).abs() <= 1e-3 * pdf);
+                    albedo += sample.f.get_internal_color().g() * sample.wi.z / sample.pdf;
+                }
+            }
+        }
+        albedo /= (n * n) as f32;
+        assert!(albedo > 0.0 && albedo < 1.01, "albedo = {}", albedo);
+    }
+
+    #[test]
+    fn test_microfacet_sampling() {
+        let white = LinearColor::new_white();
+        let wo = Vector3f::new(0.6, 0.0, 0.8);
+        for &(metallic, roughness) in &[(0.0, 0.5), (1.0, 0.3), (1.0, 1.0), (0.5, 0.8)] {
+            check_sampling(&MicrofacetBSDF::new(white, metallic, roughness), &wo);
+        }
+    }
+
+    #[test]
+    fn test_microfacet_reciprocity() {
+        let bsdf = MicrofacetBSDF::new(LinearColor::new_white(), 0.3, 0.4);
+        let wo = Vector3f::new(0.6, 0.0, 0.8);
+        let wi = Vector3f::new(-0.28, 0.96, 0.0) * 0.6 + Vector3f::new(0.0, 0.0, 0.8);
+        let a = bsdf.f(&wo, &wi).get_internal_color().r();
+        let b = bsdf.f(&wi, &wo).get_internal_color().r();
+        assert!((a - b).abs() < 1e-5);
+    }
+}
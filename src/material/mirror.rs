@@ -0,0 +1,101 @@
+use color_float::LinearColor;
+use material::channel::Channel;
+use material::{Material, specular_lookup};
+use material::bsdf::{BSDF, BSDFSample, abs_cos_theta};
+use math::{Vector2f, Vector3f, VectorialOperations};
+use integrator::{facing_normal, RAY_EPSILON};
+use tools::reflect;
+use scene::World;
+use renderer::TextureRegister;
+use ray::{Fragment, Ray};
+use sampler::Sampler;
+
+/// Un miroir parfait, dont la couleur teinte la lumière réfléchie.
+#[derive(Serialize,Deserialize,Debug,Clone)]
+pub struct MirrorMaterial {
+    pub color: Channel,
+}
+
+impl MirrorMaterial {
+    pub fn new_empty() -> MirrorMaterial {
+        MirrorMaterial { color: Channel::Solid { color: (255u8, 255u8, 255u8).into() } }
+    }
+}
+
+impl Material for MirrorMaterial {
+    // Un miroir n'a pas de couleur propre : tout ce qu'on y voit vient de la réflexion. Sans
+    // intégrateur qui suive les rebonds, on l'approche par un seul rayon réfléchi (voir
+    // `specular_lookup`).
+    fn get_color(&self,
+                 frag: &Fragment,
+                 ray: &Ray,
+                 world: &World,
+                 texture_data: Option<&TextureRegister>,
+                 sampler: &mut Sampler)
+                 -> LinearColor {
+        let direction = ray.slope() / ray.slope().norm();
+        let normal = facing_normal(frag, ray);
+        let mut reflected_ray = Ray::new(frag.position + normal * RAY_EPSILON,
+                                         reflect(&direction, &normal));
+        reflected_ray.time = ray.time;
+        self.color.color_at(frag, texture_data, world) *
+        specular_lookup(&reflected_ray, world, texture_data, sampler)
+    }
+
+    fn get_bsdf(&self,
+                frag: &Fragment,
+                world: &World,
+                texture_data: Option<&TextureRegister>)
+                -> Box<BSDF> {
+        Box::new(SpecularReflectionBSDF { color: self.color.color_at(frag, texture_data, world) })
+    }
+
+    fn get_texture_paths(&self) -> Vec<String> {
+        if self.color.is_texture() {
+            vec![self.color.get_texture_path()]
+        } else {
+            vec![]
+        }
+    }
+
+    fn reflectivity(&self) -> f32 {
+        1.0
+    }
+
+    fn reflection_color(&self,
+                        frag: &Fragment,
+                        world: &World,
+                        texture_data: Option<&TextureRegister>)
+                        -> LinearColor {
+        self.color.color_at(frag, texture_data, world)
+    }
+}
+
+/// La réflexion spéculaire parfaite : une distribution de Dirac dans la direction miroir, qui ne
+/// peut qu'être tirée.
+struct SpecularReflectionBSDF {
+    color: LinearColor,
+}
+
+impl BSDF for SpecularReflectionBSDF {
+    fn f(&self, _: &Vector3f, _: &Vector3f) -> LinearColor {
+        LinearColor::new_black()
+    }
+
+    fn sample_f(&self, wo: &Vector3f, _: Vector2f) -> Option<BSDFSample> {
+        let wi = Vector3f::new(-wo.x, -wo.y, wo.z);
+        if abs_cos_theta(&wi) == 0.0 {
+            return None;
+        }
+        Some(BSDFSample {
+                 wi: wi,
+                 f: self.color / abs_cos_theta(&wi),
+                 pdf: 1.0,
+                 specular: true,
+             })
+    }
+
+    fn pdf(&self, _: &Vector3f, _: &Vector3f) -> f32 {
+        0.0
+    }
+}
@@ -3,19 +3,206 @@ use scene::World;
 use color_float::LinearColor;
 use renderer::TextureRegister;
 use math::{VectorialOperations, Vector3f};
+use material::bsdf::BSDF;
+use material::unlit::UnlitMaterial;
+use material::lambert::LambertMaterial;
+use material::phong::PhongMaterial;
+use material::flat_material::FlatMaterial;
+use material::mirror::MirrorMaterial;
+use material::glass::GlassMaterial;
+use material::microfacet::MicrofacetMaterial;
+use material::emissive::EmissiveMaterial;
+use io_utils;
+use serde_json;
+use integrator::RAY_EPSILON;
+use sampler::Sampler;
 
 pub mod channel;
-pub mod flat_material;
 pub mod bsdf;
+pub mod flat_material;
+pub mod unlit;
+pub mod lambert;
+pub mod phong;
+pub mod mirror;
+pub mod glass;
+pub mod microfacet;
+pub mod emissive;
 pub mod ambient_occlusion;
 
 pub trait Material {
+    /// La couleur du matériau vue depuis le rayon `ray`, éclairé directement par les lumières.
+    /// C'est ce qu'affichent les intégrateurs direct et de Whitted.
     fn get_color(&self,
                  frag: &Fragment,
                  ray: &Ray,
                  world: &World,
-                 texture_data: Option<&TextureRegister>)
+                 texture_data: Option<&TextureRegister>,
+                 sampler: &mut Sampler)
                  -> LinearColor;
+
+    /// Renvoie la BSDF du matériau au point `frag`, utilisée par les intégrateurs qui suivent
+    /// les rebonds de la lumière sur les surfaces.
+    fn get_bsdf(&self,
+                frag: &Fragment,
+                world: &World,
+                texture_data: Option<&TextureRegister>)
+                -> Box<BSDF>;
+
+    /// La radiance émise par la surface au point `frag`.
+    fn get_emission(&self,
+                    _: &Fragment,
+                    _: &World,
+                    _: Option<&TextureRegister>)
+                    -> LinearColor {
+        LinearColor::new_black()
+    }
+
+    /// La radiance émise par le matériau, si elle est la même en tout point de la surface. Les
+    /// objets dont le matériau en a une sont utilisés comme lumières surfaciques.
+    fn uniform_emission(&self) -> Option<LinearColor> {
+        None
+    }
+
+    /// Les chemins des textures utilisées par le matériau, pour les charger avant le rendu.
+    fn get_texture_paths(&self) -> Vec<String>;
+
+    /// La proportion de lumière réfléchie de manière spéculaire (miroir).
+    fn reflectivity(&self) -> f32 {
+        0.0
+    }
+
+    /// La proportion de lumière transmise (réfractée) à travers la surface.
+    fn transmissivity(&self) -> f32 {
+        0.0
+    }
+
+    /// La couleur qui teinte la lumière réfléchie de manière spéculaire au point `frag`.
+    fn reflection_color(&self,
+                        _: &Fragment,
+                        _: &World,
+                        _: Option<&TextureRegister>)
+                        -> LinearColor {
+        LinearColor::new_white()
+    }
+
+    /// La couleur qui teinte la lumière transmise à travers la surface au point `frag`.
+    fn transmission_color(&self,
+                          _: &Fragment,
+                          _: &World,
+                          _: Option<&TextureRegister>)
+                          -> LinearColor {
+        LinearColor::new_white()
+    }
+
+    /// L'indice de réfraction du matériau.
+    fn index_of_refraction(&self) -> f32 {
+        1.0
+    }
+}
+
+/// Les différents matériaux que peut utiliser un objet. Dans un fichier de matériau, le type est
+/// donné par le nom de la variante, par exemple :
+/// `{"Lambert": {"diffuse": {"color": {"r": 200, "g": 200, "b": 200}}}}`
+/// Les anciens fichiers, qui ne précisent pas de type, sont lus comme des matériaux de Phong, de
+/// même que ceux de type `Flat`, l'ancien nom du matériau de Phong.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum MaterialObject {
+    Flat(FlatMaterial),
+    Unlit(UnlitMaterial),
+    Lambert(LambertMaterial),
+    Phong(PhongMaterial),
+    Mirror(MirrorMaterial),
+    Glass(GlassMaterial),
+    Microfacet(MicrofacetMaterial),
+    Emissive(EmissiveMaterial),
+}
+
+impl MaterialObject {
+    pub fn new_empty() -> MaterialObject {
+        MaterialObject::Phong(PhongMaterial::new_empty())
+    }
+
+    pub fn as_trait(&self) -> &Material {
+        match *self {
+            MaterialObject::Flat(ref material) => material,
+            MaterialObject::Unlit(ref material) => material,
+            MaterialObject::Lambert(ref material) => material,
+            MaterialObject::Phong(ref material) => material,
+            MaterialObject::Mirror(ref material) => material,
+            MaterialObject::Glass(ref material) => material,
+            MaterialObject::Microfacet(ref material) => material,
+            MaterialObject::Emissive(ref material) => material,
+        }
+    }
+
+    pub fn read_from_file(pathname: &str) -> Result<MaterialObject, String> {
+        match io_utils::open_file_as_string(pathname) {
+            Ok(file_str) => {
+                match serde_json::from_str(file_str.as_str()) {
+                    Ok(val) => Ok(val),
+                    // Ancien format, sans type de matériau
+                    Err(e) => {
+                        match serde_json::from_str(file_str.as_str()) {
+                            Ok(val) => Ok(MaterialObject::Phong(val)),
+                            Err(_) => Err(e.to_string()),
+                        }
+                    }
+                }
+            }
+            Err(e) => Err(e.to_string()), // TODO personaliser les messages d'erreur
+        }
+    }
+
+    pub fn save_to_file(&self, path: &str) {
+        io_utils::write_string_to_file(&serde_json::to_string_pretty(&self).unwrap(), path)
+            .expect("Could not save material");
+    }
+}
+
+/// Le nombre maximal de surfaces transparentes traversées par `specular_lookup`.
+const MAX_SPECULAR_LOOKUPS: u32 = 4;
+
+/// Une approximation de la lumière qui arrive le long de `ray`, pour que les miroirs et le verre
+/// ne soient pas noirs avec les intégrateurs qui ne suivent pas les rebonds : c'est la couleur de
+/// la première surface touchée qui n'est ni un miroir ni transparente, ou la radiance de
+/// l'environnement (noire s'il n'y en a pas). Les surfaces transparentes sont traversées en ligne
+/// droite, et un miroir arrête la recherche.
+pub fn specular_lookup(ray: &Ray,
+                       world: &World,
+                       texture_data: Option<&TextureRegister>,
+                       sampler: &mut Sampler)
+                       -> LinearColor {
+    let mut ray = *ray;
+    for _ in 0..MAX_SPECULAR_LOOKUPS {
+        let (position, direction) = {
+            let intersection = match world.get_intersection_point(&mut ray) {
+                Some(intersection) => intersection,
+                None => {
+                    return match world.environment() {
+                               Some(environment) => environment.radiance(&ray.slope()),
+                               None => LinearColor::new_black(),
+                           }
+                }
+            };
+            let material = intersection.material();
+            if material.reflectivity() >= 1.0 {
+                return LinearColor::new_black();
+            }
+            if material.transmissivity() < 1.0 {
+                return material.get_color(intersection.fragment(),
+                                          intersection.ray(),
+                                          world,
+                                          texture_data,
+                                          sampler);
+            }
+            let direction = ray.slope() / ray.slope().norm();
+            (intersection.fragment().position, direction)
+        };
+        let time = ray.time;
+        ray = Ray::new(position + direction * RAY_EPSILON, direction);
+        ray.time = time;
+    }
+    LinearColor::new_black()
 }
 
 /// Une structure de données qui contiens les méthodes permettant de passer d'un système de
@@ -24,6 +211,7 @@ pub trait Material {
 /// `t` - un vecteur tangent à la surface
 /// `s` - un autre vecteur tangent à la surface tel que `s` soit orthogonal à `t`
 /// Ce système de coordonnées est le système de coordonnées de réflexion locale.
+#[derive(Debug, Clone, Copy)]
 pub struct ShadingCoordinateSystem {
     n: Vector3f,
     t: Vector3f,
@@ -41,6 +229,22 @@ impl ShadingCoordinateSystem {
         }
     }
 
+    /// Crée un système de coordonnées orthonormé dont l'axe z est la normale `normal` (qui n'a
+    /// pas besoin d'être normalisée).
+    pub fn new_from_normal(normal: &Vector3f) -> Self {
+        let n = *normal / normal.norm_ref();
+        // On choisit un vecteur qui n'est pas colinéaire à la normale pour construire la base.
+        let other = if n.x.abs() > 0.9 {
+            Vector3f::new(0.0, 1.0, 0.0)
+        } else {
+            Vector3f::new(1.0, 0.0, 0.0)
+        };
+        let s = other.cross_product_ref(&n);
+        let s = s / s.norm();
+        let t = n.cross_product_ref(&s);
+        ShadingCoordinateSystem { n: n, t: t, s: s }
+    }
+
     /// Permet de transformer un vecteur exprimé dans le repère cartésien du monde, en un vecteur
     /// exprimé dans le repère `self`.
     pub fn world_into_local_space(&self, u: &Vector3f) -> Vector3f {
@@ -54,6 +258,24 @@ impl ShadingCoordinateSystem {
     pub fn local_into_world_space(&self, u: &Vector3f) -> Vector3f {
         Vector3f::new(self.s.x * u.x + self.t.x * u.y + self.n.x * u.z,
                       self.s.y * u.x + self.t.y * u.y + self.n.y * u.z,
-                      self.s.z * u.z + self.t.z * u.y + self.n.z * u.z)
+                      self.s.z * u.x + self.t.z * u.y + self.n.z * u.z)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json;
+    use super::MaterialObject;
+
+    #[test]
+    fn test_flat_material_tag() {
+        let json = r#"{"Flat": {"diffuse": {"color": {"r": 200, "g": 200, "b": 200}},
+                                "specular": {"color": {"r": 255, "g": 255, "b": 255}},
+                                "ambient": {"color": {"r": 0, "g": 0, "b": 0}}}}"#;
+        let material: MaterialObject = serde_json::from_str(json).unwrap();
+        match material {
+            MaterialObject::Flat(ref flat) => assert_eq!(flat.shininess, 32.0),
+            _ => panic!("the Flat tag should load a Phong material"),
+        }
     }
 }
@@ -0,0 +1,269 @@
+use color_float::{LinearColor, Color};
+use material::channel::Channel;
+use material::{Material, ShadingCoordinateSystem};
+use material::bsdf::{BSDF, BSDFSample, cos_theta, same_hemisphere, estimate_direct_lighting};
+use scene::World;
+use renderer::TextureRegister;
+use ray::{Fragment, Ray};
+use sampler::Sampler;
+use math::{Vector2f, Vector3f, VectorialOperations};
+use tools::monte_carlo;
+use std::f32;
+
+#[derive(Serialize,Deserialize,Debug,Clone)]
+pub struct PhongMaterial {
+    pub diffuse: Channel,
+    pub specular: Channel,
+    pub ambient: Channel,
+
+    // L'exposant de Blinn-Phong : plus il est grand, plus les reflets spéculaires sont petits
+    #[serde(default = "default_shininess")]
+    pub shininess: f32,
+
+    // La proportion de lumière réfléchie comme par un miroir
+    #[serde(default)]
+    pub reflectivity: f32,
+
+    // La proportion de lumière transmise à travers la surface
+    #[serde(default)]
+    pub transmissivity: f32,
+
+    // L'indice de réfraction du matériau, utilisé pour la transmission et le coefficient de
+    // Fresnel
+    #[serde(default = "default_index_of_refraction")]
+    pub index_of_refraction: f32,
+}
+
+// La part de la couleur ambiante ajoutée partout : le terme ambiant vaut
+// `ambient * diffuse * AMBIENT_FACTOR`. Il imite grossièrement la lumière indirecte, que le
+// modèle de Phong ne calcule pas, sans écraser l'éclairage direct quand `ambient` est blanc.
+const AMBIENT_FACTOR: f32 = 0.1;
+
+fn default_shininess() -> f32 {
+    32.0
+}
+
+fn default_index_of_refraction() -> f32 {
+    1.5
+}
+
+impl PhongMaterial {
+    pub fn new_empty() -> PhongMaterial {
+        PhongMaterial {
+            diffuse: Channel::Solid { color: (200u8, 200u8, 200u8).into() },
+            specular: Channel::Solid { color: (255u8, 255u8, 255u8).into() },
+            ambient: Channel::Solid { color: (0u8, 0u8, 0u8).into() },
+            shininess: default_shininess(),
+            reflectivity: 0.0,
+            transmissivity: 0.0,
+            index_of_refraction: default_index_of_refraction(),
+        }
+    }
+
+    fn channels(&self) -> Vec<&Channel> {
+        vec![&self.diffuse, &self.specular, &self.ambient]
+    }
+
+}
+
+
+impl Material for PhongMaterial {
+    // Un terme ambiant (la couleur ambiante modulée par la couleur diffuse, voir
+    // `AMBIENT_FACTOR`), plus l'éclairage direct estimé avec la BSDF de Blinn-Phong selon la
+    // stratégie du monde.
+    fn get_color(&self,
+                 frag: &Fragment,
+                 ray: &Ray,
+                 world: &World,
+                 texture_data: Option<&TextureRegister>,
+                 sampler: &mut Sampler)
+                 -> LinearColor {
+
+        let diffuse = self.diffuse.color_at(frag, texture_data, world);
+        let ambient = self.ambient.color_at(frag, texture_data, world) * diffuse *
+                      AMBIENT_FACTOR;
+
+        // Sans lumière, on affiche simplement la couleur diffuse.
+        if world.light_count() == 0 {
+            return &ambient + &diffuse;
+        }
+
+        let bsdf = PhongBSDF::new(diffuse,
+                                  self.specular.color_at(frag, texture_data, world),
+                                  self.shininess);
+        let frame = ShadingCoordinateSystem::new_from_normal(&frag.normal);
+        let wo = frame.world_into_local_space(&(-ray.slope() / ray.slope().norm()));
+        &ambient +
+        &estimate_direct_lighting(&bsdf, &frame, &wo, &frag.position, ray.time, world, sampler)
+    }
+
+    fn get_bsdf(&self,
+                frag: &Fragment,
+                world: &World,
+                texture_data: Option<&TextureRegister>)
+                -> Box<BSDF> {
+        Box::new(PhongBSDF::new(self.diffuse.color_at(frag, texture_data, world),
+                                self.specular.color_at(frag, texture_data, world),
+                                self.shininess))
+    }
+
+    fn get_texture_paths(&self) -> Vec<String> {
+        let mut result: Vec<String> = vec![];
+        for chan in &self.channels() {
+            if chan.is_texture() {
+                result.push(chan.get_texture_path());
+            }
+
+        }
+        result
+    }
+
+    fn reflectivity(&self) -> f32 {
+        self.reflectivity
+    }
+
+    fn transmissivity(&self) -> f32 {
+        self.transmissivity
+    }
+
+    fn index_of_refraction(&self) -> f32 {
+        self.index_of_refraction
+    }
+}
+
+/// La BSDF de Blinn-Phong normalisée : un lobe diffus et un lobe spéculaire centré sur la
+/// direction miroir, dont le facteur (s + 8) / 8π conserve à peu près l'énergie réfléchie quel
+/// que soit l'exposant. Le terme ambiant, qui n'a pas de sens physique, est ignoré.
+struct PhongBSDF {
+    diffuse: LinearColor,
+    specular: LinearColor,
+    shininess: f32,
+    // La probabilité de tirer une direction selon le lobe spéculaire
+    specular_probability: f32,
+}
+
+impl PhongBSDF {
+    fn new(diffuse: LinearColor, specular: LinearColor, shininess: f32) -> Self {
+        // Pour ne pas créer d'énergie, la somme des deux réflectances ne doit pas dépasser 1.
+        let total = &diffuse + &specular;
+        let total = total.get_internal_color();
+        let max_total = f32::max(total.r(), f32::max(total.g(), total.b()));
+        let (diffuse, specular) = if max_total > 1.0 {
+            (diffuse / max_total, specular / max_total)
+        } else {
+            (diffuse, specular)
+        };
+
+        let diffuse_weight = luminance(&diffuse);
+        let specular_weight = luminance(&specular);
+        let specular_probability = if diffuse_weight + specular_weight > 0.0 {
+            specular_weight / (diffuse_weight + specular_weight)
+        } else {
+            0.0
+        };
+        PhongBSDF {
+            diffuse: diffuse,
+            specular: specular,
+            shininess: f32::max(0.0, shininess),
+            specular_probability: specular_probability,
+        }
+    }
+}
+
+// La moyenne des composantes d'une couleur.
+fn luminance(color: &LinearColor) -> f32 {
+    let color = color.get_internal_color();
+    (color.r() + color.g() + color.b()) / 3.0
+}
+
+impl BSDF for PhongBSDF {
+    fn f(&self, wo: &Vector3f, wi: &Vector3f) -> LinearColor {
+        if !same_hemisphere(wo, wi) {
+            return LinearColor::new_black();
+        }
+        let wh = *wo + *wi;
+        let wh = wh / wh.norm();
+        let specular_factor = (self.shininess + 8.0) / (8.0 * f32::consts::PI) *
+                              cos_theta(&wh).abs().powf(self.shininess);
+        &(self.diffuse * f32::consts::FRAC_1_PI) + &(self.specular * specular_factor)
+    }
+
+    fn sample_f(&self, wo: &Vector3f, u: Vector2f) -> Option<BSDFSample> {
+        // On tire la direction comme si wo était du côté de la normale, puis on la retourne si
+        // besoin.
+        let flipped = cos_theta(wo) < 0.0;
+        let wo_up = Vector3f::new(wo.x, wo.y, wo.z.abs());
+        let mut wi = if u.x < self.specular_probability {
+            // On tire le vecteur médian selon cos^s, puis on réfléchit wo.
+            let u1 = u.x / self.specular_probability;
+            let cos = u1.powf(1.0 / (self.shininess + 1.0));
+            let sin = f32::max(0.0, 1.0 - cos * cos).sqrt();
+            let phi = 2.0 * f32::consts::PI * u.y;
+            let wh = Vector3f::new(sin * phi.cos(), sin * phi.sin(), cos);
+            2.0 * wo_up.dot_product_ref(&wh) * wh - wo_up
+        } else {
+            let u1 = (u.x - self.specular_probability) / (1.0 - self.specular_probability);
+            monte_carlo::sample_cosine_direction(Vector2f::new(u1, u.y))
+        };
+        if flipped {
+            wi.z = -wi.z;
+        }
+        let pdf = self.pdf(wo, &wi);
+        if pdf <= 0.0 {
+            return None;
+        }
+        Some(BSDFSample {
+                 wi: wi,
+                 f: self.f(wo, &wi),
+                 pdf: pdf,
+                 specular: false,
+             })
+    }
+
+    fn pdf(&self, wo: &Vector3f, wi: &Vector3f) -> f32 {
+        if !same_hemisphere(wo, wi) {
+            return 0.0;
+        }
+        let wh = *wo + *wi;
+        let wh = wh / wh.norm();
+        let specular_pdf = (self.shininess + 1.0) / (2.0 * f32::consts::PI) *
+                           cos_theta(&wh).abs().powf(self.shininess) /
+                           (4.0 * wo.dot_product_ref(&wh).abs());
+        let diffuse_pdf =
+            monte_carlo::probability_density_function_cosine_hemisphere(cos_theta(wi).abs());
+        self.specular_probability * specular_pdf + (1.0 - self.specular_probability) * diffuse_pdf
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use color_float::{LinearColor, Color};
+    use material::bsdf::BSDF;
+    use math::{Vector2f, Vector3f};
+    use super::PhongBSDF;
+
+    // Vérifie que la densité renvoyée par sample_f est celle de pdf, et que l'estimateur de
+    // l'albédo f * cos / pdf reste inférieur à 1, même pour des réflectances trop grandes.
+    #[test]
+    fn test_phong_sampling() {
+        let bsdf = PhongBSDF::new(LinearColor::new_white(), LinearColor::new_white(), 32.0);
+        for wo in &[Vector3f::new(0.6, 0.0, 0.8), Vector3f::new(0.0, -0.6, -0.8)] {
+            let n = 64;
+            let mut albedo = 0.0;
+            for i in 0..n {
+                for j in 0..n {
+                    let u = Vector2f::new((i as f32 + 0.5) / n as f32,
+                                          (j as f32 + 0.5) / n as f32);
+                    if let Some(sample) = bsdf.sample_f(wo, u) {
+                        let pdf = bsdf.pdf(wo, &sample.wi);
+                        assert!((sample.pdf - pdf).abs() <= 1e-3 * pdf);
+                        albedo += sample.f.get_internal_color().g() * sample.wi.z.abs() /
+                                  sample.pdf;
+                    }
+                }
+            }
+            albedo /= (n * n) as f32;
+            assert!(albedo > 0.5 && albedo < 1.01, "albedo = {}", albedo);
+        }
+    }
+}
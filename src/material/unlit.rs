@@ -0,0 +1,54 @@
+use color_float::LinearColor;
+use material::channel::Channel;
+use material::Material;
+use material::bsdf::{BSDF, BlackBSDF};
+use scene::World;
+use renderer::TextureRegister;
+use ray::{Fragment, Ray};
+use sampler::Sampler;
+
+/// Un matériau qui n'est pas éclairé : il a toujours la couleur de son canal, quelles que soient
+/// les lumières. Pour les intégrateurs qui suivent les rebonds de la lumière, il se comporte comme
+/// une source de lumière qui ne réfléchit rien.
+#[derive(Serialize,Deserialize,Debug,Clone)]
+pub struct UnlitMaterial {
+    pub color: Channel,
+}
+
+impl UnlitMaterial {
+    pub fn new_empty() -> UnlitMaterial {
+        UnlitMaterial { color: Channel::Solid { color: (200u8, 200u8, 200u8).into() } }
+    }
+}
+
+impl Material for UnlitMaterial {
+    fn get_color(&self,
+                 frag: &Fragment,
+                 _: &Ray,
+                 world: &World,
+                 texture_data: Option<&TextureRegister>,
+                 _: &mut Sampler)
+                 -> LinearColor {
+        self.color.color_at(frag, texture_data, world)
+    }
+
+    fn get_bsdf(&self, _: &Fragment, _: &World, _: Option<&TextureRegister>) -> Box<BSDF> {
+        Box::new(BlackBSDF)
+    }
+
+    fn get_emission(&self,
+                    frag: &Fragment,
+                    world: &World,
+                    texture_data: Option<&TextureRegister>)
+                    -> LinearColor {
+        self.color.color_at(frag, texture_data, world)
+    }
+
+    fn get_texture_paths(&self) -> Vec<String> {
+        if self.color.is_texture() {
+            vec![self.color.get_texture_path()]
+        } else {
+            vec![]
+        }
+    }
+}
@@ -74,6 +74,7 @@ impl<T> fmt::Display for Vector2<T>
 // One basic aliases for implementation convenience in other module.
 pub type Vector3f = Vector3<f32>;
 pub type Vector2f = Vector2<f32>;
+pub type Matrix3f = Matrix3<f32>;
 
 impl Vector3f {
     pub fn zero() -> Self {
@@ -81,6 +82,102 @@ impl Vector3f {
     }
 }
 
+impl Matrix3f {
+    // Creates a matrix from its rows.
+    pub fn new(data: [[f32; 3]; 3]) -> Self {
+        Matrix3 { data: data }
+    }
+
+    pub fn identity() -> Self {
+        Matrix3f::new([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]])
+    }
+
+    // A diagonal matrix that scales each axis by the matching component of `scale`.
+    pub fn scale(scale: &Vector3f) -> Self {
+        Matrix3f::new([[scale.x, 0.0, 0.0], [0.0, scale.y, 0.0], [0.0, 0.0, scale.z]])
+    }
+
+    // The rotation of `angle` radians around the unit vector `u`.
+    // Formula from Wikipedia :
+    // https://en.wikipedia.org/wiki/Rotation_matrix#Rotation_matrix_from_axis_and_angle
+    pub fn rotation(u: &Vector3f, angle: f32) -> Self {
+        let c = angle.cos();
+        let mc = 1.0 - c;
+        let s = angle.sin();
+
+        let uxy = u.x * u.y;
+        let uyz = u.y * u.z;
+        let uzx = u.z * u.x;
+
+        Matrix3f::new([[u.x * u.x * mc + c, uxy * mc - u.z * s, uzx * mc + u.y * s],
+                       [uxy * mc + u.z * s, u.y * u.y * mc + c, uyz * mc - u.x * s],
+                       [uzx * mc - u.y * s, uyz * mc + u.x * s, u.z * u.z * mc + c]])
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> f32 {
+        self.data[row][col]
+    }
+
+    pub fn transpose(&self) -> Self {
+        let d = &self.data;
+        Matrix3f::new([[d[0][0], d[1][0], d[2][0]],
+                       [d[0][1], d[1][1], d[2][1]],
+                       [d[0][2], d[1][2], d[2][2]]])
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let d = &self.data;
+        d[0][0] * (d[1][1] * d[2][2] - d[1][2] * d[2][1]) -
+        d[0][1] * (d[1][0] * d[2][2] - d[1][2] * d[2][0]) +
+        d[0][2] * (d[1][0] * d[2][1] - d[1][1] * d[2][0])
+    }
+
+    // The inverse of the matrix, computed from the adjugate. Returns None if the matrix is
+    // singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det.abs() < f32::EPSILON * f32::EPSILON {
+            return None;
+        }
+        let d = &self.data;
+        let inv_det = 1.0 / det;
+        Some(Matrix3f::new([[(d[1][1] * d[2][2] - d[1][2] * d[2][1]) * inv_det,
+                             (d[0][2] * d[2][1] - d[0][1] * d[2][2]) * inv_det,
+                             (d[0][1] * d[1][2] - d[0][2] * d[1][1]) * inv_det],
+                            [(d[1][2] * d[2][0] - d[1][0] * d[2][2]) * inv_det,
+                             (d[0][0] * d[2][2] - d[0][2] * d[2][0]) * inv_det,
+                             (d[0][2] * d[1][0] - d[0][0] * d[1][2]) * inv_det],
+                            [(d[1][0] * d[2][1] - d[1][1] * d[2][0]) * inv_det,
+                             (d[0][1] * d[2][0] - d[0][0] * d[2][1]) * inv_det,
+                             (d[0][0] * d[1][1] - d[0][1] * d[1][0]) * inv_det]]))
+    }
+}
+
+impl Mul<Vector3f> for Matrix3f {
+    type Output = Vector3f;
+    fn mul(self, v: Vector3f) -> Vector3f {
+        let d = &self.data;
+        Vector3f::new(d[0][0] * v.x + d[0][1] * v.y + d[0][2] * v.z,
+                      d[1][0] * v.x + d[1][1] * v.y + d[1][2] * v.z,
+                      d[2][0] * v.x + d[2][1] * v.y + d[2][2] * v.z)
+    }
+}
+
+impl Mul<Matrix3f> for Matrix3f {
+    type Output = Matrix3f;
+    fn mul(self, other: Matrix3f) -> Matrix3f {
+        let mut data = [[0f32; 3]; 3];
+        for (row, line) in data.iter_mut().enumerate() {
+            for (col, value) in line.iter_mut().enumerate() {
+                *value = self.data[row][0] * other.data[0][col] +
+                         self.data[row][1] * other.data[1][col] +
+                         self.data[row][2] * other.data[2][col];
+            }
+        }
+        Matrix3f::new(data)
+    }
+}
+
 impl AlmostEq<f32> for Vector3<f32> {
     fn equal_with_threshold(&self, other: &Self, threshold: Option<f32>) -> bool {
         let new_threshold = match threshold {
@@ -733,6 +830,25 @@ mod tests {
         assert_eq!(v2.dot_product_ref(&v3), -9_f32);
         assert_eq!(v1.dot_product_ref(&v1), 3_f32);
     }
+    #[test]
+    fn test_matrix_inverse() {
+        let m = Matrix3f::rotation(&Vector3f::new(0.0, 0.0, 1.0), 0.7) *
+                Matrix3f::scale(&Vector3f::new(2.0, 3.0, 0.5));
+        let inv = m.inverse().unwrap();
+        let v = Vector3f::new(1.5, -2.0, 4.0);
+        assert!((inv * (m * v)).equal_with_threshold(&v, Some(0.0001)));
+        assert!(((m * inv).get(1, 1) - 1.0).abs() < 0.0001);
+        assert!(Matrix3f::scale(&Vector3f::new(1.0, 0.0, 1.0)).inverse().is_none());
+    }
+
+    #[test]
+    fn test_matrix_rotation() {
+        // Un quart de tour autour de z envoie x sur y
+        let m = Matrix3f::rotation(&Vector3f::new(0.0, 0.0, 1.0), f32::consts::FRAC_PI_2);
+        let v = m * Vector3f::new(1.0, 0.0, 0.0);
+        assert!(v.equal_with_threshold(&Vector3f::new(0.0, 1.0, 0.0), Some(0.0001)));
+    }
+
     #[test]
     fn test_almot_eq_vec3() {
         let v1 = Vector3::new(0_f32, 0_f32, 0_f32);
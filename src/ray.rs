@@ -2,9 +2,11 @@ use math::{Vector3f, Vector2f};
 use math::VectorialOperations;
 use geometry::obj3d::Mesh;
 use material::Material;
+use material::bsdf::BSDF;
 use scene::World;
 use color_float::LinearColor;
 use renderer::TextureRegister;
+use sampler::Sampler;
 use std::f32;
 
 /** Represente un point d'intresection entre un rayon et de la géometrie */
@@ -17,11 +19,11 @@ pub struct Intersection<'a> {
 
 impl<'a> Intersection<'a> {
     /** Un peu de magie sur les lifetime pour que le compilo comprenne ce qu'il se passe*/
-    pub fn new<'b: 'a, T: Material>(frag: Fragment,
-                                    ray: Ray,
-                                    geo: &'b Mesh,
-                                    mat: &'b T)
-                                    -> Intersection<'a> {
+    pub fn new<'b: 'a>(frag: Fragment,
+                       ray: Ray,
+                       geo: &'b Mesh,
+                       mat: &'b Material)
+                       -> Intersection<'a> {
         Intersection {
             fragment: frag,
             geometry: geo,
@@ -30,21 +32,43 @@ impl<'a> Intersection<'a> {
         }
     }
 
+    pub fn fragment(&self) -> &Fragment {
+        &self.fragment
+    }
+
+    pub fn ray(&self) -> &Ray {
+        &self.ray
+    }
+
+    pub fn material(&self) -> &Material {
+        self.material
+    }
+
+    /// Renvoie la BSDF du matériau au point d'intersection.
+    pub fn get_bsdf(&self, world: &World, texture_register: &TextureRegister) -> Box<BSDF> {
+        self.material
+            .get_bsdf(&self.fragment, world, Some(texture_register))
+    }
+
+    /// Renvoie la radiance émise par le matériau au point d'intersection.
+    pub fn get_emission(&self, world: &World, texture_register: &TextureRegister) -> LinearColor {
+        self.material
+            .get_emission(&self.fragment, world, Some(texture_register))
+    }
+
     pub fn get_point_color(&self,
                            world: &World,
-                           texture_register: &TextureRegister)
+                           texture_register: &TextureRegister,
+                           sampler: &mut Sampler)
                            -> LinearColor {
-        // TODO à simplifier (tout en gardant la gestion des cas anormaux)
-        match self.fragment.tex {
-            Some(_) => {
-                self.material
-                    .get_color(&self.fragment, &self.ray, world, Some(texture_register))
-            }
-            None => {
-                self.material
-                    .get_color(&self.fragment, &self.ray, world, None)
-            }
-        }
+        // Les canaux des matériaux n'utilisent les textures que si le fragment a des coordonnées
+        // de texture (voir `Channel::color_at`).
+        self.material
+            .get_color(&self.fragment,
+                       &self.ray,
+                       world,
+                       Some(texture_register),
+                       sampler)
     }
 }
 
@@ -57,6 +81,9 @@ pub struct Ray {
     // Un paramètre qui indique l'extrémité du rayon. Par exemple, lorsque le rayon est arrêté
     // par une surface il ne se propage pas sur les surfaces situées derrière.
     pub max_t: f32,
+    // L'instant auquel le rayon est lancé, entre 0 et 1, qui fixe la position des objets en
+    // mouvement (flou de mouvement)
+    pub time: f32,
 
     inv_slope: Vector3f,
 }
@@ -100,6 +127,7 @@ impl Ray {
             origin: origin,
             slope: slope,
             max_t: -1.0,
+            time: 0.0,
             inv_slope: Vector3f::new(1.0 / slope.x, 1.0 / slope.y, 1.0 / slope.z),
         }
     }
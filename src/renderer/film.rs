@@ -0,0 +1,112 @@
+use color_float::{LinearColor, RGBColor};
+use img::{Image, RGBAPixel};
+use renderer::tone_mapping::ToneMapping;
+
+/// Un pixel du film : la somme des couleurs des samples pondérées par le filtre, et la somme des
+/// poids.
+#[derive(Clone, Copy, Debug, Default)]
+struct FilmPixel {
+    color_sum: LinearColor,
+    weight_sum: f32,
+}
+
+/** Le film de la caméra : l'image en cours de rendu, en couleurs linéaires flottantes.
+ * Les contributions des blocs y sont accumulées, et ne sont divisées par les poids du filtre et
+ * quantifiées qu'à l'export : la dynamique de l'image est conservée jusque-là. */
+#[derive(Clone, Debug)]
+pub struct Film {
+    width: u32,
+    height: u32,
+    pixels: Vec<FilmPixel>,
+}
+
+impl Film {
+    pub fn new(width: u32, height: u32) -> Self {
+        Film {
+            width: width,
+            height: height,
+            pixels: vec![FilmPixel::default(); (width * height) as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Ajoute au pixel (x, y) une somme de couleurs pondérées `color` et la somme de leurs poids
+    /// `weight`, telles que renvoyées par `Filter::accumulate`. Plusieurs passes de rendu peuvent
+    /// être accumulées dans le même film.
+    pub fn add_contribution(&mut self, x: u32, y: u32, color: &LinearColor, weight: f32) {
+        let pixel = &mut self.pixels[(x + y * self.width) as usize];
+        pixel.color_sum += color;
+        pixel.weight_sum += weight;
+    }
+
+    /// La couleur linéaire du pixel (x, y) : la moyenne pondérée des contributions, ou du noir si
+    /// le pixel n'a rien reçu.
+    pub fn get_pixel(&self, x: u32, y: u32) -> LinearColor {
+        let pixel = &self.pixels[(x + y * self.width) as usize];
+        if pixel.weight_sum == 0.0 {
+            LinearColor::new_black()
+        } else {
+            pixel.color_sum / pixel.weight_sum
+        }
+    }
+
+    /// L'image en couleurs linéaires, non bornées.
+    pub fn to_linear_image(&self) -> Image<LinearColor> {
+        let columns: Vec<Vec<LinearColor>> = (0..self.width)
+            .map(|x| (0..self.height).map(|y| self.get_pixel(x, y)).collect())
+            .collect();
+        Image::from_vec_vec(&columns)
+    }
+
+    /// L'image quantifiée sur 8 bits et encodée en `sRGB`, après l'exposition et le tone mapping.
+    pub fn to_image(&self, tone_mapping: &ToneMapping) -> Image<RGBAPixel> {
+        let columns: Vec<Vec<RGBAPixel>> = (0..self.width)
+            .map(|x| {
+                (0..self.height)
+                    .map(|y| {
+                        let color: RGBColor = tone_mapping.apply(&self.get_pixel(x, y)).into();
+                        color.into()
+                    })
+                    .collect()
+            })
+            .collect();
+        Image::from_vec_vec(&columns)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use color_float::{LinearColor, FloatColor, Color};
+    use renderer::tone_mapping::ToneMapping;
+    use super::Film;
+
+    fn gray(value: f32) -> LinearColor {
+        LinearColor::new(FloatColor::new(value, value, value))
+    }
+
+    #[test]
+    fn test_film_accumulation() {
+        let mut film = Film::new(4, 3);
+        assert!(film.get_pixel(3, 2).is_black());
+
+        // Deux passes sur le même pixel : la moyenne est pondérée par les poids du filtre.
+        film.add_contribution(3, 2, &gray(2.0), 2.0);
+        film.add_contribution(3, 2, &gray(12.0), 3.0);
+        assert_eq!(film.get_pixel(3, 2).get_internal_color().r, 14.0 / 5.0);
+        assert!(film.get_pixel(2, 2).is_black());
+
+        // La dynamique est conservée jusqu'à l'export, qui borne la couleur.
+        let linear = film.to_linear_image();
+        assert_eq!(linear.get_pixel_at(3, 2).get_internal_color().g, 14.0 / 5.0);
+        let image = film.to_image(&ToneMapping::default());
+        assert_eq!(image.get_pixel_at(3, 2), (255, 255, 255, 255));
+        assert_eq!(image.get_pixel_at(0, 0), (0, 0, 0, 255));
+    }
+}
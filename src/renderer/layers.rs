@@ -0,0 +1,101 @@
+use color_float::{LinearColor, FloatColor, Color};
+use math::{Vector2f, VectorialOperations};
+use ray::Ray;
+use scene::World;
+use renderer::TextureRegister;
+use material::ShadingCoordinateSystem;
+use material::bsdf::abs_cos_theta;
+use sampler::samplers::get_halton;
+
+/// Le nombre de directions utilisées pour estimer l'albédo d'une surface.
+const ALBEDO_SAMPLES: u32 = 16;
+
+/// Un calque ajouté à l'image des fichiers OpenEXR, à côté du rendu lui-même, par exemple pour
+/// un débruiteur. Il est calculé avec un seul rayon au centre de chaque pixel.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ExrLayer {
+    /// La proportion de lumière réfléchie par la première surface touchée, vers l'observateur.
+    Albedo,
+    /// La normale unitaire de la première surface touchée, dans l'espace du monde.
+    Normal,
+}
+
+impl ExrLayer {
+    /// Le nom du calque dans le fichier : ses composantes sont `<nom>.R`, `<nom>.G` et
+    /// `<nom>.B`.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            ExrLayer::Albedo => "albedo",
+            ExrLayer::Normal => "normal",
+        }
+    }
+
+    /// La valeur du calque pour le rayon `ray`, noire si le rayon ne touche rien.
+    pub fn value(&self, ray: &Ray, world: &World, textures: &TextureRegister) -> LinearColor {
+        let mut ray = *ray;
+        let intersection = match world.get_intersection_point(&mut ray) {
+            Some(intersection) => intersection,
+            None => return LinearColor::new_black(),
+        };
+        let frag = intersection.fragment();
+        let normal = frag.normal / frag.normal.norm();
+        match *self {
+            ExrLayer::Normal => LinearColor::new(FloatColor::new(normal.x, normal.y, normal.z)),
+            ExrLayer::Albedo => {
+                // L'albédo est estimé en tirant des directions selon la BSDF, ce qui marche
+                // pour tous les matériaux, spéculaires compris.
+                let frame = ShadingCoordinateSystem::new_from_normal(&normal);
+                let wo = frame.world_into_local_space(&(-ray.slope() / ray.slope().norm()));
+                let bsdf = intersection.get_bsdf(world, textures);
+                let mut result = LinearColor::new_black();
+                for i in 0..ALBEDO_SAMPLES {
+                    let u = Vector2f::new(get_halton(i, 2), get_halton(i, 3));
+                    if let Some(sample) = bsdf.sample_f(&wo, u) {
+                        if sample.pdf > 0.0 {
+                            result += &(sample.f * (abs_cos_theta(&sample.wi) / sample.pdf));
+                        }
+                    }
+                }
+                result / ALBEDO_SAMPLES as f32
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use math::Vector3f;
+    use color_float::Color;
+    use ray::Ray;
+    use scene::World;
+    use renderer::TextureRegister;
+    use super::ExrLayer;
+
+    #[test]
+    fn test_exr_layers() {
+        // Un plan de 2x2 perpendiculaire à l'axe y
+        let mut world = World::new_empty();
+        world.add_object(Vector3f::new(0.0, 0.0, 0.0),
+                         "models/plane_no_uv.obj".to_string(),
+                         "plane".to_string());
+        world.build_acceleration_structure();
+        let textures = TextureRegister::new();
+        let ray_at = |x: f32| {
+            let mut ray = Ray::new(Vector3f::new(x, -1.0, 0.0), Vector3f::new(0.0, 1.0, 0.0));
+            ray.max_t = 10.0;
+            ray
+        };
+
+        let normal = ExrLayer::Normal.value(&ray_at(0.0), &world, &textures);
+        let normal = normal.get_internal_color();
+        assert!((normal.g().abs() - 1.0).abs() < 1e-4 && normal.r().abs() < 1e-4);
+        let albedo = ExrLayer::Albedo.value(&ray_at(0.0), &world, &textures);
+        let albedo = albedo.get_internal_color();
+        assert!(albedo.r() >= 0.0 && albedo.g() >= 0.0 && albedo.b() >= 0.0);
+
+        // Le calque est noir là où le rayon ne touche rien.
+        for layer in &[ExrLayer::Albedo, ExrLayer::Normal] {
+            assert!(layer.value(&ray_at(5.0), &world, &textures).is_black());
+        }
+    }
+}
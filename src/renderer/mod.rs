@@ -5,6 +5,9 @@ use sampler::Sample;
 
 pub mod render;
 pub mod block;
+pub mod film;
+pub mod tone_mapping;
+pub mod layers;
 
 /** Type representant un registre de texture */
 pub type TextureRegister = HashMap<String, Image<RGBAPixel>>;
@@ -1,14 +1,20 @@
 use scene;
 use img::{Image, RGBAPixel};
-use color_float::RGBColor;
+use color_float::{RGBColor, LinearColor};
 use ray::{Ray, Intersection};
-use geometry::obj3d::Object;
 use std::collections::HashMap;
 use std::fmt;
 use renderer::Pixel;
 use renderer::block::Block;
+use renderer::film::Film;
+use renderer::tone_mapping::ToneMapping;
+use renderer::layers::ExrLayer;
 use filter::FilterFactory;
-use sampler::SamplerFactory;
+use sampler::{Sample, Sampler, SamplerFactory};
+use integrator::{Integrator, IntegratorFactory};
+use light::light_sampler::LightSamplingStrategy;
+use material::bsdf::DirectLightingStrategy;
+use hdr::ExrPixelType;
 use std::sync::Mutex;
 use std::clone::Clone;
 use std::ops::DerefMut;
@@ -17,6 +23,7 @@ use scoped_pool::Pool;
 use colored::*;
 use pbr::ProgressBar;
 use std::f32;
+use math::Vector2f;
 
 // Le ratio n'est pas enregistré à la deserialization, il faut penser à appeler compute_ratio()
 // pour avoir un ratio autre que 0.
@@ -37,6 +44,27 @@ pub struct Renderer {
     #[serde(rename = "filter")]
     filter_factory: FilterFactory,
 
+    #[serde(rename = "integrator", default)]
+    integrator_factory: IntegratorFactory,
+
+    #[serde(default)]
+    light_sampling: LightSamplingStrategy,
+
+    #[serde(default)]
+    direct_lighting: DirectLightingStrategy,
+
+    // L'exposition et le tone mapping appliqués à l'export 8 bits
+    #[serde(default)]
+    tone_mapping: ToneMapping,
+
+    // Le type des composantes des images OpenEXR écrites
+    #[serde(default)]
+    exr_pixel_type: ExrPixelType,
+
+    // Les calques écrits à côté du rendu dans les images OpenEXR
+    #[serde(default)]
+    exr_layers: Vec<ExrLayer>,
+
     background_color: RGBColor,
 
     #[serde(skip_serializing, skip_deserializing, default = "HashMap::new")]
@@ -57,6 +85,12 @@ impl Renderer {
             textures: HashMap::new(),
             sampler_factory: SamplerFactory::HaltonSampler { subdivision_sampling: 4 },
             filter_factory: FilterFactory::BoxFilter,
+            integrator_factory: IntegratorFactory::Direct,
+            light_sampling: LightSamplingStrategy::All,
+            direct_lighting: DirectLightingStrategy::default(),
+            tone_mapping: ToneMapping::default(),
+            exr_pixel_type: ExrPixelType::default(),
+            exr_layers: vec![],
             bucket_size: 10,
             threads: 1,
         }
@@ -69,6 +103,26 @@ impl Renderer {
 
     }
 
+    pub fn light_sampling(&self) -> LightSamplingStrategy {
+        self.light_sampling
+    }
+
+    pub fn direct_lighting(&self) -> DirectLightingStrategy {
+        self.direct_lighting
+    }
+
+    pub fn tone_mapping(&self) -> ToneMapping {
+        self.tone_mapping
+    }
+
+    pub fn exr_pixel_type(&self) -> ExrPixelType {
+        self.exr_pixel_type
+    }
+
+    pub fn exr_layers(&self) -> &[ExrLayer] {
+        &self.exr_layers
+    }
+
     pub fn compute_ratio(&mut self) {
         self.ratio = self.res_x as f32 / self.res_y as f32;
     }
@@ -103,55 +157,36 @@ impl Renderer {
     }
 
     pub fn calculate_ray_intersection<'b>(&self,
-                                          objects: &[&'b Object], // TODO Changer en raytree
+                                          world: &'b scene::World,
                                           ray: &mut Ray)
                                           -> Option<Intersection<'b>> {
-
-        let mut intersection_point: Option<Intersection> = None;
-        for object in objects {
-            // TODO : Peut être virer le branching ici ?
-            // TODO : Regarder le geometry/intersection.rs dans tray
-            if object.bounding_box().intersects(ray) {
-                if let Some(point) = object.get_intersection_point(ray) {
-                    intersection_point = Some(point);
-                }
-            }
-        }
-        intersection_point
+        world.get_intersection_point(ray)
     }
 
     /** Calcule les rayons à lancer pour le canvas passé en paramètres.
-    Calcule ensuite la couleur finale de chaque rayon et stocke le résultat dans
-    le canvas passé en paramètres. */
-    pub fn calculate_rays(&self, world: &scene::World, camera: &scene::Camera, pixel: &mut Pixel) {
-
-        let objects = world.objects()
-            .iter()
-            .filter(|bbox| bbox.is_visible())
-            .collect::<Vec<&Object>>();
+    Calcule ensuite la couleur finale de chaque rayon grâce à l'intégrateur, et stocke le
+    résultat dans le canvas passé en paramètres. */
+    pub fn calculate_rays(&self,
+                          world: &scene::World,
+                          camera: &scene::Camera,
+                          pixel: &mut Pixel,
+                          integrator: &Integrator,
+                          sampler: &mut Sampler) {
 
         for sample in &mut pixel.samples {
             // On récupère le rayon à partir du sample
-            let mut ray: Ray =
-                camera.create_ray_from_sample(sample,
-                                              self.ratio,
-                                              self.res_x as f32,
-                                              self.res_y as f32);
-
-            // CALCUL DE LA COULEUR DU RAYON (TODO à mettre ailleurs)
-
-            let point = self.calculate_ray_intersection(&objects, &mut ray);
-
-            // On détermine la couleur du rayon, simplement à partir du fragment retourné et
-            // du matériau associé à l'objet intersecté.
-            match point {
-                Some(p) => {
-                    sample.color = p.get_point_color(world, &self.textures);
-                }
-                _ => {
-                    sample.color = self.background_color.into();
-                }
-            }
+            let ray = camera.create_ray_from_sample(sample,
+                                                    sampler.get_2d(),
+                                                    sampler.get_1d(),
+                                                    self.ratio,
+                                                    self.res_x as f32,
+                                                    self.res_y as f32);
+
+            sample.color = match ray {
+                Some(ray) => integrator.radiance(&ray, world, &self.textures, sampler),
+                // Le sample est en dehors de l'image de la caméra.
+                None => LinearColor::new_black(),
+            };
         }
     }
 
@@ -215,10 +250,10 @@ impl Renderer {
     }
 
     /** Fonction principale, qui génére les blocs de l'image et les rends, pour enfin les
-     * recombiner dans une image finale. */
+     * accumuler dans le film, en couleurs linéaires. */
     #[allow(let_and_return)]
-    pub fn render(&self, world: &scene::World, camera: &scene::Camera) -> Image<RGBAPixel> {
-        let shared_image: Mutex<Image<RGBAPixel>> = Mutex::new(Image::new(self.res_x, self.res_y));
+    pub fn render(&self, world: &scene::World, camera: &scene::Camera) -> Film {
+        let shared_film: Mutex<Film> = Mutex::new(Film::new(self.res_x as u32, self.res_y as u32));
 
         // On definit le nombre de threads à utiliser
         let pool = Pool::new(self.threads);
@@ -238,61 +273,83 @@ impl Renderer {
         pool.scoped(|scope| while !blocks.is_empty() {
             let block = blocks.pop().unwrap();
             scope.execute(|| {
-                self.render_block(block, world, camera, &shared_image);
+                self.render_block(block, world, camera, &shared_film);
                 progress_bar.lock().unwrap().inc();
             });
         });
 
         progress_bar.lock().unwrap().finish();
 
-        // On transforme le Arc<Mutex<Image>> en Image
-        let result = shared_image.lock().unwrap().deref_mut().clone();
+        // On transforme le Mutex<Film> en Film
+        let result = shared_film.lock().unwrap().deref_mut().clone();
         result
     }
 
+    /// Calcule le calque `layer` de l'image vue par `camera`, avec un rayon au centre de chaque
+    /// pixel, à l'ouverture de l'obturateur.
+    pub fn render_layer(&self,
+                        world: &scene::World,
+                        camera: &scene::Camera,
+                        layer: ExrLayer)
+                        -> Image<LinearColor> {
+        let center = Vector2f::new(0.5, 0.5);
+        let columns: Vec<Vec<LinearColor>> = (0..self.res_x)
+            .map(|x| {
+                (0..self.res_y)
+                    .map(|y| {
+                        let sample = Sample::new(x as f32 + 0.5, y as f32 + 0.5);
+                        match camera.create_ray_from_sample(&sample,
+                                                            center,
+                                                            0.0,
+                                                            self.ratio,
+                                                            self.res_x as f32,
+                                                            self.res_y as f32) {
+                            Some(ray) => layer.value(&ray, world, &self.textures),
+                            None => LinearColor::new_black(),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Image::from_vec_vec(&columns)
+    }
+
     /** Cette fonction se charge de rendre un bloc de l'image. */
     pub fn render_block(&self,
                         mut block: Block,
                         world: &scene::World,
                         camera: &scene::Camera,
-                        shared_image: &Mutex<Image<RGBAPixel>>) {
+                        shared_film: &Mutex<Film>) {
 
         // Generation des samples
-        self.sampler_factory
-            .create_sampler()
-            .create_samples(&mut block);
+        let mut sampler = self.sampler_factory.create_sampler();
+        sampler.create_samples(&mut block);
+
+        let integrator = self.integrator_factory
+            .create_integrator(self.background_color.into());
 
         let filter = self.filter_factory
             .create_filter(self.res_x as u32, self.res_y as u32);
 
         // Emission des rayons
         for pixel in block.pixels_mut() {
-            self.calculate_rays(world, camera, pixel);
+            self.calculate_rays(world, camera, pixel, &*integrator, &mut *sampler);
         }
 
 
-        let mut temp_result: Vec<Vec<RGBAPixel>> = vec![];
-
-        // Reconstruction de l'image à partir des samples et du filtre
+        // Reconstruction de l'image à partir des samples et du filtre : les couleurs pondérées
+        // sont accumulées dans le film
+        let mut film = shared_film.lock().unwrap();
         for x in 0..block.dimensions().0 {
-            let mut col: Vec<RGBAPixel> = vec![];
-
             for y in 0..block.dimensions().1 {
-                let color: RGBColor = filter.compute_color(block.get_pixel(x, y),
-                                   (block.position_x(), block.position_y()))
-                    .into();
-                col.push(color.into());
+                let (color, weight) = filter.accumulate(block.get_pixel(x, y),
+                                                        (block.position_x(), block.position_y()));
+                film.add_contribution(block.position_x() + x,
+                                      block.position_y() + y,
+                                      &color,
+                                      weight);
             }
-            temp_result.push(col);
         }
-
-        // Superposition de l'image rendue à l'image finale
-        shared_image.lock()
-            .unwrap()
-            .deref_mut()
-            .superpose_sub_image(Image::<RGBAPixel>::from_vec_vec(&temp_result),
-                                 block.position_x(),
-                                 block.position_y());
     }
 }
 
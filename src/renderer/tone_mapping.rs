@@ -0,0 +1,146 @@
+use color_float::{LinearColor, FloatColor, Color};
+
+/// L'opérateur qui ramène les radiances du film dans [0, 1] avant la quantification sur 8 bits.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum ToneMappingOperator {
+    /// Les composantes au-delà de 1 sont simplement bornées.
+    Clamp,
+    /// L / (1 + L), appliqué à la luminance pour conserver la teinte.
+    Reinhard,
+    /// Reinhard étendu : la luminance `white_point` (et au-delà) devient du blanc.
+    ReinhardExtended { white_point: f32 },
+    /// L'approximation de la courbe filmique ACES par K. Narkowicz, par composante.
+    ACES,
+    /// La courbe filmique de J. Hable (Uncharted 2), par composante.
+    Hable,
+}
+
+impl Default for ToneMappingOperator {
+    fn default() -> Self {
+        ToneMappingOperator::Clamp
+    }
+}
+
+/// Le post-traitement des couleurs à l'export 8 bits : une exposition, en EV (chaque cran double
+/// la radiance), puis l'opérateur de tone mapping. Les exports à grande dynamique n'en tiennent
+/// pas compte.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct ToneMapping {
+    #[serde(default)]
+    pub exposure: f32,
+    #[serde(default)]
+    pub operator: ToneMappingOperator,
+}
+
+// Les paramètres de la courbe de Hable, et la radiance qui devient du blanc.
+const HABLE_SHOULDER_STRENGTH: f32 = 0.15;
+const HABLE_LINEAR_STRENGTH: f32 = 0.50;
+const HABLE_LINEAR_ANGLE: f32 = 0.10;
+const HABLE_TOE_STRENGTH: f32 = 0.20;
+const HABLE_TOE_NUMERATOR: f32 = 0.02;
+const HABLE_TOE_DENOMINATOR: f32 = 0.30;
+const HABLE_WHITE: f32 = 11.2;
+
+impl ToneMapping {
+    pub fn new(exposure: f32, operator: ToneMappingOperator) -> Self {
+        ToneMapping {
+            exposure: exposure,
+            operator: operator,
+        }
+    }
+
+    /// La couleur exposée puis ramenée dans [0, 1] par l'opérateur.
+    pub fn apply(&self, color: &LinearColor) -> LinearColor {
+        let color = *color * 2f32.powf(self.exposure);
+        let mut result = match self.operator {
+            ToneMappingOperator::Clamp => color,
+            ToneMappingOperator::Reinhard => scale_luminance(&color, |l| l / (1.0 + l)),
+            ToneMappingOperator::ReinhardExtended { white_point } => {
+                let white_squared = white_point * white_point;
+                scale_luminance(&color, |l| l * (1.0 + l / white_squared) / (1.0 + l))
+            }
+            ToneMappingOperator::ACES => map_components(&color, aces),
+            ToneMappingOperator::Hable => {
+                let white_scale = 1.0 / hable(HABLE_WHITE);
+                // Le biais d'exposition de 2 est celui de la courbe d'origine.
+                map_components(&color, |x| hable(2.0 * x) * white_scale)
+            }
+        };
+        result.clamp();
+        result
+    }
+}
+
+// Multiplie la couleur pour que sa luminance L devienne f(L).
+fn scale_luminance<F: Fn(f32) -> f32>(color: &LinearColor, f: F) -> LinearColor {
+    let luminance = color.luminance();
+    if luminance <= 0.0 {
+        LinearColor::new_black()
+    } else {
+        *color * (f(luminance) / luminance)
+    }
+}
+
+// Applique f à chaque composante de la couleur.
+fn map_components<F: Fn(f32) -> f32>(color: &LinearColor, f: F) -> LinearColor {
+    let color = color.get_internal_color();
+    LinearColor::new(FloatColor::new(f(color.r().max(0.0)),
+                                     f(color.g().max(0.0)),
+                                     f(color.b().max(0.0))))
+}
+
+fn aces(x: f32) -> f32 {
+    (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)
+}
+
+fn hable(x: f32) -> f32 {
+    let (a, b, c) = (HABLE_SHOULDER_STRENGTH, HABLE_LINEAR_STRENGTH, HABLE_LINEAR_ANGLE);
+    let (d, e, f) = (HABLE_TOE_STRENGTH, HABLE_TOE_NUMERATOR, HABLE_TOE_DENOMINATOR);
+    ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f
+}
+
+#[cfg(test)]
+mod test {
+    use color_float::{LinearColor, FloatColor, Color};
+    use super::{ToneMapping, ToneMappingOperator};
+
+    fn gray(value: f32) -> LinearColor {
+        LinearColor::new(FloatColor::new(value, value, value))
+    }
+
+    fn mapped(tone_mapping: &ToneMapping, value: f32) -> f32 {
+        tone_mapping.apply(&gray(value)).get_internal_color().g()
+    }
+
+    #[test]
+    fn test_tone_mapping_operators() {
+        let clamp = ToneMapping::new(1.0, ToneMappingOperator::Clamp);
+        assert!((mapped(&clamp, 0.25) - 0.5).abs() < 1e-6);
+        assert_eq!(mapped(&clamp, 4.0), 1.0);
+
+        let reinhard = ToneMapping::new(0.0, ToneMappingOperator::Reinhard);
+        assert!((mapped(&reinhard, 1.0) - 0.5).abs() < 1e-5);
+        // La teinte est conservée.
+        let color = reinhard.apply(&LinearColor::new(FloatColor::new(2.0, 1.0, 0.0)));
+        let color = color.get_internal_color();
+        assert!((color.r() - 2.0 * color.g()).abs() < 1e-5);
+
+        let extended =
+            ToneMapping::new(0.0, ToneMappingOperator::ReinhardExtended { white_point: 4.0 });
+        assert!((mapped(&extended, 4.0) - 1.0).abs() < 1e-5);
+        assert!(mapped(&extended, 1.0) > mapped(&reinhard, 1.0));
+
+        // Les courbes filmiques sont croissantes, partent de 0 et tendent vers le blanc.
+        for operator in &[ToneMappingOperator::ACES, ToneMappingOperator::Hable] {
+            let filmic = ToneMapping::new(0.0, *operator);
+            assert!(mapped(&filmic, 0.0).abs() < 1e-6);
+            let values: Vec<f32> = [0.1, 0.5, 1.0, 2.0, 8.0].iter()
+                .map(|&value| mapped(&filmic, value))
+                .collect();
+            assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(mapped(&filmic, 100.0) > 0.95 && mapped(&filmic, 100.0) <= 1.0);
+        }
+        let hable = ToneMapping::new(0.0, ToneMappingOperator::Hable);
+        assert!((mapped(&hable, 5.6) - 1.0).abs() < 1e-5);
+    }
+}
@@ -65,6 +65,17 @@ pub trait Sampler {
     fn get_sample_distribution(&self) -> Vec<Vector2f> {
         vec![]
     }
+
+    /// Renvoie un nombre aléatoire compris dans [0;1[, utilisé par les intégrateurs pour
+    /// échantillonner les directions et les lumières.
+    fn get_1d(&mut self) -> f32;
+
+    /// Renvoie un point aléatoire de [0;1[ x [0;1[.
+    fn get_2d(&mut self) -> Vector2f {
+        let x = self.get_1d();
+        let y = self.get_1d();
+        Vector2f::new(x, y)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -1,5 +1,6 @@
 use math::Vector2f;
 use sampler::Sampler;
+use rand::{Rng, XorShiftRng, weak_rng};
 
 /** Sampler avec une distribution d'échantillon uniforme à travers les pixels
 (Stratified sampler without jittering)*/
@@ -7,6 +8,8 @@ pub struct DefaultSampler {
     sample_rate: u32,
 
     sample_square_root: u32,
+
+    rng: XorShiftRng,
 }
 
 impl DefaultSampler {
@@ -14,6 +17,7 @@ impl DefaultSampler {
         DefaultSampler {
             sample_rate: sample_rate,
             sample_square_root: (sample_rate as f32).sqrt().ceil() as u32,
+            rng: weak_rng(),
         }
     }
 }
@@ -36,10 +40,16 @@ impl Sampler for DefaultSampler {
 
         result
     }
+
+    fn get_1d(&mut self) -> f32 {
+        self.rng.next_f32()
+    }
 }
 
 // TODO PBRT propose une opti pour la base 2
-fn get_halton(a: u32, basis: u32) -> f32 {
+/// Le `a`-ième terme de la suite de Van der Corput en base `basis`, qui forme une des
+/// dimensions de la suite de Halton.
+pub fn get_halton(a: u32, basis: u32) -> f32 {
     // Inversion de a
     let mut a_rev = 0;
     let mut a_not_rev = a;
@@ -60,11 +70,16 @@ fn get_halton(a: u32, basis: u32) -> f32 {
 /** Sampler 2D utilisant les séquences de Halton. */
 pub struct HaltonSampler {
     sample_rate: u32,
+
+    rng: XorShiftRng,
 }
 
 impl HaltonSampler {
     pub fn new(sample_rate: u32) -> HaltonSampler {
-        HaltonSampler { sample_rate: sample_rate }
+        HaltonSampler {
+            sample_rate: sample_rate,
+            rng: weak_rng(),
+        }
     }
 }
 
@@ -80,4 +95,8 @@ impl Sampler for HaltonSampler {
 
         result
     }
+
+    fn get_1d(&mut self) -> f32 {
+        self.rng.next_f32()
+    }
 }
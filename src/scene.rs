@@ -1,13 +1,27 @@
+use std::f32;
 use std::vec::Vec;
-use math::{Vector3, Vector3f, VectorialOperations};
-use geometry::obj3d::Object;
-use light::LightObject;
+use math::{Vector2f, Vector3, Vector3f, VectorialOperations};
+use geometry::obj3d::{Object, Mesh, MeshRegister};
+use geometry::bounding_box::BoundingBox;
+use geometry::bvh::{BVH, AccelerationStructure};
+use light::{Light, LightObject};
+use light::area_light::AreaLight;
+use light::environment_light::EnvironmentLight;
+use light::light_sampler::{LightSampler, LightSamplingStrategy};
+use material::ShadingCoordinateSystem;
+use material::bsdf::DirectLightingStrategy;
 use sampler::Sample;
-use ray::Ray;
+use tools::monte_carlo;
+use animation::{self, CameraAnimation, FrameRange, frame_file_path};
+use ray::{Ray, Intersection};
 use io_utils;
+use hdr;
+use img::Image;
+use color_float::LinearColor;
 use serde_json;
 use renderer::render::Renderer;
 use std::time::Instant;
+use std::sync::Arc;
 use ray::Surface;
 
 // Une simple scène
@@ -15,6 +29,9 @@ use ray::Surface;
 pub struct Scene {
     pub world: World,
     pub renderer: Renderer,
+    // Les images à rendre pour une animation
+    #[serde(default)]
+    pub animation: FrameRange,
 }
 
 impl Scene {
@@ -30,7 +47,16 @@ impl Scene {
             }
             Err(e) => panic!("Error while reading file {} : {}", file, e),
         };
+        if let Err(e) = scene.animation.check() {
+            panic!("Error while loading world. {}", e);
+        }
         scene.world.load_objects();
+        scene.world.load_lights();
+        scene.world.set_light_sampling(scene.renderer.light_sampling());
+        scene.world.set_direct_lighting(scene.renderer.direct_lighting());
+        scene.world.build_acceleration_structure();
+        let first_frame = scene.animation.frame_start;
+        scene.set_frame(first_frame);
         scene.renderer.initialize(&scene.world);
         scene
     }
@@ -40,6 +66,7 @@ impl Scene {
         Scene {
             world: World::new_empty(),
             renderer: Renderer::new(960, 540),
+            animation: FrameRange::default(),
         }
     }
 
@@ -54,22 +81,175 @@ impl Scene {
         }
     }
 
+    // Rend l'image vue par la première caméra dans le fichier "file_path"
     pub fn render_to_file(&self, file_path: &str) {
+        self.render_camera_to_file(0, file_path)
+    }
+
+    /// Rend l'image vue par la caméra d'indice `camera` dans le fichier `file_path`.
+    pub fn render_camera_to_file(&self, camera: usize, file_path: &str) {
         self.renderer.show_information();
-        println!("Starting to render...");
+        println!("Starting to render camera {}...", camera);
         let now = Instant::now();
-        let image = self.renderer
-            .render(&self.world, self.world.get_camera(0));
+        let film = self.renderer
+            .render(&self.world, self.world.get_camera(camera));
         println!("Render done in {} s, writting result to file {}",
                  now.elapsed().as_secs() as f64 + (now.elapsed().subsec_nanos() as f64 *
                      (1.0/1_000_000_000_f64)),
                  &file_path,);
-        image.write_to_file(file_path)
+        // Les formats à grande dynamique reçoivent les couleurs linéaires du film, les autres
+        // l'image quantifiée.
+        if hdr::is_hdr_file(file_path) {
+            let image = film.to_linear_image();
+            let pixel_type = self.renderer.exr_pixel_type();
+            let layers = self.renderer.exr_layers();
+            let result = if hdr::is_exr_file(file_path) && !layers.is_empty() {
+                // Le rendu est le calque sans nom, suivi des calques demandés.
+                let camera = self.world.get_camera(camera);
+                let images: Vec<Image<LinearColor>> = layers
+                    .iter()
+                    .map(|layer| self.renderer.render_layer(&self.world, camera, *layer))
+                    .collect();
+                let mut named = vec![("", &image)];
+                for (layer, image) in layers.iter().zip(&images) {
+                    named.push((layer.name(), image));
+                }
+                hdr::write_exr_file(file_path, &named, pixel_type)
+            } else {
+                hdr::write_hdr_file(file_path, &image, pixel_type)
+            };
+            if let Err(e) = result {
+                println!("Could not write image. Error : {}", e);
+            }
+        } else {
+            film.to_image(&self.renderer.tone_mapping()).write_to_file(file_path)
+        }
+    }
+
+    /// Rend l'image vue par la caméra désignée par `camera`, son nom ou son indice, dans le
+    /// fichier `file_path`.
+    pub fn render_named_camera_to_file(&self,
+                                       camera: &str,
+                                       file_path: &str)
+                                       -> Result<(), String> {
+        match self.world.find_camera(camera) {
+            Some(index) => {
+                self.render_camera_to_file(index, file_path);
+                Ok(())
+            }
+            None => Err(format!("No camera named {} in the scene", camera)),
+        }
+    }
+
+    /// Place les caméras et les objets animés dans l'état de l'image `frame`.
+    pub fn set_frame(&mut self, frame: u32) {
+        let time = self.animation.time(frame);
+        let duration = self.animation.frame_duration();
+        self.world.set_time(time, duration);
+    }
+
+    /// Rend l'image vue par la caméra `camera`, désignée par son nom ou son indice (la première
+    /// caméra par défaut), dans le fichier `file_path`. Si `all_cameras` est vrai, toutes les
+    /// caméras sont rendues et `file_path` est un motif (voir `render_all_cameras`).
+    pub fn render_cameras(&self,
+                          camera: Option<&str>,
+                          all_cameras: bool,
+                          file_path: &str)
+                          -> Result<(), String> {
+        if all_cameras {
+            self.render_all_cameras(file_path);
+            return Ok(());
+        }
+        match camera {
+            Some(camera) => self.render_named_camera_to_file(camera, file_path),
+            None => {
+                self.render_to_file(file_path);
+                Ok(())
+            }
+        }
+    }
+
+    /// Rend toutes les images de l'animation avec `render_cameras`. Le fichier de chaque image
+    /// est donné par `pattern` (voir `animation::frame_file_path`). La géomètrie et les textures
+    /// ne sont chargées qu'une fois.
+    pub fn render_frames(&mut self,
+                         camera: Option<&str>,
+                         all_cameras: bool,
+                         pattern: &str)
+                         -> Result<(), String> {
+        // On vérifie la caméra avant de rendre la première image.
+        if let Some(camera) = camera {
+            if !all_cameras && self.world.find_camera(camera).is_none() {
+                return Err(format!("No camera named {} in the scene", camera));
+            }
+        }
+        for frame in self.animation.frame_start..(self.animation.frame_end + 1) {
+            self.set_frame(frame);
+            self.render_cameras(camera, all_cameras, &frame_file_path(pattern, frame))?;
+        }
+        Ok(())
+    }
+
+    /// Rend les images vues par toutes les caméras, l'une après l'autre : la géomètrie et les
+    /// textures ne sont chargées qu'une fois. Le fichier de chaque image est donné par `pattern`
+    /// (voir `camera_file_path`).
+    pub fn render_all_cameras(&self, pattern: &str) {
+        for index in 0..self.world.cameras().len() {
+            let path = camera_file_path(pattern, index, self.world.get_camera(index));
+            self.render_camera_to_file(index, &path);
+        }
+    }
+}
+
+/// Le chemin du fichier de l'image de la caméra d'indice `index`, d'après le motif `pattern`, où
+/// `{index}` est remplacé par l'indice de la caméra et `{name}` par son nom (ou son indice si
+/// elle n'en a pas). Si le motif ne contient aucun des deux, le nom est ajouté avant
+/// l'extension : `out.png` devient `out_<nom>.png`.
+pub fn camera_file_path(pattern: &str, index: usize, camera: &Camera) -> String {
+    let name = if camera.name().is_empty() {
+        index.to_string()
+    } else {
+        camera.name().to_string()
+    };
+    if pattern.contains("{index}") || pattern.contains("{name}") {
+        return pattern.replace("{index}", &index.to_string()).replace("{name}", &name);
+    }
+    match pattern.rfind('.') {
+        Some(dot) if !pattern[dot..].contains('/') => {
+            format!("{}_{}{}", &pattern[..dot], name, &pattern[dot..])
+        }
+        _ => format!("{}_{}", pattern, name),
+    }
+}
+
+/// La projection d'une caméra. Par exemple : `"projection": {"Orthographic": {"width": 10.0}}`
+/// ou `"projection": "Equirectangular"`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    /// Une projection en perspective, définie par `fov` et `clip`.
+    Perspective,
+    /// Des rayons parallèles à l'axe de la caméra, qui partent d'un cadre de largeur `width`
+    /// centré sur la caméra : les dimensions ne dépendent pas de la distance.
+    Orthographic { width: f32 },
+    /// Un fisheye équidistant dont l'image circulaire couvre un angle `max_angle`, en degrés
+    /// (jusqu'à 360°). Les pixels en dehors du cercle sont noirs.
+    Fisheye { max_angle: f32 },
+    /// Un panorama à 360° autour de la verticale de la caméra (`up`), sur 180° de haut en bas.
+    Equirectangular,
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection::Perspective
     }
 }
 
 #[derive(Serialize,Deserialize,Debug)]
 pub struct Camera {
+    // Le nom de la caméra, qui permet de la choisir pour le rendu
+    #[serde(default)]
+    name: String,
+
     /// The position fo the camera exprimed in the standard word space coordinates (where {0,0,0}
     /// is the center of the world)
     pub world_position: Vector3f,
@@ -85,6 +265,34 @@ pub struct Camera {
 
     // La distance entre le canvas et l'origine de la caméra.
     clip: f32,
+
+    // Le rayon de l'objectif. Une ouverture nulle donne une caméra à sténopé, où tout est net.
+    #[serde(default)]
+    aperture_radius: f32,
+
+    // La distance, le long de l'axe de la caméra, du plan où l'image est nette. Si elle n'est pas
+    // donnée, ou si `autofocus` est vrai, la caméra fait la mise au point sur `target_position`.
+    #[serde(default)]
+    focus_distance: Option<f32>,
+
+    #[serde(default)]
+    autofocus: bool,
+
+    // La projection de la caméra. Pour les projections panoramiques, la zone nette est la
+    // sphère de rayon `focus_distance` autour de la caméra.
+    #[serde(default)]
+    projection: Projection,
+
+    // Les paramètres animés de la caméra
+    #[serde(default)]
+    animation: Option<CameraAnimation>,
+
+    // Les instants d'ouverture et de fermeture de l'obturateur, entre 0 et 1 : les rayons sont
+    // lancés à des instants tirés entre les deux, ce qui donne le flou des objets en mouvement.
+    #[serde(default)]
+    shutter_open: f32,
+    #[serde(default)]
+    shutter_close: f32,
 }
 
 const DEFAULT_FOV: f32 = 70.0;
@@ -93,28 +301,99 @@ const DEFAULT_CLIP: f32 = 0.1;
 impl Camera {
     pub fn new(position: Vector3f, target: Vector3f, up: Vector3f) -> Self {
         Camera {
+            name: String::new(),
             world_position: position,
             target_position: target,
             fov: DEFAULT_FOV,
             up: up,
             clip: DEFAULT_CLIP,
+            aperture_radius: 0.0,
+            focus_distance: None,
+            autofocus: false,
+            projection: Projection::Perspective,
+            animation: None,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
         }
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+    }
+
     pub fn set_fov(&mut self, fov: f32) {
         self.fov = fov;
     }
 
-    /** Donne un repère pour placer le cadre de la caméra. Le premier point
-    correspond à l'origine en haut à gauche, les deux autres aux vecteurs
-    x et y qui définissent les dimensions et orientations du cadre respectivement */
-    pub fn get_canvas_base(&self, ratio: f32) -> (Vector3f, Vector3f, Vector3f) {
+    /// Règle la profondeur de champ : le rayon de l'objectif et la distance de mise au point
+    /// (`None` pour faire la mise au point sur la cible).
+    pub fn set_lens(&mut self, aperture_radius: f32, focus_distance: Option<f32>) {
+        self.aperture_radius = aperture_radius;
+        self.focus_distance = focus_distance;
+    }
+
+    pub fn set_animation(&mut self, animation: Option<CameraAnimation>) {
+        self.animation = animation;
+    }
+
+    /// Donne aux paramètres animés de la caméra leur valeur à l'instant `time`, en secondes.
+    pub fn set_time(&mut self, time: f32) {
+        let animation = match self.animation {
+            Some(ref animation) => animation,
+            None => return,
+        };
+        if let Some(position) = animation::evaluate(&animation.world_position, time) {
+            self.world_position = position;
+        }
+        if let Some(target) = animation::evaluate(&animation.target_position, time) {
+            self.target_position = target;
+        }
+        if let Some(fov) = animation::evaluate(&animation.fov, time) {
+            self.fov = fov;
+        }
+        if let Some(distance) = animation::evaluate(&animation.focus_distance, time) {
+            self.focus_distance = Some(distance);
+        }
+    }
+
+    /// Règle les instants d'ouverture et de fermeture de l'obturateur, entre 0 et 1.
+    pub fn set_shutter(&mut self, open: f32, close: f32) {
+        self.shutter_open = open;
+        self.shutter_close = close;
+    }
+
+    pub fn set_projection(&mut self, projection: Projection) {
+        self.projection = projection;
+    }
+
+    /// La distance du plan net, le long de l'axe de la caméra.
+    pub fn get_focus_distance(&self) -> f32 {
+        match self.focus_distance {
+            Some(distance) if !self.autofocus => distance,
+            _ => (self.target_position - self.world_position).norm(),
+        }
+    }
+
+    // Le repère de la caméra : la droite, le bas et l'avant de l'image, unitaires.
+    fn get_frame(&self) -> (Vector3f, Vector3f, Vector3f) {
         let cam_vector = self.target_position - self.world_position;
         let e1_not_norm = cam_vector.cross_product(&self.up);
 
         let e1 = e1_not_norm / e1_not_norm.norm();
         let e3 = cam_vector / cam_vector.norm();
         let e2 = e3.cross_product(&e1);
+        (e1, e2, e3)
+    }
+
+    /** Donne un repère pour placer le cadre de la caméra. Le premier point
+    correspond à l'origine en haut à gauche, les deux autres aux vecteurs
+    x et y qui définissent les dimensions et orientations du cadre respectivement */
+    pub fn get_canvas_base(&self, ratio: f32) -> (Vector3f, Vector3f, Vector3f) {
+        let (e1, e2, e3) = self.get_frame();
 
         let fov_tan = (self.fov / 2.0).to_radians().tan();
 
@@ -126,19 +405,105 @@ impl Camera {
     }
 
     // Crée un rayon dont la direction est déterminé par les coordonnées du sample
-    // passé en paramètres.
+    // passé en paramètres, selon la projection de la caméra. `lens` est un point de
+    // [0;1[ x [0;1[ qui choisit l'origine du rayon sur l'objectif (modèle de lentille mince), et
+    // `shutter` un nombre de [0;1[ qui choisit l'instant du rayon pendant l'ouverture de
+    // l'obturateur. Renvoie `None` pour un sample en dehors de l'image d'un fisheye.
     pub fn create_ray_from_sample(&self,
                                   sample: &Sample,
+                                  lens: Vector2f,
+                                  shutter: f32,
                                   ratio: f32,
                                   sample_res_x: f32,
                                   sample_res_y: f32)
-                                  -> Ray {
-        // TODO ici on fait un appel à get_canvas_basis pour chaque sample
-        let (origin, e1, e2) = self.get_canvas_base(ratio);
+                                  -> Option<Ray> {
+        let time = self.shutter_open + (self.shutter_close - self.shutter_open) * shutter;
+        self.create_ray(sample, lens, ratio, sample_res_x, sample_res_y)
+            .map(|mut ray| {
+                     ray.time = time;
+                     ray
+                 })
+    }
+
+    // Le rayon de `create_ray_from_sample`, avant que son instant ne soit choisi.
+    fn create_ray(&self,
+                  sample: &Sample,
+                  lens: Vector2f,
+                  ratio: f32,
+                  sample_res_x: f32,
+                  sample_res_y: f32)
+                  -> Option<Ray> {
         let sample_coord = sample.position();
-        let target = origin + e1 * sample_coord.x / sample_res_x +
-                     e2 * sample_coord.y / sample_res_y;
-        Ray::new(self.world_position, target - self.world_position)
+        // La position du sample dans l'image, de (0, 0) en haut à gauche à (1, 1) en bas à droite
+        let (x, y) = (sample_coord.x / sample_res_x, sample_coord.y / sample_res_y);
+        let (right, down, forward) = self.get_frame();
+        let (origin, slope) = match self.projection {
+            Projection::Perspective => {
+                // TODO ici on fait un appel à get_canvas_basis pour chaque sample
+                let (origin, e1, e2) = self.get_canvas_base(ratio);
+                let target = origin + e1 * x + e2 * y;
+                (self.world_position, target - self.world_position)
+            }
+            Projection::Orthographic { width } => {
+                let origin = self.world_position + right * ((x - 0.5) * width) +
+                             down * ((y - 0.5) * width / ratio);
+                (origin, forward)
+            }
+            Projection::Fisheye { max_angle } => {
+                // Projection équidistante : l'angle avec l'axe est proportionnel à la distance au
+                // centre de l'image. Le cercle de l'image touche les bords du plus petit côté.
+                let (px, py) = ((x - 0.5) * sample_res_x, (y - 0.5) * sample_res_y);
+                let radius = f32::min(sample_res_x, sample_res_y) / 2.0;
+                let r = (px * px + py * py).sqrt() / radius;
+                if r > 1.0 {
+                    return None;
+                }
+                let theta = r * (max_angle / 2.0).to_radians();
+                let side = if r > 0.0 {
+                    (right * px + down * py) / (r * radius)
+                } else {
+                    right
+                };
+                let direction = forward * theta.cos() + side * theta.sin();
+                return Some(self.panoramic_ray(direction, lens));
+            }
+            Projection::Equirectangular => {
+                // La longitude va de -180° à 180° autour de la verticale, l'avant au centre, et
+                // la colatitude de 0 (en haut) à 180° (en bas).
+                let phi = (x - 0.5) * 2.0 * f32::consts::PI;
+                let theta = y * f32::consts::PI;
+                let horizontal = forward * phi.cos() + right * phi.sin();
+                let direction = -down * theta.cos() + horizontal * theta.sin();
+                return Some(self.panoramic_ray(direction, lens));
+            }
+        };
+        if self.aperture_radius <= 0.0 {
+            return Some(Ray::new(origin, slope));
+        }
+
+        // Tous les rayons qui passent par un même point du canvas se croisent sur le plan net.
+        let t = self.get_focus_distance() / slope.dot_product_ref(&forward);
+        let focus_point = origin + slope * t;
+        let d = monte_carlo::sample_disk_concentric(lens) * self.aperture_radius;
+        let lens_point = origin + right * d.x + down * d.y;
+        // La pente est divisée par t pour garder la même échelle que sans objectif.
+        Some(Ray::new(lens_point, (focus_point - lens_point) / t))
+    }
+
+    // Le rayon d'une projection panoramique, de direction unitaire `direction`. Avec un objectif,
+    // son origine est tirée sur un disque perpendiculaire à la direction, et il passe par le
+    // point net à `focus_distance` de la caméra dans cette direction.
+    fn panoramic_ray(&self, direction: Vector3f, lens: Vector2f) -> Ray {
+        if self.aperture_radius <= 0.0 {
+            return Ray::new(self.world_position, direction);
+        }
+        let distance = self.get_focus_distance();
+        let focus_point = self.world_position + direction * distance;
+        let frame = ShadingCoordinateSystem::new_from_normal(&direction);
+        let d = monte_carlo::sample_disk_concentric(lens) * self.aperture_radius;
+        let lens_point = self.world_position +
+                         frame.local_into_world_space(&Vector3f::new(d.x, d.y, 0.0));
+        Ray::new(lens_point, (focus_point - lens_point) / distance)
     }
 }
 
@@ -152,6 +517,36 @@ pub struct World {
     objects: Vec<Object>,
 
     lights: Vec<LightObject>,
+
+    // Les lumières surfaciques, créées à partir des objets dont le matériau émet de la lumière
+    #[serde(skip_serializing, skip_deserializing, default = "Vec::new")]
+    area_lights: Vec<AreaLight>,
+
+    // Le choix des lumières évaluées en chaque point, construit sur les lumières du monde
+    #[serde(skip_serializing, skip_deserializing, default = "LightSampler::new_empty")]
+    light_sampler: LightSampler,
+
+    // La manière d'estimer l'éclairage direct, choisie dans la configuration du renderer
+    #[serde(skip_serializing, skip_deserializing, default = "DirectLightingStrategy::default")]
+    direct_lighting: DirectLightingStrategy,
+
+    // La structure d'accélération utilisée pour les calculs d'intersection
+    #[serde(default)]
+    acceleration_structure: AccelerationStructure,
+
+    // Le BVH de haut niveau, construit sur les objets. Chaque objet possède son propre BVH, dans
+    // son espace local.
+    #[serde(skip_serializing, skip_deserializing, default = "BVH::new_empty")]
+    bvh: BVH,
+
+    // Les meshs déjà chargés, partagés entre les objets qui utilisent le même .obj
+    #[serde(skip_serializing, skip_deserializing, default = "MeshRegister::new")]
+    meshes: MeshRegister,
+
+    // Le nombre d'objets dont la géomètrie est chargée : les objets ajoutés ensuite ne le sont
+    // qu'au prochain appel à `load_objects`
+    #[serde(skip_serializing, skip_deserializing)]
+    loaded_objects: usize,
 }
 
 impl World {
@@ -161,13 +556,85 @@ impl World {
             .push(Camera::new(position, target, self.base_vector[2]));
     }
 
-    // Charge la géomètrie de tous les objets. Utilisé uniquement en fin de deserialization.
-    fn load_objects(&mut self) {
-        for obj in &mut self.objects {
-            obj.initialize();
+    // Charge la géomètrie des objets qui ne sont pas encore chargés : en fin de deserialization,
+    // ou après des appels à `add_object`. Chaque .obj n'est lu qu'une seule fois : les objets qui
+    // utilisent le même fichier partagent le même mesh, et donc le même BVH de bas niveau.
+    pub fn load_objects(&mut self) {
+        for index in self.loaded_objects..self.objects.len() {
+            let path = self.objects[index].obj_path().to_string();
+            let mesh = self.load_mesh(&path);
+            self.objects[index].initialize_with_mesh(mesh);
+        }
+        println!("{} objects loaded, using {} distinct meshes",
+                 self.objects.len(),
+                 self.meshes.len());
+        self.loaded_objects = self.objects.len();
+        self.update_area_lights();
+        if !self.area_lights.is_empty() {
+            println!("{} emissive objects used as area lights", self.area_lights.len());
         }
     }
 
+    // Charge les données des lumières qui ne sont pas dans le fichier de scène. Utilisé
+    // uniquement en fin de deserialization.
+    fn load_lights(&mut self) {
+        for light in &mut self.lights {
+            if let Err(e) = light.load() {
+                panic!("Error while loading light. {}", e);
+            }
+        }
+    }
+
+    // Recrée les lumières surfaciques à partir des objets visibles dont le matériau émet de la
+    // lumière. Une émission qui varie sur la surface (texture) n'est pas échantillonnée : l'objet
+    // éclaire alors la scène seulement quand un rayon le touche.
+    fn update_area_lights(&mut self) {
+        self.area_lights = self.objects
+            .iter()
+            .filter(|obj| obj.is_visible())
+            .filter_map(|obj| match obj.material().uniform_emission() {
+                            Some(radiance) => AreaLight::new(obj, radiance),
+                            None => None,
+                        })
+            .collect();
+        self.update_light_sampler();
+    }
+
+    /// Choisit la stratégie d'échantillonnage des lumières.
+    pub fn set_light_sampling(&mut self, strategy: LightSamplingStrategy) {
+        let lights: Vec<&Light> = self.lights().collect();
+        self.light_sampler = LightSampler::new(strategy, &lights);
+    }
+
+    /// Choisit la manière d'estimer l'éclairage direct.
+    pub fn set_direct_lighting(&mut self, strategy: DirectLightingStrategy) {
+        self.direct_lighting = strategy;
+    }
+
+    pub fn direct_lighting(&self) -> DirectLightingStrategy {
+        self.direct_lighting
+    }
+
+    // Reconstruit l'échantillonnage des lumières, dont la liste a changé.
+    fn update_light_sampler(&mut self) {
+        let strategy = self.light_sampler.strategy();
+        self.set_light_sampling(strategy);
+    }
+
+    // Renvoie le mesh correspondant au .obj `path`, en le chargeant s'il n'est pas déjà dans le
+    // cache. Le BVH du mesh est construit au chargement si la structure d'accélération le
+    // demande.
+    fn load_mesh(&mut self, path: &str) -> Arc<Mesh> {
+        let max_primitives_in_node = match self.acceleration_structure {
+            AccelerationStructure::Linear => None,
+            AccelerationStructure::BVH { max_primitives_in_node } => Some(max_primitives_in_node),
+        };
+        self.meshes
+            .entry(path.to_string())
+            .or_insert_with(|| Arc::new(Mesh::load(path, max_primitives_in_node)))
+            .clone()
+    }
+
     // Génére un monde vide
     pub fn new_empty() -> World {
         let base_vector = [Vector3::new(1_f32, 0_f32, 0_f32),
@@ -178,12 +645,89 @@ impl World {
             cameras: vec![],
             objects: vec![],
             lights: vec![],
+            area_lights: vec![],
+            light_sampler: LightSampler::new_empty(),
+            direct_lighting: DirectLightingStrategy::default(),
+            acceleration_structure: AccelerationStructure::default(),
+            bvh: BVH::new_empty(),
+            meshes: MeshRegister::new(),
+            loaded_objects: 0,
+        }
+    }
+
+    // Construit la structure d'accélération choisie, après avoir chargé la géomètrie des objets
+    // qui ne l'est pas encore. Les BVH des meshs sont construits au chargement des meshs, seul le
+    // BVH de haut niveau est construit ici. Doit être rappelé après l'ajout d'objets.
+    pub fn build_acceleration_structure(&mut self) {
+        if self.loaded_objects < self.objects.len() {
+            self.load_objects();
+        }
+        self.bvh = BVH::new_empty();
+
+        match self.acceleration_structure {
+            AccelerationStructure::Linear => {}
+            AccelerationStructure::BVH { max_primitives_in_node } => {
+                let now = Instant::now();
+                let triangle_count: usize = self.meshes
+                    .values()
+                    .map(|mesh| mesh.triangle_count())
+                    .sum();
+
+                // Le BVH de haut niveau est construit avec tous les objets, même ceux qui sont
+                // cachés, afin de pouvoir les faire réapparaître par un simple refit.
+                let bboxes: Vec<BoundingBox> = self.objects
+                    .iter()
+                    .map(|obj| obj.bounding_box().clone())
+                    .collect();
+                self.bvh = BVH::new(&bboxes, max_primitives_in_node);
+                self.refit_top_level();
+
+                println!("BVH built over {} objects and {} distinct triangles in {} s",
+                         self.objects.len(),
+                         triangle_count,
+                         now.elapsed().as_secs() as f64 +
+                         (now.elapsed().subsec_nanos() as f64 * (1.0 / 1_000_000_000_f64)));
+            }
         }
     }
 
-    // Ajoute un objet dans le monde
+    // Met à jour le BVH de haut niveau après qu'un objet a été déplacé, caché ou montré. Les BVH
+    // des meshs ne sont pas touchés.
+    fn refit_top_level(&mut self) {
+        let bboxes: Vec<BoundingBox> = self.objects
+            .iter()
+            .map(|obj| if obj.is_visible() {
+                     obj.bounding_box().clone()
+                 } else {
+                     BoundingBox::new()
+                 })
+            .collect();
+        self.bvh.refit(&bboxes);
+    }
+
+    // Renvoie l'indice de l'objet qui s'appelle `name`
+    pub fn get_object_index(&self, name: &str) -> Option<usize> {
+        self.objects.iter().position(|obj| obj.name() == name)
+    }
+
+    // Permet de modifier un objet (position, rotation, échelle, visibilité). Le BVH de haut
+    // niveau est ensuite mis à jour, sans recharger ni reconstruire la géométrie de l'objet.
+    pub fn update_object<F>(&mut self, index: usize, f: F)
+        where F: FnOnce(&mut Object)
+    {
+        f(&mut self.objects[index]);
+        self.refit_top_level();
+        self.update_area_lights();
+    }
+
+    // Ajoute un objet dans le monde. Sa géomètrie n'est chargée qu'au prochain appel à
+    // `load_objects` ou `build_acceleration_structure`.
     pub fn add_object(&mut self, pos: Vector3f, path: String, name: String) {
-        self.objects.push(Object::new(pos, path, name));
+        let mut obj = Object::new_empty();
+        obj.set_position(pos);
+        obj.set_name(name);
+        obj.set_obj_path(path);
+        self.objects.push(obj);
     }
 
     pub fn get_camera(&self, cam_indice: usize) -> &Camera {
@@ -192,35 +736,193 @@ impl World {
             .expect("Out of bound camera index")
     }
 
+    /// Place les caméras et les objets animés dans leur état à l'instant `time`, en secondes.
+    /// Les objets bougent pendant `frame_duration` à partir de cet instant, ce qui donne le flou
+    /// de mouvement si l'obturateur de la caméra est ouvert.
+    pub fn set_time(&mut self, time: f32, frame_duration: f32) {
+        for camera in &mut self.cameras {
+            camera.set_time(time);
+        }
+        let mut moved = false;
+        for object in &mut self.objects {
+            moved |= object.set_time(time, frame_duration);
+        }
+        if moved {
+            self.refit_top_level();
+            self.update_area_lights();
+        }
+    }
+
+    pub fn cameras(&self) -> &Vec<Camera> {
+        &self.cameras
+    }
+
+    /// L'indice de la caméra désignée par `camera` : son nom, ou à défaut son indice.
+    pub fn find_camera(&self, camera: &str) -> Option<usize> {
+        match self.cameras.iter().position(|cam| cam.name() == camera) {
+            Some(index) => Some(index),
+            None => {
+                camera.parse::<usize>()
+                    .ok()
+                    .and_then(|index| if index < self.cameras.len() {
+                                  Some(index)
+                              } else {
+                                  None
+                              })
+            }
+        }
+    }
+
     pub fn objects(&self) -> &Vec<Object> {
         &self.objects
     }
 
+    // Renvoie la Bounding Box de tous les objets du monde.
+    pub fn bounding_box(&self) -> BoundingBox {
+        if !self.bvh.is_empty() {
+            return self.bvh.bounding_box();
+        }
+        let mut bbox = BoundingBox::new();
+        for obj in &self.objects {
+            bbox.add_bounding_box(obj.bounding_box());
+        }
+        bbox
+    }
+
+    // Renvoie l'intersection la plus proche entre le rayon et les objets visibles du monde.
+    pub fn get_intersection_point(&self, ray: &mut Ray) -> Option<Intersection> {
+        match self.acceleration_structure {
+            AccelerationStructure::Linear => {
+                let mut intersection_point: Option<Intersection> = None;
+                for object in self.objects.iter().filter(|obj| obj.is_visible()) {
+                    if object.bounding_box().intersects(ray) {
+                        if let Some(point) = object.get_intersection_point(ray) {
+                            intersection_point = Some(point);
+                        }
+                    }
+                }
+                intersection_point
+            }
+            AccelerationStructure::BVH { .. } => {
+                let mut closest = None;
+                let objects = &self.objects;
+                self.bvh.intersect(ray, |i, ray| {
+                    if !objects[i].is_visible() {
+                        return false;
+                    }
+                    match objects[i].get_intersection_fragment(ray) {
+                        Some(frag) => {
+                            closest = Some((i, frag));
+                            true
+                        }
+                        None => false,
+                    }
+                });
+                match closest {
+                    Some((obj_index, frag)) => {
+                        Some(self.objects[obj_index].intersection_from_fragment(frag, ray))
+                    }
+                    None => None,
+                }
+            }
+        }
+    }
+
     // Represente le fait qu'un point soit visible par un autre : on revoie true si le rayon
     // intersecte un triangle.
     pub fn is_occluded(&self, ray: &mut Ray) -> bool {
-        for obj in &self.objects {
-            if obj.fast_intersection(ray) {
-                return true;
+        match self.acceleration_structure {
+            AccelerationStructure::Linear => {
+                for obj in &self.objects {
+                    if obj.fast_intersection(ray) {
+                        return true;
+                    }
+                    continue;
+                }
+                false
+            }
+            AccelerationStructure::BVH { .. } => {
+                let objects = &self.objects;
+                self.bvh.occluded(ray, |i, ray| objects[i].fast_intersection(ray))
             }
-            continue;
         }
-        false
     }
 
-    pub fn lights(&self) -> &Vec<LightObject> {
-        &self.lights
+    // Renvoie toutes les lumières du monde : celles de la scène, puis les lumières surfaciques.
+    pub fn lights(&self) -> Lights {
+        Lights {
+            world: self,
+            index: 0,
+        }
+    }
+
+    // Le nombre de lumières du monde, lumières surfaciques comprises.
+    pub fn light_count(&self) -> usize {
+        self.lights.len() + self.area_lights.len()
+    }
+
+    // Renvoie la lumière d'indice `index`, dans l'ordre de `lights`.
+    pub fn light(&self, index: usize) -> &Light {
+        if index < self.lights.len() {
+            self.lights[index].as_trait()
+        } else {
+            &self.area_lights[index - self.lights.len()]
+        }
+    }
+
+    // Renvoie les lumières à évaluer au point `point`, choisies selon la stratégie
+    // d'échantillonnage des lumières à partir du nombre `u` de [0;1[, avec le poids par lequel
+    // multiplier leur contribution.
+    pub fn sample_lights(&self, point: &Vector3f, u: f32) -> Vec<(&Light, f32)> {
+        self.light_sampler
+            .sample(point, u)
+            .into_iter()
+            .map(|(index, weight)| (self.light(index), weight))
+            .collect()
+    }
+
+    // Renvoie la lumière d'environnement de la scène, s'il y en a une : c'est elle qui donne la
+    // radiance des rayons qui ne touchent rien.
+    pub fn environment(&self) -> Option<&EnvironmentLight> {
+        self.lights.iter().filter_map(|light| light.as_environment()).next()
+    }
+}
+
+/// L'itérateur sur les lumières du monde renvoyé par `World::lights`.
+pub struct Lights<'a> {
+    world: &'a World,
+    index: usize,
+}
+
+impl<'a> Iterator for Lights<'a> {
+    type Item = &'a Light;
+
+    fn next(&mut self) -> Option<&'a Light> {
+        if self.index >= self.world.light_count() {
+            return None;
+        }
+        self.index += 1;
+        Some(self.world.light(self.index - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.world.light_count() - self.index;
+        (remaining, Some(remaining))
     }
 }
 
 #[cfg(test)]
 mod test {
-    use scene::Camera;
-    use math::{Vector3f, VectorialOperations};
+    use std::f32;
+    use scene::{Camera, Projection, World, camera_file_path};
+    use sampler::Sample;
+    use math::{Vector2f, Vector3f, VectorialOperations};
+    use ray::Ray;
 
     #[test]
     fn test_camera_canvas_base() {
         let cam: Camera = Camera {
+            name: String::new(),
             world_position: Vector3f {
                 x: 4.0,
                 y: 4.0,
@@ -238,6 +940,13 @@ mod test {
             },
             fov: 90.0,
             clip: 2.0_f32.sqrt(),
+            aperture_radius: 0.0,
+            focus_distance: None,
+            autofocus: false,
+            projection: Projection::Perspective,
+            animation: None,
+            shutter_open: 0.0,
+            shutter_close: 0.0,
         };
 
         let (origin, vec1, vec2) = cam.get_canvas_base(1.0);
@@ -264,4 +973,141 @@ mod test {
                      })
                     .norm() < 0.001);
     }
+
+    #[test]
+    fn test_thin_lens_camera() {
+        let mut cam = Camera::new(Vector3f::new(0.0, 0.0, 0.0),
+                                  Vector3f::new(0.0, 5.0, 0.0),
+                                  Vector3f::new(0.0, 0.0, 1.0));
+        assert!((cam.get_focus_distance() - 5.0).abs() < 1e-5);
+
+        // Tous les rayons d'un même sample se croisent sur le plan net, quel que soit le point de
+        // l'objectif d'où ils partent.
+        cam.set_lens(0.5, Some(3.0));
+        let sample = Sample::new(30.0, 70.0);
+        let focus_point = |lens: Vector2f| -> Vector3f {
+            let ray = cam.create_ray_from_sample(&sample, lens, 0.0, 1.0, 100.0, 100.0).unwrap();
+            ray.origin() + ray.slope() * ((3.0 - ray.origin().y) / ray.slope().y)
+        };
+        let center = focus_point(Vector2f::new(0.5, 0.5));
+        for &(u, v) in &[(0.1, 0.2), (0.9, 0.4), (0.3, 0.95)] {
+            assert!((focus_point(Vector2f::new(u, v)) - center).norm() < 1e-4);
+        }
+        let pinhole = Camera::new(Vector3f::new(0.0, 0.0, 0.0),
+                                  Vector3f::new(0.0, 5.0, 0.0),
+                                  Vector3f::new(0.0, 0.0, 1.0))
+            .create_ray_from_sample(&sample, Vector2f::new(0.1, 0.2), 0.0, 1.0, 100.0, 100.0)
+            .unwrap();
+        assert!((pinhole.origin() + pinhole.slope() * (3.0 / pinhole.slope().y) - center)
+                    .norm() < 1e-4);
+
+        // Les rayons partent de points différents de l'objectif.
+        let lens = Vector2f::new(0.9, 0.4);
+        let ray = cam.create_ray_from_sample(&sample, lens, 0.0, 1.0, 100.0, 100.0).unwrap();
+        assert!(ray.origin().norm() > 0.1 && ray.origin().norm() <= 0.5 + 1e-5);
+    }
+
+    #[test]
+    fn test_camera_projections() {
+        let mut cam = Camera::new(Vector3f::new(0.0, 0.0, 0.0),
+                                  Vector3f::new(1.0, 0.0, 0.0),
+                                  Vector3f::new(0.0, 0.0, 1.0));
+        let direction = |cam: &Camera, x: f32, y: f32| -> Option<Vector3f> {
+            let lens = Vector2f::new(0.5, 0.5);
+            cam.create_ray_from_sample(&Sample::new(x, y), lens, 0.0, 2.0, 200.0, 100.0)
+                .map(|ray| ray.slope() / ray.slope().norm())
+        };
+        let close = |a: Vector3f, b: Vector3f| (a - b).norm() < 1e-4;
+
+        cam.set_projection(Projection::Orthographic { width: 4.0 });
+        let ray = cam.create_ray_from_sample(&Sample::new(0.0, 0.0),
+                                     Vector2f::new(0.5, 0.5),
+                                     0.0,
+                                     2.0,
+                                     200.0,
+                                     100.0)
+            .unwrap();
+        assert!(close(ray.origin(), Vector3f::new(0.0, 2.0, 1.0)));
+        assert!(close(direction(&cam, 0.0, 0.0).unwrap(), Vector3f::new(1.0, 0.0, 0.0)));
+
+        // Le bord du cercle d'un fisheye à 180° regarde à 90° de l'axe.
+        cam.set_projection(Projection::Fisheye { max_angle: 180.0 });
+        assert!(close(direction(&cam, 100.0, 50.0).unwrap(), Vector3f::new(1.0, 0.0, 0.0)));
+        assert!(close(direction(&cam, 100.0, 0.0).unwrap(), Vector3f::new(0.0, 0.0, 1.0)));
+        assert!(close(direction(&cam, 150.0, 50.0).unwrap(), Vector3f::new(0.0, -1.0, 0.0)));
+        assert!(direction(&cam, 0.0, 0.0).is_none());
+
+        cam.set_projection(Projection::Equirectangular);
+        assert!(close(direction(&cam, 100.0, 50.0).unwrap(), Vector3f::new(1.0, 0.0, 0.0)));
+        assert!(close(direction(&cam, 0.0, 50.0).unwrap(), Vector3f::new(-1.0, 0.0, 0.0)));
+        assert!(close(direction(&cam, 150.0, 50.0).unwrap(), Vector3f::new(0.0, -1.0, 0.0)));
+        let zenith = direction(&cam, 30.0, 0.0).unwrap();
+        assert!(close(zenith, Vector3f::new(0.0, 0.0, 1.0)));
+        assert!((direction(&cam, 100.0, 25.0).unwrap().z - (f32::consts::PI / 4.0).cos()).abs() <
+                1e-4);
+
+        // Avec un objectif, les rayons panoramiques partent de points différents mais se croisent
+        // à `focus_distance` dans la direction du pixel.
+        cam.set_lens(0.5, Some(3.0));
+        let fisheye = Projection::Fisheye { max_angle: 180.0 };
+        for projection in &[fisheye, Projection::Equirectangular] {
+            cam.set_projection(*projection);
+            let ray_at = |lens: Vector2f| {
+                cam.create_ray_from_sample(&Sample::new(150.0, 50.0), lens, 0.0, 2.0, 200.0, 100.0)
+                    .unwrap()
+            };
+            let (a, b) = (ray_at(Vector2f::new(0.1, 0.2)), ray_at(Vector2f::new(0.9, 0.7)));
+            assert!(!close(a.origin(), b.origin()));
+            assert!(close(a.origin() + a.slope() * 3.0, Vector3f::new(0.0, -3.0, 0.0)));
+            assert!(close(b.origin() + b.slope() * 3.0, Vector3f::new(0.0, -3.0, 0.0)));
+        }
+    }
+
+    #[test]
+    fn test_camera_selection() {
+        let mut world = World::new_empty();
+        let origin = Vector3f::new(0.0, 0.0, 0.0);
+        let target = Vector3f::new(1.0, 0.0, 0.0);
+        world.add_camera(origin, target);
+        world.add_camera(origin, target);
+        world.cameras[1].set_name("front".to_string());
+        assert_eq!(world.find_camera("front"), Some(1));
+        assert_eq!(world.find_camera("0"), Some(0));
+        assert_eq!(world.find_camera("2"), None);
+        assert_eq!(world.find_camera("back"), None);
+
+        assert_eq!(camera_file_path("out_{index}.png", 1, world.get_camera(1)),
+                   "out_1.png");
+        assert_eq!(camera_file_path("renders/{name}.png", 1, world.get_camera(1)),
+                   "renders/front.png");
+        assert_eq!(camera_file_path("renders/out.png", 0, world.get_camera(0)),
+                   "renders/out_0.png");
+        assert_eq!(camera_file_path("./out", 1, world.get_camera(1)), "./out_front");
+    }
+
+    #[test]
+    fn test_add_object() {
+        // L'ajout d'un objet ne charge pas sa géomètrie.
+        let mut world = World::new_empty();
+        world.add_object(Vector3f::new(0.0, 0.0, 0.0),
+                         "models/does_not_exist.obj".to_string(),
+                         "missing".to_string());
+        assert_eq!(world.objects().len(), 1);
+
+        // Les objets ajoutés après la construction du BVH y entrent quand on le reconstruit.
+        let mut world = World::new_empty();
+        world.add_object(Vector3f::new(0.0, 0.0, 0.0),
+                         "models/plane_no_uv.obj".to_string(),
+                         "first".to_string());
+        world.build_acceleration_structure();
+        world.add_object(Vector3f::new(10.0, 0.0, 0.0),
+                         "models/plane_no_uv.obj".to_string(),
+                         "second".to_string());
+        world.build_acceleration_structure();
+        assert_eq!(world.objects()[1].triangle_count(), world.objects()[0].triangle_count());
+        let mut ray = Ray::new(Vector3f::new(10.0, -1.0, 0.0), Vector3f::new(0.0, 1.0, 0.0));
+        ray.max_t = 10.0;
+        let hit = world.get_intersection_point(&mut ray).unwrap();
+        assert!((hit.fragment().position.x - 10.0).abs() < 1e-4);
+    }
 }
@@ -0,0 +1,119 @@
+/// Une table d'alias (méthode de Walker), qui permet de tirer un indice parmi `n` avec des
+/// probabilités proportionnelles à des poids donnés, en temps constant.
+
+use std::cmp;
+
+#[derive(Debug, Clone)]
+struct AliasBin {
+    // La probabilité de garder l'indice de la case plutôt que son alias
+    threshold: f32,
+    alias: usize,
+    // La probabilité de tirer l'indice de la case
+    pmf: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct AliasTable {
+    bins: Vec<AliasBin>,
+}
+
+impl AliasTable {
+    /// Crée la table à partir des poids positifs `weights`. Si tous les poids sont nuls, les
+    /// indices sont équiprobables.
+    pub fn new(weights: &[f32]) -> Self {
+        let n = weights.len();
+        let total: f32 = weights.iter().map(|weight| f32::max(0.0, *weight)).sum();
+        let pmfs: Vec<f32> = weights.iter()
+            .map(|weight| if total > 0.0 {
+                     f32::max(0.0, *weight) / total
+                 } else {
+                     1.0 / n as f32
+                 })
+            .collect();
+
+        let mut bins: Vec<AliasBin> = pmfs.iter()
+            .map(|pmf| {
+                     AliasBin {
+                         threshold: 1.0,
+                         alias: 0,
+                         pmf: *pmf,
+                     }
+                 })
+            .collect();
+
+        // On sépare les cases dont la probabilité (multipliée par n) est inférieure à 1 de
+        // celles dont elle est supérieure, puis on complète chaque petite case avec une grande.
+        let mut scaled: Vec<f32> = pmfs.iter().map(|pmf| pmf * n as f32).collect();
+        let mut small: Vec<usize> = (0..n).filter(|&i| scaled[i] < 1.0).collect();
+        let mut large: Vec<usize> = (0..n).filter(|&i| scaled[i] >= 1.0).collect();
+        while !small.is_empty() && !large.is_empty() {
+            let less = small.pop().unwrap();
+            let more = large.pop().unwrap();
+            bins[less].threshold = scaled[less];
+            bins[less].alias = more;
+            scaled[more] -= 1.0 - scaled[less];
+            if scaled[more] < 1.0 {
+                small.push(more);
+            } else {
+                large.push(more);
+            }
+        }
+        // Les erreurs d'arrondi peuvent laisser des cases d'un seul côté : elles sont pleines.
+        for i in small.into_iter().chain(large.into_iter()) {
+            bins[i].threshold = 1.0;
+        }
+        AliasTable { bins: bins }
+    }
+
+    /// Le nombre d'indices.
+    pub fn len(&self) -> usize {
+        self.bins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bins.is_empty()
+    }
+
+    /// Tire un indice à partir de `u` dans [0;1[ et renvoie sa probabilité.
+    pub fn sample(&self, u: f32) -> (usize, f32) {
+        let n = self.len();
+        let scaled = u * n as f32;
+        let bin = cmp::min(scaled as usize, n - 1);
+        // La position de u dans la case est elle-même uniforme : on la réutilise.
+        let up = f32::min(scaled - bin as f32, 1.0);
+        let index = if up < self.bins[bin].threshold {
+            bin
+        } else {
+            self.bins[bin].alias
+        };
+        (index, self.bins[index].pmf)
+    }
+
+    /// La probabilité de tirer l'indice `index`.
+    pub fn pmf(&self, index: usize) -> f32 {
+        self.bins[index].pmf
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AliasTable;
+
+    #[test]
+    fn test_alias_table() {
+        let weights = [1.0, 0.0, 3.0, 4.0];
+        let table = AliasTable::new(&weights);
+        let n = 8000;
+        let mut counts = [0; 4];
+        for i in 0..n {
+            let (index, pmf) = table.sample((i as f32 + 0.5) / n as f32);
+            assert_eq!(pmf, table.pmf(index));
+            counts[index] += 1;
+        }
+        for i in 0..4 {
+            let expected = weights[i] / 8.0;
+            assert!((counts[i] as f32 / n as f32 - expected).abs() < 1e-3);
+            assert!((table.pmf(i) - expected).abs() < 1e-6);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+/// Des distributions constantes par morceaux, qui permettent de tirer des points avec une densité
+/// proportionnelle à une fonction tabulée (par exemple la luminance des pixels d'une image).
+
+use std::cmp;
+use std::f32;
+use math::Vector2f;
+
+/// Une distribution sur [0;1[ proportionnelle à une fonction constante sur `n` intervalles de
+/// même longueur.
+#[derive(Debug, Clone)]
+pub struct Distribution1D {
+    func: Vec<f32>,
+    // La fonction de répartition, avec n + 1 valeurs de 0 à 1
+    cdf: Vec<f32>,
+    integral: f32,
+}
+
+impl Distribution1D {
+    /// Crée la distribution à partir des valeurs positives de la fonction sur chaque intervalle.
+    /// Si la fonction est nulle partout, la distribution est uniforme.
+    pub fn new(func: Vec<f32>) -> Self {
+        let n = func.len();
+        let mut cdf = Vec::with_capacity(n + 1);
+        cdf.push(0.0);
+        for i in 0..n {
+            let previous = cdf[i];
+            cdf.push(previous + func[i] / n as f32);
+        }
+        let integral = cdf[n];
+        for (i, value) in cdf.iter_mut().enumerate() {
+            *value = if integral > 0.0 {
+                *value / integral
+            } else {
+                i as f32 / n as f32
+            };
+        }
+        Distribution1D {
+            func: func,
+            cdf: cdf,
+            integral: integral,
+        }
+    }
+
+    /// Le nombre d'intervalles.
+    pub fn count(&self) -> usize {
+        self.func.len()
+    }
+
+    /// L'intégrale de la fonction sur [0;1].
+    pub fn integral(&self) -> f32 {
+        self.integral
+    }
+
+    /// Tire un point de [0;1[ à partir de `u` dans [0;1[. Renvoie le point, sa densité et
+    /// l'indice de l'intervalle qui le contient.
+    pub fn sample_continuous(&self, u: f32) -> (f32, f32, usize) {
+        // Le dernier indice i tel que cdf[i] <= u
+        let mut low = 0;
+        let mut high = self.count();
+        while high - low > 1 {
+            let middle = (low + high) / 2;
+            if self.cdf[middle] <= u {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        let offset = low;
+        let width = self.cdf[offset + 1] - self.cdf[offset];
+        let du = if width > 0.0 {
+            (u - self.cdf[offset]) / width
+        } else {
+            0.0
+        };
+        let x = f32::min((offset as f32 + du) / self.count() as f32,
+                         1.0 - f32::EPSILON);
+        (x, self.pdf_at(offset), offset)
+    }
+
+    /// La densité du point `x` de [0;1[.
+    pub fn pdf(&self, x: f32) -> f32 {
+        let offset = f32::max(0.0, x * self.count() as f32) as usize;
+        self.pdf_at(cmp::min(offset, self.count() - 1))
+    }
+
+    fn pdf_at(&self, offset: usize) -> f32 {
+        if self.integral > 0.0 {
+            self.func[offset] / self.integral
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Une distribution sur [0;1[ x [0;1[ proportionnelle à une fonction constante sur une grille :
+/// on tire d'abord la ligne selon la distribution marginale, puis la colonne selon la
+/// distribution conditionnelle de la ligne.
+#[derive(Debug, Clone)]
+pub struct Distribution2D {
+    conditionals: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// Crée la distribution à partir des valeurs de la fonction, rangées par lignes :
+    /// `func[v][u]`.
+    pub fn new(func: Vec<Vec<f32>>) -> Self {
+        let conditionals: Vec<Distribution1D> = func.into_iter()
+            .map(Distribution1D::new)
+            .collect();
+        let marginal = Distribution1D::new(conditionals.iter()
+                                               .map(|distribution| distribution.integral())
+                                               .collect());
+        Distribution2D {
+            conditionals: conditionals,
+            marginal: marginal,
+        }
+    }
+
+    /// Tire un point (u, v) à partir d'un point de [0;1[ x [0;1[ et renvoie sa densité.
+    pub fn sample_continuous(&self, u: Vector2f) -> (Vector2f, f32) {
+        let (v, pdf_v, row) = self.marginal.sample_continuous(u.y);
+        let (u, pdf_u, _) = self.conditionals[row].sample_continuous(u.x);
+        (Vector2f::new(u, v), pdf_u * pdf_v)
+    }
+
+    /// La densité du point (u, v).
+    pub fn pdf(&self, point: &Vector2f) -> f32 {
+        let rows = self.conditionals.len();
+        let row = cmp::min(f32::max(0.0, point.y * rows as f32) as usize, rows - 1);
+        self.conditionals[row].pdf(point.x) * self.marginal.pdf(point.y)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use math::Vector2f;
+    use super::{Distribution1D, Distribution2D};
+
+    #[test]
+    fn test_distribution_1d() {
+        let distribution = Distribution1D::new(vec![1.0, 0.0, 3.0]);
+        assert!((distribution.integral() - 4.0 / 3.0).abs() < 1e-6);
+        // Le premier quart de la masse est dans le premier intervalle.
+        let (x, pdf, offset) = distribution.sample_continuous(0.125);
+        assert_eq!(offset, 0);
+        assert!((x - 1.0 / 6.0).abs() < 1e-6);
+        assert!((pdf - 0.75).abs() < 1e-6);
+        // L'intervalle de valeur nulle n'est jamais tiré.
+        let (x, pdf, offset) = distribution.sample_continuous(0.25);
+        assert_eq!(offset, 2);
+        assert!((x - 2.0 / 3.0).abs() < 1e-6);
+        assert!((pdf - 2.25).abs() < 1e-6);
+        assert_eq!(distribution.pdf(0.5), 0.0);
+
+        let uniform = Distribution1D::new(vec![0.0, 0.0]);
+        assert_eq!(uniform.sample_continuous(0.75).0, 0.75);
+        assert_eq!(uniform.pdf(0.1), 1.0);
+    }
+
+    #[test]
+    fn test_distribution_2d() {
+        let distribution = Distribution2D::new(vec![vec![1.0, 1.0], vec![0.0, 6.0]]);
+        let n = 32;
+        let mut sum = 0.0;
+        for i in 0..n {
+            for j in 0..n {
+                let u = Vector2f::new((i as f32 + 0.5) / n as f32, (j as f32 + 0.5) / n as f32);
+                let (point, pdf) = distribution.sample_continuous(u);
+                assert!((distribution.pdf(&point) - pdf).abs() < 1e-5);
+                assert!(!(point.x < 0.5 && point.y >= 0.5));
+                sum += 1.0 / pdf;
+            }
+        }
+        // L'estimateur de l'aire du support (3/4 du carré) à partir des densités
+        assert!((sum / (n * n) as f32 - 0.75).abs() < 1e-3);
+    }
+}
@@ -1,6 +1,9 @@
 pub mod monte_carlo;
+pub mod distribution;
+pub mod alias_table;
 
 use math::{VectorialOperations, Vector3f};
+use std::f32;
 
 /// Une fonction qui à partir de 2 vecteurs renvoies 2 nouveaux vecteurs orthogonaux selon le
 /// procédé d'orthogonalisation de Schmidt (POS) .
@@ -11,3 +14,68 @@ pub fn orthogonalize_vec(u: Vector3f, v: Vector3f) -> (Vector3f, Vector3f) {
     let v2 = u - u.dot_product_ref(&v) / u.dot_product_ref(&u) * u;
     (u, v2)
 }
+
+/// Renvoie la direction réfléchie de `v` par rapport à la normale unitaire `n`.
+pub fn reflect(v: &Vector3f, n: &Vector3f) -> Vector3f {
+    *v - 2.0 * v.dot_product_ref(n) * *n
+}
+
+/// Renvoie la direction réfractée du vecteur unitaire `v` à travers une surface de normale
+/// unitaire `n` (orientée du côté d'où vient `v`), où `eta` est le rapport des indices de
+/// réfraction du milieu incident et du milieu transmis. Renvoie `None` en cas de réflexion
+/// totale interne.
+pub fn refract(v: &Vector3f, n: &Vector3f, eta: f32) -> Option<Vector3f> {
+    let cos_i = -v.dot_product_ref(n);
+    let sin2_t = eta * eta * f32::max(0.0, 1.0 - cos_i * cos_i);
+    if sin2_t > 1.0 {
+        None
+    } else {
+        let cos_t = (1.0 - sin2_t).sqrt();
+        Some(eta * *v + (eta * cos_i - cos_t) * *n)
+    }
+}
+
+/// Le coefficient de Fresnel d'un diélectrique : la proportion de lumière réfléchie par une
+/// surface entre un milieu d'indice `eta_i` et un milieu d'indice `eta_t`, pour un rayon
+/// incident qui fait un angle de cosinus `cos_i` avec la normale.
+pub fn fresnel_dielectric(cos_i: f32, eta_i: f32, eta_t: f32) -> f32 {
+    let cos_i = f32::min(1.0, cos_i.abs());
+    let sin_t = eta_i / eta_t * f32::max(0.0, 1.0 - cos_i * cos_i).sqrt();
+    if sin_t >= 1.0 {
+        // Réflexion totale interne
+        return 1.0;
+    }
+    let cos_t = f32::max(0.0, 1.0 - sin_t * sin_t).sqrt();
+    let r_parallel = (eta_t * cos_i - eta_i * cos_t) / (eta_t * cos_i + eta_i * cos_t);
+    let r_perpendicular = (eta_i * cos_i - eta_t * cos_t) / (eta_i * cos_i + eta_t * cos_t);
+    (r_parallel * r_parallel + r_perpendicular * r_perpendicular) / 2.0
+}
+
+#[cfg(test)]
+mod test {
+    use math::{Vector3f, VectorialOperations};
+    use super::{reflect, refract, fresnel_dielectric};
+
+    #[test]
+    fn test_fresnel_dielectric() {
+        // Incidence normale air -> verre : ((1.5 - 1) / (1.5 + 1))² = 0.04
+        assert!((fresnel_dielectric(1.0, 1.0, 1.5) - 0.04).abs() < 1e-5);
+        // Réflexion totale interne du verre vers l'air en incidence rasante
+        assert_eq!(fresnel_dielectric(0.1, 1.5, 1.0), 1.0);
+    }
+
+    #[test]
+    fn test_reflect_refract() {
+        let n = Vector3f::new(0.0, 0.0, 1.0);
+        let v = Vector3f::new(1.0, 0.0, -1.0) / 2f32.sqrt();
+        let r = reflect(&v, &n);
+        assert!((r - Vector3f::new(1.0, 0.0, 1.0) / 2f32.sqrt()).norm() < 1e-5);
+
+        // Sans changement d'indice, le rayon n'est pas dévié
+        let t = refract(&v, &n, 1.0).unwrap();
+        assert!((t - v).norm() < 1e-5);
+
+        // À 45°, le passage du verre vers l'air provoque une réflexion totale interne
+        assert!(refract(&v, &n, 1.5).is_none());
+    }
+}
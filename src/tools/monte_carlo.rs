@@ -20,9 +20,10 @@ fn generate_random_point() -> Vector2f {
                   range.ind_sample(&mut random_number_generator))
 }
 
-/// Crée un échantillon sur le disque unité, avec une distribution uniforme.
-/// * `u` - C'est un point dont chacune des coordonnées doit appartenir à [-1;1]
-fn sample_disk_concentric(u: Vector2f) -> Vector2f {
+/// Crée un échantillon sur le disque unité, avec une distribution uniforme. La projection
+/// concentrique de Shirley conserve la stratification des points de `u`.
+/// * `u` - C'est un point dont chacune des coordonnées doit appartenir à [0;1[
+pub fn sample_disk_concentric(u: Vector2f) -> Vector2f {
     let offset: Vector2f = 2.0 * u - Vector2f::new(1f32, 1f32);
     if offset.x == 0.0 && offset.y == 0.0 {
         Vector2f::new(0.0, 0.0)
@@ -61,8 +62,15 @@ fn generate_sample_uniform_hemisphere(u: Vector2f) -> Vector3f {
     Vector3f::new(r * f32::cos(phi), r * f32::sin(phi), z)
 }
 
+/// Tire une direction selon une distribution en cosinus sur l'hémisphère orienté selon l'axe z
+/// du système de coordonnées de shading.
+/// * `u` - Un point dont les coordonnées sont comprises entre 0 et 1.
+pub fn sample_cosine_direction(u: Vector2f) -> Vector3f {
+    generate_sample_cosine_hemisphere(u)
+}
+
 /// Renvoie la densité de probabibilité pour un angle donnée.
-fn probability_density_function_cosine_hemisphere(cos_theta: f32) -> f32 {
+pub fn probability_density_function_cosine_hemisphere(cos_theta: f32) -> f32 {
     cos_theta * f32::consts::FRAC_1_PI
 }
 